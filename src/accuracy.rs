@@ -0,0 +1,122 @@
+//! Receiver accuracy estimates from vendor sentences, Garmin `$PGRME`/`$PGRMZ` and
+//! u-blox `$PUBX,00`, and from the standard pseudorange error statistics in GST.
+
+use std::time::Duration;
+
+use crate::{sentence::Sentence, status::StatusValue};
+
+/// One foot in meters.
+const FOOT: f64 = 0.3048;
+
+#[derive(Debug)]
+pub struct Accuracy {
+    /// Estimated horizontal position error in meters.
+    pub horizontal: StatusValue<f64>,
+    /// Estimated vertical position error in meters.
+    pub vertical: StatusValue<f64>,
+    /// Estimated overall (spherical) position error in meters.
+    pub spherical: StatusValue<f64>,
+    /// Altitude reported by `$PGRMZ` in meters, barometric on units with an altimeter.
+    pub garmin_altitude: StatusValue<f64>,
+    /// Sentence the estimates came from.
+    pub source: StatusValue<&'static str>,
+    /// Standard deviations of latitude, longitude and altitude error in meters, from GST.
+    pub sigma_lat: StatusValue<f64>,
+    pub sigma_lon: StatusValue<f64>,
+    pub sigma_alt: StatusValue<f64>,
+    /// Error ellipse from GST.
+    pub ellipse: StatusValue<Ellipse>,
+}
+
+/// Horizontal position error as an ellipse of one standard deviation.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Copy, Debug)]
+pub struct Ellipse {
+    /// Semi-major axis in meters.
+    pub semi_major: f64,
+    /// Semi-minor axis in meters.
+    pub semi_minor: f64,
+    /// Direction of the semi-major axis in degrees from true north.
+    pub orientation: f64,
+}
+
+impl Accuracy {
+    pub fn new(timeout: Duration) -> Accuracy {
+        Accuracy {
+            horizontal: StatusValue::new(timeout),
+            vertical: StatusValue::new(timeout),
+            spherical: StatusValue::new(timeout),
+            garmin_altitude: StatusValue::new(timeout),
+            source: StatusValue::new(timeout),
+            sigma_lat: StatusValue::new(timeout),
+            sigma_lon: StatusValue::new(timeout),
+            sigma_alt: StatusValue::new(timeout),
+            ellipse: StatusValue::new(timeout),
+        }
+    }
+
+    pub fn update(&mut self, sentence: &Sentence) {
+        match sentence.kind {
+            // HPE, M, VPE, M, EPE, M
+            "PGRME" => {
+                self.horizontal.update(meters(sentence, 0));
+                self.vertical.update(meters(sentence, 2));
+                self.spherical.update(meters(sentence, 4));
+                self.source.update("PGRME");
+            }
+            // altitude, f/m, fix dimension
+            "PGRMZ" => self.garmin_altitude.update(meters(sentence, 0)),
+            // 00, time, lat, N/S, lon, E/W, alt, nav status, hAcc, vAcc, ...
+            "PUBX" if sentence.fields.first() == Some(&"00") => {
+                self.horizontal.update(meters(sentence, 8));
+                self.vertical.update(meters(sentence, 9));
+                self.spherical.update(None);
+                self.source.update("PUBX");
+            }
+            // time, RMS, semi-major, semi-minor, orientation, sigma lat, lon, alt
+            "GST" => {
+                let number = |index: usize| sentence.fields.get(index)?.parse::<f64>().ok();
+                self.sigma_lat.update(number(5));
+                self.sigma_lon.update(number(6));
+                self.sigma_alt.update(number(7));
+                self.ellipse
+                    .update(number(2).zip(number(3)).map(|(major, minor)| Ellipse {
+                        semi_major: major.max(minor),
+                        semi_minor: major.min(minor),
+                        orientation: number(4).unwrap_or(0.0),
+                    }));
+            }
+            _ => {}
+        }
+    }
+
+    /// Horizontal error around the position: the ellipse from GST, else one from the
+    /// latitude and longitude deviations, else a circle of the horizontal error estimate.
+    #[cfg(feature = "tui")]
+    pub fn error_ellipse(&self) -> Option<Ellipse> {
+        if let Some(&ellipse) = self.ellipse.get() {
+            return Some(ellipse);
+        }
+        if let (Some(&lat), Some(&lon)) = (self.sigma_lat.get(), self.sigma_lon.get()) {
+            return Some(Ellipse {
+                semi_major: lat.max(lon),
+                semi_minor: lat.min(lon),
+                orientation: if lat >= lon { 0.0 } else { 90.0 },
+            });
+        }
+        self.horizontal.get().map(|&radius| Ellipse {
+            semi_major: radius,
+            semi_minor: radius,
+            orientation: 0.0,
+        })
+    }
+}
+
+/// Reads a value at `index`, converting from feet when the following unit field is `f`.
+fn meters(sentence: &Sentence, index: usize) -> Option<f64> {
+    let value = sentence.fields.get(index)?.parse::<f64>().ok()?;
+    match sentence.fields.get(index + 1) {
+        Some(&"f") | Some(&"F") => Some(value * FOOT),
+        _ => Some(value),
+    }
+}
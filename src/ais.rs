@@ -0,0 +1,322 @@
+//! AIS targets decoded from `!AIVDM` sentences.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{geo, sentence::Sentence};
+
+#[derive(Clone, Debug)]
+pub struct AisTarget {
+    pub mmsi: u32,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// Speed over ground in knots.
+    pub sog: Option<f64>,
+    pub cog: Option<f64>,
+    pub heading: Option<f64>,
+    pub name: Option<String>,
+    pub call_sign: Option<String>,
+    pub ship_type: Option<u32>,
+    /// Length and beam in meters, from the GPS antenna offsets.
+    pub dimensions: Option<(u32, u32)>,
+    pub destination: Option<String>,
+    pub updated_at: Instant,
+}
+
+impl AisTarget {
+    fn new(mmsi: u32) -> AisTarget {
+        AisTarget {
+            mmsi,
+            lat: None,
+            lon: None,
+            sog: None,
+            cog: None,
+            heading: None,
+            name: None,
+            call_sign: None,
+            ship_type: None,
+            dimensions: None,
+            destination: None,
+            updated_at: Instant::now(),
+        }
+    }
+
+    pub fn position(&self) -> Option<(f64, f64)> {
+        self.lat.zip(self.lon)
+    }
+
+    /// Applies a position report given the bit offsets of its sog, lon, lat, cog and
+    /// heading fields.
+    fn apply_position(&mut self, payload: &Payload, offsets: (usize, usize, usize, usize, usize)) {
+        let (sog, lon, lat, cog, heading) = offsets;
+        self.sog = payload
+            .unsigned(sog, 10)
+            .filter(|&v| v != 1023)
+            .map(|v| f64::from(v) / 10.0);
+        self.lon = payload
+            .signed(lon, 28)
+            .map(|v| f64::from(v) / 600_000.0)
+            .filter(|v| v.abs() <= 180.0);
+        self.lat = payload
+            .signed(lat, 27)
+            .map(|v| f64::from(v) / 600_000.0)
+            .filter(|v| v.abs() <= 90.0);
+        self.cog = payload
+            .unsigned(cog, 12)
+            .filter(|&v| v < 3600)
+            .map(|v| f64::from(v) / 10.0);
+        self.heading = payload
+            .unsigned(heading, 9)
+            .filter(|&v| v < 360)
+            .map(f64::from);
+    }
+
+    /// East/north velocity in m/s.
+    fn velocity(&self) -> Option<(f64, f64)> {
+        match (self.sog?, self.cog) {
+            (sog, Some(cog)) => Some(geo::displacement(cog, sog * geo::KNOT)),
+            (sog, None) if sog < 0.1 => Some((0.0, 0.0)),
+            _ => None,
+        }
+    }
+
+    /// Closest point of approach relative to `own`.
+    pub fn cpa(&self, own: &OwnShip) -> Option<Cpa> {
+        let position = geo::local_xy(own.position, self.position()?);
+        let (vx, vy) = self.velocity()?;
+        let (own_vx, own_vy) = geo::displacement(own.cog, own.sog * geo::KNOT);
+        let (distance, time) = geo::closest_approach(position, (vx - own_vx, vy - own_vy));
+        Some(Cpa { distance, time })
+    }
+}
+
+/// Own-ship position and velocity used as the reference for CPA.
+#[derive(Clone, Copy, Debug)]
+pub struct OwnShip {
+    pub position: (f64, f64),
+    /// Speed over ground in knots.
+    pub sog: f64,
+    pub cog: f64,
+}
+
+/// Closest point of approach.
+#[derive(Clone, Copy, Debug)]
+pub struct Cpa {
+    /// Distance at closest approach in meters.
+    pub distance: f64,
+    /// Seconds until closest approach; negative when it has already passed.
+    pub time: f64,
+}
+
+/// A multi-sentence message waiting for its remaining fragments.
+#[derive(Debug)]
+struct Fragments {
+    total: u16,
+    received: u16,
+    payload: String,
+}
+
+#[derive(Debug)]
+pub struct AisTargets {
+    targets: BTreeMap<u32, AisTarget>,
+    /// Incomplete messages keyed by sequential message id and channel.
+    fragments: HashMap<(String, String), Fragments>,
+    /// CPA distance in meters below which a target is dangerous.
+    cpa_alarm: f64,
+    /// Only closest approaches within this time are alarmed.
+    tcpa_alarm: Duration,
+    /// Targets silent for longer than this are dropped.
+    expiry: Duration,
+}
+
+impl AisTargets {
+    pub fn new(cpa_alarm: f64, tcpa_alarm: Duration, expiry: Duration) -> AisTargets {
+        AisTargets {
+            targets: BTreeMap::new(),
+            fragments: HashMap::new(),
+            cpa_alarm,
+            tcpa_alarm,
+            expiry,
+        }
+    }
+
+    /// Applies a single VDM sentence, decoding the message once all of its fragments
+    /// arrived.
+    pub fn update(&mut self, vdm: &Sentence) {
+        // total, number, sequential id, channel, payload, fill bits
+        let (Some(Ok(total)), Some(Ok(number)), Some(payload)) = (
+            vdm.fields.first().map(|f| f.parse::<u16>()),
+            vdm.fields.get(1).map(|f| f.parse::<u16>()),
+            vdm.fields.get(4),
+        ) else {
+            return;
+        };
+
+        let payload = if total == 1 {
+            payload.to_string()
+        } else {
+            let key = (
+                vdm.fields.get(2).unwrap_or(&"").to_string(),
+                vdm.fields.get(3).unwrap_or(&"").to_string(),
+            );
+            if number == 1 {
+                self.fragments.insert(
+                    key.clone(),
+                    Fragments {
+                        total,
+                        received: 0,
+                        payload: String::new(),
+                    },
+                );
+            }
+            let Some(fragments) = self.fragments.get_mut(&key) else {
+                return;
+            };
+            if fragments.total != total || fragments.received + 1 != number {
+                self.fragments.remove(&key);
+                return;
+            }
+            fragments.received = number;
+            fragments.payload.push_str(payload);
+            if number != total {
+                return;
+            }
+            match self.fragments.remove(&key) {
+                Some(fragments) => fragments.payload,
+                None => return,
+            }
+        };
+
+        if let Some(payload) = Payload::dearmor(&payload) {
+            self.apply(&payload);
+        }
+    }
+
+    /// Merges a decoded message into the target with the same MMSI.
+    fn apply(&mut self, payload: &Payload) {
+        let (Some(kind), Some(mmsi)) = (payload.unsigned(0, 6), payload.unsigned(8, 30)) else {
+            return;
+        };
+        if !matches!(kind, 1..=3 | 5 | 18 | 19 | 24) {
+            return;
+        }
+
+        let target = self
+            .targets
+            .entry(mmsi)
+            .or_insert_with(|| AisTarget::new(mmsi));
+        match kind {
+            1..=3 => target.apply_position(payload, (50, 61, 89, 116, 128)),
+            18 | 19 => target.apply_position(payload, (46, 57, 85, 112, 124)),
+            5 => {
+                target.call_sign = payload.text(70, 7).or(target.call_sign.take());
+                target.name = payload.text(112, 20).or(target.name.take());
+                target.ship_type = payload.unsigned(232, 8).filter(|&t| t != 0);
+                target.dimensions = payload.dimensions(240).or(target.dimensions);
+                target.destination = payload.text(302, 20).or(target.destination.take());
+            }
+            // Class B static data comes in two parts.
+            24 => match payload.unsigned(38, 2) {
+                Some(0) => target.name = payload.text(40, 20).or(target.name.take()),
+                Some(1) => {
+                    target.ship_type = payload.unsigned(40, 8).filter(|&t| t != 0);
+                    target.call_sign = payload.text(90, 7).or(target.call_sign.take());
+                    target.dimensions = payload.dimensions(132).or(target.dimensions);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        target.updated_at = Instant::now();
+
+        let expiry = self.expiry;
+        self.targets
+            .retain(|_, target| target.updated_at.elapsed() < expiry);
+    }
+
+    /// Targets heard from within the expiry time.
+    pub fn iter(&self) -> impl Iterator<Item = &AisTarget> {
+        self.targets
+            .values()
+            .filter(|target| target.updated_at.elapsed() < self.expiry)
+    }
+
+    /// Targets with their CPA relative to `own`, closest first. Targets without a CPA
+    /// come last in MMSI order.
+    pub fn by_cpa(&self, own: Option<&OwnShip>) -> Vec<(&AisTarget, Option<Cpa>)> {
+        let mut targets = self
+            .iter()
+            .map(|target| (target, own.and_then(|own| target.cpa(own))))
+            .collect::<Vec<_>>();
+        targets.sort_by(|(_, a), (_, b)| match (a, b) {
+            (Some(a), Some(b)) => a.distance.total_cmp(&b.distance),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        targets
+    }
+
+    /// Whether a closest approach is within the alarm distance and time.
+    pub fn is_dangerous(&self, cpa: &Cpa) -> bool {
+        cpa.distance <= self.cpa_alarm
+            && cpa.time >= 0.0
+            && cpa.time <= self.tcpa_alarm.as_secs_f64()
+    }
+}
+
+/// A de-armored AIS payload, one 6-bit value per byte.
+struct Payload(Vec<u8>);
+
+impl Payload {
+    fn dearmor(armored: &str) -> Option<Payload> {
+        armored
+            .bytes()
+            .map(|c| match c.checked_sub(48)? {
+                v @ 0..=39 => Some(v),
+                v @ 48..=71 => Some(v - 8),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Payload)
+    }
+
+    fn unsigned(&self, start: usize, len: usize) -> Option<u32> {
+        if start + len > self.0.len() * 6 {
+            return None;
+        }
+        Some((start..start + len).fold(0, |acc, i| {
+            (acc << 1) | u32::from((self.0[i / 6] >> (5 - i % 6)) & 1)
+        }))
+    }
+
+    /// Decodes `chars` characters of 6-bit text, trimming `@` padding and spaces.
+    fn text(&self, start: usize, chars: usize) -> Option<String> {
+        let text = (0..chars)
+            .map(|i| {
+                let c = self.unsigned(start + i * 6, 6)? as u8;
+                Some(if c < 32 { (c + 64) as char } else { c as char })
+            })
+            .collect::<Option<String>>()?;
+        let text = text.trim_end_matches(['@', ' ']).trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    /// Length and beam from the to-bow, to-stern, to-port and to-starboard offsets
+    /// starting at `start`.
+    fn dimensions(&self, start: usize) -> Option<(u32, u32)> {
+        let length = self.unsigned(start, 9)? + self.unsigned(start + 9, 9)?;
+        let beam = self.unsigned(start + 18, 6)? + self.unsigned(start + 24, 6)?;
+        (length > 0 && beam > 0).then_some((length, beam))
+    }
+
+    fn signed(&self, start: usize, len: usize) -> Option<i32> {
+        let shift = 32 - len;
+        self.unsigned(start, len)
+            .map(|v| ((v << shift) as i32) >> shift)
+    }
+}
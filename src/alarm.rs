@@ -0,0 +1,256 @@
+//! Alarms raised for alerts, from when they appear until they are acknowledged with `A`
+//! or the `acknowledge_alarms` control command, and the history of past ones.
+//!
+//! An alarm stays in the banner until it is acknowledged, also after its condition
+//! cleared, so that no alert goes unnoticed. A condition that returns before that is
+//! merged into its alarm, and past [`MAX_ACTIVE`] alarms the oldest cleared ones go to
+//! the history unacknowledged, so that an unattended instance does not pile them up.
+//!
+//! An alarm sounds the terminal bell in a pattern set per kind of alert in the `[alarms]`
+//! of the config file, until it clears or is acknowledged, or alarms are silenced with
+//! `S`. The kind is the first word of the alert id, such as `cpa`, `dsc`, `battery` or
+//! `traffic`. Kinds without a pattern use the one named `default`, or ring once.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, SystemTime},
+};
+
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::status::Alert;
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlarmPattern {
+    /// Bells each time the alarm sounds.
+    pub beeps: u32,
+    /// Seconds between soundings.
+    pub interval: f64,
+    /// Times to sound, until the alert clears when not set. `0` keeps the kind silent.
+    pub count: Option<u32>,
+}
+
+impl Default for AlarmPattern {
+    fn default() -> Self {
+        AlarmPattern {
+            beeps: 1,
+            interval: 10.0,
+            count: Some(1),
+        }
+    }
+}
+
+/// Past alarms kept for the alarms screen.
+const HISTORY_LEN: usize = 500;
+
+/// Alarms kept in the banner, beyond those whose condition lasts.
+const MAX_ACTIVE: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct Alarm {
+    /// Id of the alert that raised it.
+    pub id: String,
+    /// Latest text of the alert.
+    pub text: String,
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub raised: SystemTime,
+    pub cleared: Option<SystemTime>,
+    pub acknowledged: Option<SystemTime>,
+    /// Times sounded and when to sound next.
+    sounded: u32,
+    next: Instant,
+}
+
+impl Alarm {
+    /// How long the condition lasted, or has lasted so far.
+    #[cfg(feature = "tui")]
+    pub fn duration(&self) -> Duration {
+        self.cleared
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(self.raised)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Alarms {
+    patterns: HashMap<String, AlarmPattern>,
+    /// Alarms shown in the banner, oldest first: those whose condition lasts and those
+    /// not acknowledged yet.
+    active: Vec<Alarm>,
+    /// Alarms cleared and acknowledged, oldest first.
+    history: VecDeque<Alarm>,
+    /// Nothing sounds while set; alerts are still tracked.
+    pub silenced: bool,
+    /// Bells rung since the start, for the UI to ring the ones it has not.
+    pub bells: u64,
+}
+
+impl Alarms {
+    pub fn new(patterns: HashMap<String, AlarmPattern>) -> Alarms {
+        Alarms {
+            patterns,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_patterns(&mut self, patterns: HashMap<String, AlarmPattern>) {
+        self.patterns = patterns;
+    }
+
+    fn pattern(&self, id: &str) -> AlarmPattern {
+        let kind = id.split(' ').next().unwrap_or_default();
+        self.patterns
+            .get(kind)
+            .or_else(|| self.patterns.get("default"))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Raises alarms for new alerts, clears those whose alert is gone and sounds the
+    /// ones that are due. Called every second with the current alerts.
+    pub fn update(&mut self, alerts: &[Alert]) {
+        let now = Instant::now();
+        let time = SystemTime::now();
+        for alarm in &mut self.active {
+            match alerts.iter().find(|alert| alert.id == alarm.id) {
+                Some(alert) if alarm.cleared.is_none() => alarm.text.clone_from(&alert.text),
+                Some(_) => {}
+                None => {
+                    alarm.cleared.get_or_insert(time);
+                }
+            }
+        }
+        for alert in alerts {
+            // A condition returning after it cleared is a new alarm, unless the old one
+            // was not acknowledged yet.
+            let alarm = self.active.iter_mut().find(|alarm| {
+                alarm.id == alert.id && (alarm.cleared.is_none() || alarm.acknowledged.is_none())
+            });
+            if let Some(alarm) = alarm {
+                if alarm.cleared.take().is_some() {
+                    alarm.text.clone_from(&alert.text);
+                }
+            } else {
+                self.active.push(Alarm {
+                    id: alert.id.clone(),
+                    text: alert.text.clone(),
+                    raised: time,
+                    cleared: None,
+                    acknowledged: None,
+                    sounded: 0,
+                    next: now,
+                });
+            }
+        }
+        self.archive();
+
+        for i in 0..self.active.len() {
+            let pattern = self.pattern(&self.active[i].id);
+            let alarm = &mut self.active[i];
+            if alarm.cleared.is_some()
+                || alarm.acknowledged.is_some()
+                || pattern.count.is_some_and(|count| alarm.sounded >= count)
+                || now < alarm.next
+            {
+                continue;
+            }
+            alarm.sounded += 1;
+            alarm.next = now + Duration::from_secs_f64(pattern.interval.max(1.0));
+            if !self.silenced {
+                self.bells += u64::from(pattern.beeps);
+            }
+        }
+    }
+
+    /// Acknowledges every alarm in the banner, which stops them sounding and removes
+    /// those that cleared. Returns how many there were.
+    pub fn acknowledge(&mut self) -> usize {
+        let time = SystemTime::now();
+        let mut count = 0;
+        for alarm in &mut self.active {
+            if alarm.acknowledged.is_none() {
+                alarm.acknowledged = Some(time);
+                count += 1;
+            }
+        }
+        self.archive();
+        count
+    }
+
+    /// Moves cleared and acknowledged alarms to the history, and the oldest cleared ones
+    /// beyond [`MAX_ACTIVE`].
+    fn archive(&mut self) {
+        let (done, mut active): (Vec<_>, Vec<_>) = std::mem::take(&mut self.active)
+            .into_iter()
+            .partition(|alarm| alarm.cleared.is_some() && alarm.acknowledged.is_some());
+        self.history.extend(done);
+        while active.len() > MAX_ACTIVE {
+            let Some(oldest) = active.iter().position(|alarm| alarm.cleared.is_some()) else {
+                break;
+            };
+            self.history.push_back(active.remove(oldest));
+        }
+        self.active = active;
+        while self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+    }
+
+    /// Alarms shown in the banner, oldest first.
+    #[cfg(feature = "tui")]
+    pub fn active(&self) -> &[Alarm] {
+        &self.active
+    }
+
+    /// The alarms in the banner, then past ones, each newest first.
+    #[cfg(feature = "tui")]
+    pub fn iter(&self) -> impl Iterator<Item = &Alarm> {
+        self.active.iter().rev().chain(self.history.iter().rev())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(id: &str) -> Alert {
+        Alert {
+            id: id.to_string(),
+            text: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn returning_condition_joins_its_unacknowledged_alarm() {
+        let mut alarms = Alarms::default();
+        for _ in 0..5 {
+            alarms.update(&[alert("depth")]);
+            alarms.update(&[]);
+        }
+        assert_eq!(alarms.active.len(), 1);
+        assert!(alarms.active[0].cleared.is_some());
+
+        alarms.acknowledge();
+        alarms.update(&[alert("depth")]);
+        assert_eq!(alarms.active.len(), 1);
+        assert!(alarms.active[0].acknowledged.is_none());
+    }
+
+    #[test]
+    fn cleared_alarms_beyond_the_limit_are_archived() {
+        let mut alarms = Alarms::default();
+        for i in 0..MAX_ACTIVE * 2 {
+            alarms.update(&[alert(&format!("cpa {i}"))]);
+        }
+        alarms.update(&[alert("depth")]);
+        assert_eq!(alarms.active.len(), MAX_ACTIVE);
+        assert_eq!(alarms.active.last().unwrap().id, "depth");
+        assert_eq!(
+            alarms.active.len() + alarms.history.len(),
+            MAX_ACTIVE * 2 + 1
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Almanac bookkeeping from ALM sentences, for telling how complete and how old the
+//! receiver's almanac is when diagnosing slow cold starts.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "tui")]
+use std::time::SystemTime;
+
+#[cfg(feature = "tui")]
+use crate::gps_time::{self, ROLLOVER_WEEKS};
+use crate::{satellite::SatelliteId, sentence::Sentence};
+
+/// Satellites a complete almanac covers, by constellation.
+#[cfg(feature = "tui")]
+fn expected(constellation: &str) -> Option<usize> {
+    match constellation {
+        "GPS" => Some(32),
+        "GLONASS" => Some(24),
+        "Galileo" => Some(36),
+        "BeiDou" => Some(63),
+        "QZSS" => Some(10),
+        _ => None,
+    }
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Debug)]
+struct Entry {
+    /// Week number of the almanac.
+    week: u32,
+    /// Whether the satellite reported itself healthy.
+    healthy: bool,
+}
+
+/// Almanac state of one constellation.
+#[cfg(feature = "tui")]
+#[derive(Clone, Debug)]
+pub struct Coverage {
+    pub constellation: &'static str,
+    pub satellites: usize,
+    pub expected: Option<usize>,
+    pub unhealthy: usize,
+    /// Newest almanac week reported.
+    pub week: u32,
+    /// Weeks the newest almanac is behind the receiver's GPS week, when its time is known.
+    pub age: Option<u32>,
+}
+
+/// Weeks from almanac week `week` to `current`, both as broadcast, across a wrap of the
+/// 10-bit week number. Some receivers send the full week, which is wrapped first.
+#[cfg(feature = "tui")]
+fn weeks_behind(week: u32, current: u32) -> u32 {
+    (current + ROLLOVER_WEEKS - week % ROLLOVER_WEEKS) % ROLLOVER_WEEKS
+}
+
+#[derive(Default, Debug)]
+pub struct Almanac {
+    entries: BTreeMap<SatelliteId, Entry>,
+    /// ALM sentences received in total.
+    pub sentences: u64,
+}
+
+impl Almanac {
+    /// Total sentences, sentence number, PRN, week, health, then the orbit in hex.
+    pub fn update(&mut self, alm: &Sentence) {
+        self.sentences += 1;
+        let fields = &alm.fields;
+        let (Some(Ok(prn)), Some(Ok(week))) = (
+            fields.get(2).map(|f| f.parse::<u32>()),
+            fields.get(3).map(|f| f.parse::<u32>()),
+        ) else {
+            return;
+        };
+        let healthy = fields
+            .get(4)
+            .and_then(|f| u8::from_str_radix(f, 16).ok())
+            .is_none_or(|health| health == 0);
+        self.entries.insert(
+            SatelliteId {
+                talker: alm.talker.to_string(),
+                prn,
+            },
+            Entry { week, healthy },
+        );
+    }
+
+    /// Coverage of each constellation with almanac data, in id order, aged against the
+    /// receiver time `utc`.
+    #[cfg(feature = "tui")]
+    pub fn coverage(&self, utc: Option<SystemTime>) -> Vec<Coverage> {
+        let current = utc.and_then(gps_time::week);
+        let age = |week| current.map(|current| weeks_behind(week, current));
+        let mut coverage: Vec<Coverage> = Vec::new();
+        for (id, entry) in &self.entries {
+            let constellation = id.constellation();
+            let index = match coverage
+                .iter()
+                .position(|c| c.constellation == constellation)
+            {
+                Some(index) => index,
+                None => {
+                    coverage.push(Coverage {
+                        constellation,
+                        satellites: 0,
+                        expected: expected(constellation),
+                        unhealthy: 0,
+                        week: entry.week,
+                        age: age(entry.week),
+                    });
+                    coverage.len() - 1
+                }
+            };
+            let c = &mut coverage[index];
+            c.satellites += 1;
+            c.unhealthy += usize::from(!entry.healthy);
+            // Across a wrap the newest almanac has the lowest week number.
+            let newer = match (age(entry.week), c.age) {
+                (Some(entry), Some(newest)) => entry < newest,
+                _ => entry.week > c.week,
+            };
+            if newer {
+                c.week = entry.week;
+                c.age = age(entry.week);
+            }
+        }
+        coverage
+    }
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gps_week_of_receiver_time() {
+        // 2024-09-01 starts GPS week 2330, broadcast as 282.
+        let utc = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_725_192_000);
+        assert_eq!(gps_time::week(utc), Some(282));
+    }
+
+    #[test]
+    fn age_across_the_week_rollover() {
+        assert_eq!(weeks_behind(281, 283), 2);
+        assert_eq!(weeks_behind(1022, 3), 5);
+        assert_eq!(weeks_behind(2329, 281), 0);
+    }
+
+    #[test]
+    fn newest_almanac_across_the_week_rollover() {
+        let mut almanac = Almanac::default();
+        for (prn, week) in [("1", "1023"), ("2", "1")] {
+            almanac.update(&Sentence {
+                talker: "GP",
+                kind: "ALM",
+                fields: vec!["1", "1", prn, week, "00"],
+            });
+        }
+        // Week 2 after the rollover of 2019-04-07.
+        let utc =
+            SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_554_595_200 + 604_800 * 2);
+        let coverage = almanac.coverage(Some(utc));
+        assert_eq!((coverage[0].week, coverage[0].age), (1, Some(1)));
+    }
+}
@@ -0,0 +1,268 @@
+//! Position beacons to APRS-IS, with `--aprs-call`, turning the monitor into a simple
+//! tracker.
+//!
+//! Beacons follow the usual smart beaconing scheme: rarely at rest, more often the
+//! faster the receiver moves, and at once after a turn.
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{bail, Result};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+    net::TcpStream,
+    sync::RwLock,
+    time::Instant,
+};
+use tracing::{info, warn};
+
+use crate::status::NmeaStatus;
+
+/// Destination address identifying the software, from the experimental range.
+const TOCALL: &str = "APZNMM";
+
+/// Below this speed in knots, beacon every [`SLOW_RATE`].
+const SLOW_SPEED: f64 = 4.0;
+const SLOW_RATE: Duration = Duration::from_secs(30 * 60);
+/// Above this speed in knots, beacon every [`FAST_RATE`]; in between, proportionally.
+const FAST_SPEED: f64 = 52.0;
+const FAST_RATE: Duration = Duration::from_secs(3 * 60);
+/// A turn of at least this many degrees, plus [`TURN_SLOPE`] divided by the speed in
+/// knots, triggers a beacon.
+const MIN_TURN_ANGLE: f64 = 28.0;
+const TURN_SLOPE: f64 = 222.0;
+/// Beacons for turns are at least this far apart.
+const MIN_TURN_TIME: Duration = Duration::from_secs(15);
+
+/// Wait before connecting again after the connection failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug)]
+pub struct AprsConfig {
+    /// Callsign with SSID, `N0CALL-9`.
+    pub call: String,
+    pub pass: String,
+    /// `host:port` of an APRS-IS server.
+    pub server: String,
+    /// Symbol table and code, `/>` for a car.
+    pub symbol: String,
+}
+
+/// Shown on the status screen.
+#[derive(Default, Debug)]
+pub struct AprsStatus {
+    pub beacons: u64,
+    pub last: Option<SystemTime>,
+    /// Why the connection was lost, until it is back.
+    pub error: Option<String>,
+}
+
+#[derive(Default, Debug)]
+struct SmartBeacon {
+    /// Time and course of the last beacon.
+    last: Option<(Instant, Option<f64>)>,
+}
+
+impl SmartBeacon {
+    fn due(&self, sog: Option<f64>, cog: Option<f64>) -> bool {
+        let Some((at, last_cog)) = self.last else {
+            return true;
+        };
+        let elapsed = at.elapsed();
+        let sog = sog.unwrap_or(0.0);
+        let rate = if sog < SLOW_SPEED {
+            SLOW_RATE
+        } else if sog > FAST_SPEED {
+            FAST_RATE
+        } else {
+            FAST_RATE.mul_f64(FAST_SPEED / sog)
+        };
+        if elapsed >= rate {
+            return true;
+        }
+        let turn = cog.zip(last_cog).map(|(cog, last)| {
+            let turn = (cog - last).rem_euclid(360.0);
+            turn.min(360.0 - turn)
+        });
+        sog >= SLOW_SPEED
+            && elapsed >= MIN_TURN_TIME
+            && turn.is_some_and(|turn| turn > MIN_TURN_ANGLE + TURN_SLOPE / sog)
+    }
+
+    fn sent(&mut self, cog: Option<f64>) {
+        self.last = Some((Instant::now(), cog));
+    }
+}
+
+/// Connects to APRS-IS and beacons the position until the program ends, reconnecting
+/// when the connection is lost.
+pub fn spawn(config: AprsConfig, nmea: Arc<RwLock<NmeaStatus>>) {
+    tokio::spawn(async move {
+        let mut beacon = SmartBeacon::default();
+        loop {
+            let error = session(&config, &nmea, &mut beacon).await.err();
+            match &error {
+                Some(e) => warn!(server = config.server, "APRS-IS failed: {e:#}"),
+                None => info!(server = config.server, "APRS-IS disconnected"),
+            }
+            if let Some(aprs) = &mut nmea.write().await.aprs {
+                aprs.error =
+                    Some(error.map_or_else(|| "disconnected".to_string(), |e| format!("{e:#}")));
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+async fn session(
+    config: &AprsConfig,
+    nmea: &RwLock<NmeaStatus>,
+    beacon: &mut SmartBeacon,
+) -> Result<()> {
+    let stream = TcpStream::connect(&config.server).await?;
+    let (reader, mut writer) = stream.into_split();
+    let login = format!(
+        "user {} pass {} vers nmea-monitor {}\r\n",
+        config.call,
+        config.pass,
+        env!("CARGO_PKG_VERSION")
+    );
+    writer.write_all(login.as_bytes()).await?;
+    info!(server = config.server, "APRS-IS connected");
+    if let Some(aprs) = &mut nmea.write().await.aprs {
+        aprs.error = None;
+    }
+
+    let mut lines = BufReader::new(reader).lines();
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line? {
+                Some(line) if line.starts_with("# logresp") && line.contains("unverified") => {
+                    bail!("passcode not accepted");
+                }
+                // Server comments and, with a filter, traffic; neither is used.
+                Some(_) => {}
+                None => return Ok(()),
+            },
+            _ = interval.tick() => {
+                // Not held while writing, so that a stalled server cannot hold up decoding.
+                let (position, sog, cog) = {
+                    let nmea = nmea.read().await;
+                    let (Some(&lat), Some(&lon)) = (nmea.lat.get(), nmea.lon.get()) else {
+                        continue;
+                    };
+                    ((lat, lon), nmea.sog.get().copied(), nmea.cog.get().copied())
+                };
+                if !beacon.due(sog, cog) {
+                    continue;
+                }
+                let packet = packet(config, position, sog, cog);
+                writer.write_all(format!("{packet}\r\n").as_bytes()).await?;
+                beacon.sent(cog);
+                if let Some(aprs) = &mut nmea.write().await.aprs {
+                    aprs.beacons += 1;
+                    aprs.last = Some(SystemTime::now());
+                }
+            }
+        }
+    }
+}
+
+/// Uncompressed position report, with course and speed when moving.
+fn packet(
+    config: &AprsConfig,
+    (lat, lon): (f64, f64),
+    sog: Option<f64>,
+    cog: Option<f64>,
+) -> String {
+    let mut symbol = config.symbol.chars();
+    let table = symbol.next().unwrap_or('/');
+    let code = symbol.next().unwrap_or('>');
+    let mut packet = format!(
+        "{}>{TOCALL},TCPIP*:!{}{table}{}{code}",
+        config.call,
+        coordinate(lat, 2, ['N', 'S']),
+        coordinate(lon, 3, ['E', 'W']),
+    );
+    if let (Some(sog), Some(cog)) = (sog, cog) {
+        // Course 000 means unknown, so north is 360.
+        let course = match cog.round() as u32 % 360 {
+            0 => 360,
+            course => course,
+        };
+        packet.push_str(&format!("{course:03}/{:03}", sog.round().min(999.0) as u32));
+    }
+    packet
+}
+
+/// `DDMM.mmN` or `DDDMM.mmE`.
+fn coordinate(value: f64, degree_digits: usize, [positive, negative]: [char; 2]) -> String {
+    let hundredths = (value.abs() * 6000.0).round() as u64;
+    let (degrees, minutes) = (hundredths / 6000, hundredths % 6000);
+    let hemisphere = if value < 0.0 { negative } else { positive };
+    format!(
+        "{degrees:0degree_digits$}{:02}.{:02}{hemisphere}",
+        minutes / 100,
+        minutes % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(symbol: &str) -> AprsConfig {
+        AprsConfig {
+            call: "N0CALL-9".to_string(),
+            pass: "-1".to_string(),
+            server: "localhost:14580".to_string(),
+            symbol: symbol.to_string(),
+        }
+    }
+
+    #[test]
+    fn coordinates_are_degrees_and_minutes() {
+        assert_eq!(coordinate(35.668833, 2, ['N', 'S']), "3540.13N");
+        assert_eq!(coordinate(-33.5, 2, ['N', 'S']), "3330.00S");
+        assert_eq!(coordinate(139.7595, 3, ['E', 'W']), "13945.57E");
+        assert_eq!(coordinate(-8.05, 3, ['E', 'W']), "00803.00W");
+        assert_eq!(coordinate(0.0, 3, ['E', 'W']), "00000.00E");
+    }
+
+    #[test]
+    fn rounding_carries_into_the_degrees() {
+        assert_eq!(coordinate(59.999_999, 2, ['N', 'S']), "6000.00N");
+    }
+
+    #[test]
+    fn packets_carry_course_and_speed_when_known() {
+        assert_eq!(
+            packet(&config("/>"), (35.668833, 139.7595), None, None),
+            "N0CALL-9>APZNMM,TCPIP*:!3540.13N/13945.57E>"
+        );
+        assert_eq!(
+            packet(
+                &config("\\s"),
+                (35.668833, -139.7595),
+                Some(5.4),
+                Some(87.6)
+            ),
+            "N0CALL-9>APZNMM,TCPIP*:!3540.13N\\13945.57Ws088/005"
+        );
+    }
+
+    #[test]
+    fn north_is_course_360() {
+        let packet = packet(&config("/>"), (0.0, 0.0), Some(1000.0), Some(359.8));
+        assert!(packet.ends_with(">360/999"), "{packet}");
+    }
+
+    #[test]
+    fn missing_symbol_is_a_car() {
+        let packet = packet(&config(""), (0.0, 0.0), None, None);
+        assert!(packet.ends_with("!0000.00N/00000.00E>"), "{packet}");
+    }
+}
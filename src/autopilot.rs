@@ -0,0 +1,113 @@
+//! What a chartplotter commands the autopilot, from APB and HSC sentences.
+
+use std::time::Duration;
+
+use crate::{sentence::Sentence, status::StatusValue};
+
+/// A course in degrees with its reference.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Course {
+    pub degrees: f64,
+    pub magnetic: bool,
+}
+
+impl Course {
+    fn parse(value: Option<&&str>, reference: Option<&&str>) -> Option<Course> {
+        Some(Course {
+            degrees: value?.parse().ok()?,
+            magnetic: match *reference? {
+                "M" => true,
+                "T" => false,
+                _ => return None,
+            },
+        })
+    }
+
+    /// True course, given the magnetic variation (east positive) for magnetic ones.
+    #[cfg(feature = "tui")]
+    pub fn true_course(&self, variation: Option<f64>) -> Option<f64> {
+        if self.magnetic {
+            variation.map(|variation| (self.degrees + variation).rem_euclid(360.0))
+        } else {
+            Some(self.degrees)
+        }
+    }
+}
+
+/// Cross-track error in nautical miles and the side to steer to get back on track.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CrossTrack {
+    pub distance: f64,
+    /// `L` or `R`.
+    pub steer: char,
+}
+
+#[derive(Debug)]
+pub struct Autopilot {
+    /// Heading to steer, from APB or HSC.
+    pub steer: StatusValue<Course>,
+    pub cross_track: StatusValue<CrossTrack>,
+    /// Bearing from the present position to the destination.
+    pub bearing: StatusValue<Course>,
+    pub destination: StatusValue<String>,
+    /// Whether the arrival circle has been entered.
+    pub arrived: StatusValue<bool>,
+}
+
+impl Autopilot {
+    pub fn new(timeout: Duration) -> Autopilot {
+        Autopilot {
+            steer: StatusValue::new(timeout),
+            cross_track: StatusValue::new(timeout),
+            bearing: StatusValue::new(timeout),
+            destination: StatusValue::new(timeout),
+            arrived: StatusValue::new(timeout),
+        }
+    }
+
+    pub fn update(&mut self, sentence: &Sentence) {
+        let fields = &sentence.fields;
+        match sentence.kind {
+            // Status, status, XTE, direction to steer, XTE units, arrival circle,
+            // perpendicular passed, bearing origin to destination, M/T, destination id,
+            // bearing present position to destination, M/T, heading to steer, M/T.
+            "APB" => {
+                // Void when the receiver has no usable fix.
+                if fields.first() == Some(&"V") || fields.get(1) == Some(&"V") {
+                    return;
+                }
+                let distance = fields
+                    .get(2)
+                    .and_then(|f| f.parse::<f64>().ok())
+                    .and_then(|xte| match fields.get(4).copied() {
+                        Some("N") => Some(xte),
+                        Some("K") => Some(xte / 1.852),
+                        _ => None,
+                    });
+                let steer = fields.get(3).and_then(|f| f.chars().next());
+                self.cross_track.update(
+                    distance
+                        .zip(steer)
+                        .map(|(distance, steer)| CrossTrack { distance, steer }),
+                );
+                self.arrived.update(fields.get(5).map(|f| *f == "A"));
+                self.destination.update(
+                    fields
+                        .get(9)
+                        .filter(|f| !f.is_empty())
+                        .map(|f| f.to_string()),
+                );
+                self.bearing
+                    .update(Course::parse(fields.get(10), fields.get(11)));
+                self.steer
+                    .update(Course::parse(fields.get(12), fields.get(13)));
+            }
+            // Heading to steer true, T, magnetic, M.
+            "HSC" => self.steer.update(
+                Course::parse(fields.first(), fields.get(1))
+                    .or_else(|| Course::parse(fields.get(2), fields.get(3))),
+            ),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Barometric pressure from XDR pressure transducers: pressure altitude and the
+//! three-hour tendency that forecasts weather.
+
+use std::{collections::VecDeque, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::{sentence::Sentence, status::StatusValue};
+
+/// Span of the pressure tendency, as in weather reports.
+const TENDENCY_SPAN: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// Pressure is kept at most this often; the tendency needs no more.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Standard sea level pressure in hPa.
+#[cfg(feature = "tui")]
+const SEA_LEVEL: f64 = 1013.25;
+
+#[derive(Debug)]
+pub struct Barometer {
+    /// Pressure in hPa over the tendency span, oldest first.
+    samples: VecDeque<(Instant, f64)>,
+    /// Pressure in hPa.
+    pub pressure: StatusValue<f64>,
+    /// A fall of more than this many hPa over the tendency span raises an alert.
+    drop_alarm: Option<f64>,
+}
+
+impl Barometer {
+    pub fn new(timeout: Duration, drop_alarm: Option<f64>) -> Barometer {
+        Barometer {
+            samples: VecDeque::new(),
+            pressure: StatusValue::new(timeout),
+            drop_alarm,
+        }
+    }
+
+    /// Takes the first pressure quadruplet of an XDR sentence.
+    pub fn update(&mut self, xdr: &Sentence) {
+        let Some(hpa) = xdr.fields.chunks_exact(4).find_map(|quad| {
+            let value = quad[1].parse::<f64>().ok()?;
+            match (quad[0], quad[2]) {
+                ("P", "B") => Some(value * 1000.0),
+                ("P", "P") => Some(value / 100.0),
+                _ => None,
+            }
+        }) else {
+            return;
+        };
+        let now = Instant::now();
+        self.pressure.update(hpa);
+        if self
+            .samples
+            .back()
+            .is_none_or(|(at, _)| now.duration_since(*at) >= SAMPLE_INTERVAL)
+        {
+            self.samples.push_back((now, hpa));
+        }
+        while self
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > TENDENCY_SPAN)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Change in hPa since the oldest sample, and the time it spans. `None` until a
+    /// quarter of the span is covered, as shorter changes are mostly noise.
+    pub fn tendency(&self) -> Option<(f64, Duration)> {
+        let latest = *self.pressure.get()?;
+        let &(at, oldest) = self.samples.front()?;
+        let span = at.elapsed();
+        (span >= TENDENCY_SPAN / 4).then_some((latest - oldest, span))
+    }
+
+    /// Whether the pressure fell faster than the alarm allows, extrapolated to the
+    /// full tendency span.
+    pub fn is_dropping(&self) -> bool {
+        let (Some(alarm), Some((change, span))) = (self.drop_alarm, self.tendency()) else {
+            return false;
+        };
+        -change * TENDENCY_SPAN.as_secs_f64() / span.as_secs_f64() > alarm
+    }
+}
+
+/// Altitude in meters of `hpa` in the standard atmosphere.
+#[cfg(feature = "tui")]
+pub fn pressure_altitude(hpa: f64) -> f64 {
+    44_330.77 * (1.0 - (hpa / SEA_LEVEL).powf(0.190_263))
+}
+
+/// Weather report words for a change of `change` hPa over three hours.
+#[cfg(feature = "tui")]
+pub fn tendency_name(change: f64) -> &'static str {
+    match change.abs() {
+        c if c < 0.1 => "steady",
+        c if c <= 1.5 && change > 0.0 => "rising slowly",
+        c if c <= 1.5 => "falling slowly",
+        c if c <= 3.5 && change > 0.0 => "rising",
+        c if c <= 3.5 => "falling",
+        c if c <= 6.0 && change > 0.0 => "rising quickly",
+        c if c <= 6.0 => "falling quickly",
+        _ if change > 0.0 => "rising very rapidly",
+        _ => "falling very rapidly",
+    }
+}
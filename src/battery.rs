@@ -0,0 +1,110 @@
+//! Battery voltages from XDR voltage transducers, with a low-voltage alarm and the last
+//! hour of readings.
+//!
+//! Every voltage transducer is shown; listing one in the config names it and sets its
+//! alarm.
+//!
+//! ```toml
+//! [[battery]]
+//! id = "BATT1"
+//! name = "house"
+//! low = 12.0
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::{history::Series, sensors::Sensors};
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BatteryConfig {
+    /// XDR transducer id.
+    pub id: String,
+    pub name: Option<String>,
+    /// Voltage below which an alert is raised.
+    pub low: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct Battery<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub voltage: f64,
+    pub low: Option<f64>,
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub history: Option<&'a Series>,
+}
+
+impl Battery<'_> {
+    pub fn is_low(&self) -> bool {
+        self.low.is_some_and(|low| self.voltage < low)
+    }
+}
+
+#[derive(Debug)]
+pub struct Batteries {
+    config: Vec<BatteryConfig>,
+    /// Voltage sampled once a second, by transducer id.
+    history: BTreeMap<String, Series>,
+}
+
+impl Batteries {
+    pub fn new(config: Vec<BatteryConfig>) -> Batteries {
+        Batteries {
+            config,
+            history: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: Vec<BatteryConfig>) {
+        self.config = config;
+    }
+
+    /// Records the current voltages.
+    pub fn sample(&mut self, sensors: &Sensors) {
+        let now = Instant::now();
+        for (id, reading) in sensors.iter() {
+            if is_voltage(&reading.kind, &reading.unit) {
+                self.history
+                    .entry(id.clone())
+                    .or_default()
+                    .push(now, Some(reading.value));
+            }
+        }
+    }
+
+    /// Configured batteries first, in the order given, then other voltage transducers.
+    pub fn iter<'a>(&'a self, sensors: &'a Sensors) -> Vec<Battery<'a>> {
+        let configured = self.config.iter().filter_map(|config| {
+            let reading = sensors.get(&config.id)?;
+            Some(Battery {
+                id: &config.id,
+                name: config.name.as_deref().unwrap_or(sensors.name(&config.id)),
+                voltage: reading.value,
+                low: config.low,
+                history: self.history.get(&config.id),
+            })
+        });
+        let others = sensors
+            .iter()
+            .filter(|(id, reading)| {
+                is_voltage(&reading.kind, &reading.unit)
+                    && !self.config.iter().any(|config| &config.id == *id)
+            })
+            .map(|(id, reading)| Battery {
+                id,
+                name: sensors.name(id),
+                voltage: reading.value,
+                low: None,
+                history: self.history.get(id),
+            });
+        configured.chain(others).collect()
+    }
+}
+
+fn is_voltage(kind: &str, unit: &str) -> bool {
+    kind == "U" && unit == "V"
+}
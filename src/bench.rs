@@ -0,0 +1,116 @@
+//! Throughput benchmark of the decode pipeline, for measuring performance regressions.
+//!
+//! The log is read into memory first and then decoded repeatedly for at least
+//! [`MIN_DURATION`], so that the result does not depend on the disk. Allocation counts
+//! need the `bench-alloc` feature, which installs a counting global allocator.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context as _, Result};
+
+use crate::{config::Config, input, raw_log, status::NmeaStatus, Args};
+
+/// Decoding repeats until it has taken this long.
+const MIN_DURATION: Duration = Duration::from_secs(1);
+
+#[cfg(feature = "bench-alloc")]
+mod alloc {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    pub static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    pub static BYTES: AtomicU64 = AtomicU64::new(0);
+
+    struct Counting;
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    /// Allocations and bytes allocated so far.
+    pub fn counts() -> (u64, u64) {
+        (
+            ALLOCATIONS.load(Ordering::Relaxed),
+            BYTES.load(Ordering::Relaxed),
+        )
+    }
+}
+
+pub fn run(args: &Args, config: &Config, log: &Path) -> Result<()> {
+    let data = std::fs::read(log).with_context(|| format!("Failed to read {}", log.display()))?;
+    let mut reader = &data[..];
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    while input::read_line_blocking(&mut reader, &mut buf)? > 0 {
+        let line = input::extract_text(&buf).0;
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+        buf.clear();
+    }
+
+    let mut counts = HashMap::<String, u64>::new();
+    for line in &lines {
+        let address = raw_log::address(line).unwrap_or_else(|| "(other)".to_string());
+        *counts.entry(address).or_default() += 1;
+    }
+
+    // One status for all passes, as if the log kept streaming in.
+    let mut nmea = NmeaStatus::new(args, config);
+    #[cfg(feature = "bench-alloc")]
+    let before = alloc::counts();
+    let started = Instant::now();
+    let mut passes = 0;
+    while passes == 0 || started.elapsed() < MIN_DURATION {
+        for line in &lines {
+            if nmea.receive(0, line) {
+                nmea.handle_line(line);
+            }
+        }
+        passes += 1;
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+    let total = (lines.len() * passes) as f64;
+
+    println!("{} lines x {passes} passes in {elapsed:.2} s", lines.len());
+    println!(
+        "{:.0} lines/s, {:.1} MB/s",
+        total / elapsed,
+        (data.len() * passes) as f64 / elapsed / 1e6
+    );
+    #[cfg(feature = "bench-alloc")]
+    {
+        let (allocations, bytes) = alloc::counts();
+        println!(
+            "{:.1} allocations, {:.0} bytes allocated per line",
+            (allocations - before.0) as f64 / total,
+            (bytes - before.1) as f64 / total,
+        );
+    }
+    #[cfg(not(feature = "bench-alloc"))]
+    println!("allocation counts need --features bench-alloc");
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for (address, count) in counts {
+        println!("{count:>10} {address}");
+    }
+    Ok(())
+}
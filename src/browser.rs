@@ -0,0 +1,38 @@
+//! Opening the current position on a web map.
+
+use std::{
+    io,
+    process::{Command, Stdio},
+};
+
+/// Used when no template is configured.
+pub const DEFAULT_MAP_URL: &str =
+    "https://www.openstreetmap.org/?mlat={lat}&mlon={lon}#map=16/{lat}/{lon}";
+
+/// Expands `{lat}` and `{lon}` in a URL template with decimal degrees.
+pub fn map_url(template: &str, lat: f64, lon: f64) -> String {
+    template
+        .replace("{lat}", &format!("{lat:.7}"))
+        .replace("{lon}", &format!("{lon:.7}"))
+}
+
+/// Opens `url` with the platform's default handler.
+pub fn open(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        // The empty argument is the window title `start` would otherwise take from the URL.
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
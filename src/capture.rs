@@ -0,0 +1,89 @@
+//! Pre-trigger capture: the last few minutes of raw input are kept in memory and only
+//! written to disk when something goes wrong, like a dashcam.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::File,
+    io::{self, BufWriter, Write as _},
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::record;
+
+#[derive(Debug)]
+pub struct Capture {
+    /// Captures are written next to this path with a timestamp added to the name.
+    base: PathBuf,
+    window: Duration,
+    lines: VecDeque<(Instant, String)>,
+    /// Conditions seen at the last check, so that each fires once when it appears.
+    active: HashSet<String>,
+    /// Last capture written, and why.
+    pub last: Option<(PathBuf, String)>,
+}
+
+impl Capture {
+    pub fn new(base: PathBuf, window: Duration) -> Capture {
+        Capture {
+            base,
+            window,
+            lines: VecDeque::new(),
+            active: HashSet::new(),
+            last: None,
+        }
+    }
+
+    pub fn push(&mut self, line: &str) {
+        let now = Instant::now();
+        while self
+            .lines
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > self.window)
+        {
+            self.lines.pop_front();
+        }
+        self.lines.push_back((now, line.to_string()));
+    }
+
+    /// Triggers a capture for each condition that was not present at the last check.
+    pub fn check(&mut self, conditions: Vec<String>) {
+        let conditions = conditions.into_iter().collect::<HashSet<_>>();
+        let new = conditions.difference(&self.active).next().cloned();
+        self.active = conditions;
+        if let Some(reason) = new {
+            // A failed capture leaves the previous one shown.
+            if let Err(e) = self.trigger(&reason) {
+                warn!(reason, "capture failed: {e}");
+            }
+        }
+    }
+
+    /// Writes the buffered lines to a new file in the background and empties the buffer,
+    /// so that a following capture only holds what came after.
+    pub fn trigger(&mut self, reason: &str) -> io::Result<PathBuf> {
+        let path = record::segment_path(&self.base, SystemTime::now());
+        // Created here so that a capture within the same second gets another name.
+        let file = File::create(&path)?;
+        let lines = self
+            .lines
+            .drain(..)
+            .map(|(_, line)| line)
+            .collect::<Vec<_>>();
+        info!(path = %path.display(), reason, "capturing");
+        std::thread::spawn(move || write(file, &lines));
+        self.last = Some((path.clone(), reason.to_string()));
+        Ok(path)
+    }
+}
+
+fn write(file: File, lines: &[String]) -> io::Result<()> {
+    let mut file = BufWriter::new(file);
+    for line in lines {
+        writeln!(file, "{line}")?;
+    }
+    file.flush()
+}
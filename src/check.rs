@@ -0,0 +1,143 @@
+//! Golden-log checks: a log is run through the decoders without the TUI and the final
+//! state compared with an expectation file, for regression tests of the decoders and
+//! of users' own logs in CI.
+//!
+//! Without `--expect` the state is printed, to create the expectation file from a log
+//! that is known to be decoded correctly.
+//!
+//! `cargo test` checks every log in `tests/golden` against the `.json` file of the same
+//! name.
+
+use std::{collections::BTreeMap, fs::File, io::BufReader, path::Path};
+
+use anyhow::{Context as _, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{config::Config, input, status::NmeaStatus, Args};
+
+/// Numbers closer than this are equal, so that expectations survive float formatting.
+const TOLERANCE: f64 = 1e-6;
+
+/// The decoded state that expectations are written against. Values are the last ones
+/// received, however old, so the result does not depend on how fast the log is read.
+#[derive(Serialize, Debug)]
+struct Snapshot {
+    lines: u64,
+    errors: u64,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    alt: Option<f64>,
+    fix: Option<&'static str>,
+    sog: Option<f64>,
+    cog: Option<f64>,
+    heading: Option<f64>,
+    depth: Option<f64>,
+    stw: Option<f64>,
+    wind_angle: Option<f64>,
+    wind_speed: Option<f64>,
+    satellites: usize,
+    #[cfg(feature = "ais")]
+    ais_targets: Vec<u32>,
+    dsc_calls: usize,
+    /// Transducer readings by id.
+    sensors: BTreeMap<String, f64>,
+    track_points: usize,
+}
+
+impl Snapshot {
+    fn new(nmea: &NmeaStatus) -> Snapshot {
+        let last = |value: &crate::status::StatusValue<f64>| value.last().map(|(&v, _)| v);
+        Snapshot {
+            lines: nmea.sources.iter().map(|source| source.lines).sum(),
+            errors: nmea.sources.iter().map(|source| source.errors).sum(),
+            lat: last(&nmea.lat),
+            lon: last(&nmea.lon),
+            alt: last(&nmea.alt),
+            fix: nmea.fix_type.last().map(|(&fix, _)| fix),
+            sog: last(&nmea.sog_raw),
+            cog: last(&nmea.cog_raw),
+            heading: last(&nmea.hdg),
+            depth: last(&nmea.depth),
+            stw: last(&nmea.stw),
+            wind_angle: last(&nmea.wind_angle),
+            wind_speed: last(&nmea.wind_speed),
+            satellites: nmea.satellites.iter().count(),
+            #[cfg(feature = "ais")]
+            ais_targets: nmea.ais.iter().map(|target| target.mmsi).collect(),
+            dsc_calls: nmea.dsc.iter().count(),
+            sensors: nmea
+                .sensors
+                .iter()
+                .map(|(id, reading)| (id.clone(), reading.value))
+                .collect(),
+            track_points: nmea.track.iter().count(),
+        }
+    }
+}
+
+/// Decodes `log` and compares the result with `expect`, or prints it. Returns whether
+/// the check passed.
+pub fn run(args: &Args, config: &Config, log: &Path, expect: Option<&Path>) -> Result<bool> {
+    let mut nmea = NmeaStatus::new(args, config);
+    let file = File::open(log).with_context(|| format!("Failed to open {}", log.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut buf = Vec::new();
+    while input::read_line_blocking(&mut reader, &mut buf)? > 0 {
+        let (line, dropped) = input::extract_text(&buf);
+        nmea.dropped_bytes += dropped as u64;
+        if !line.is_empty() && nmea.receive(0, line) {
+            nmea.handle_line(line);
+        }
+        buf.clear();
+    }
+    let actual = serde_json::to_value(Snapshot::new(&nmea))?;
+
+    let Some(expect) = expect else {
+        println!("{}", serde_json::to_string_pretty(&actual)?);
+        return Ok(true);
+    };
+    let text = std::fs::read_to_string(expect)
+        .with_context(|| format!("Failed to read {}", expect.display()))?;
+    let expected: Value = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", expect.display()))?;
+
+    let mut differences = Vec::new();
+    compare("", &expected, &actual, &mut differences);
+    for difference in &differences {
+        eprintln!("{difference}");
+    }
+    Ok(differences.is_empty())
+}
+
+/// Collects differences between `expected` and `actual`. Only the keys present in the
+/// expectation are checked, so it can be limited to what matters.
+fn compare(path: &str, expected: &Value, actual: &Value, differences: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match actual.get(key) {
+                    Some(actual) => compare(&path, expected, actual, differences),
+                    None => differences.push(format!("{path}: no such field")),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                compare(&format!("{path}[{i}]"), expected, actual, differences);
+            }
+        }
+        (Value::Number(expected), Value::Number(actual))
+            if expected
+                .as_f64()
+                .zip(actual.as_f64())
+                .is_some_and(|(expected, actual)| (expected - actual).abs() <= TOLERANCE) => {}
+        _ if expected == actual => {}
+        _ => differences.push(format!("{path}: expected {expected}, got {actual}")),
+    }
+}
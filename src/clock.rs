@@ -0,0 +1,153 @@
+//! Wall-clock times in the zone chosen with `--timezone`, for everything that shows or
+//! writes times. Receivers report UTC; it stays the default.
+
+#[cfg(feature = "export")]
+use std::time::Duration;
+use std::{sync::RwLock, time::SystemTime};
+
+use anyhow::{Context as _, Result};
+use jiff::{
+    civil::{Date, DateTime, Time},
+    tz::TimeZone,
+    Timestamp, Zoned,
+};
+
+struct Clock {
+    zone: TimeZone,
+    /// Also show UTC where there is room.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    both: bool,
+}
+
+static CLOCK: RwLock<Option<Clock>> = RwLock::new(None);
+
+/// `UTC`, `local` for the system's zone, or an IANA name such as `Asia/Tokyo`.
+fn parse_zone(name: &str) -> Result<TimeZone> {
+    match name {
+        "UTC" | "utc" => Ok(TimeZone::UTC),
+        "local" => TimeZone::try_system().context("Failed to find the system time zone"),
+        name => TimeZone::get(name).with_context(|| format!("Unknown time zone {name}")),
+    }
+}
+
+/// Sets the zone times are shown in, UTC when `None`, replacing the earlier one when the
+/// config is reloaded.
+pub fn init(zone: Option<&str>, both: bool) -> Result<()> {
+    let zone = zone.map_or(Ok(TimeZone::UTC), parse_zone)?;
+    *CLOCK.write().expect("clock lock poisoned") = Some(Clock { zone, both });
+    Ok(())
+}
+
+fn zone() -> TimeZone {
+    CLOCK
+        .read()
+        .expect("clock lock poisoned")
+        .as_ref()
+        .map_or(TimeZone::UTC, |clock| clock.zone.clone())
+}
+
+fn zoned(time: SystemTime) -> Zoned {
+    Timestamp::try_from(time)
+        .unwrap_or(Timestamp::UNIX_EPOCH)
+        .to_zoned(zone())
+}
+
+/// Whether times should also be shown in UTC, because they are shown in another zone.
+#[cfg(feature = "tui")]
+pub fn show_utc() -> bool {
+    CLOCK
+        .read()
+        .expect("clock lock poisoned")
+        .as_ref()
+        .is_some_and(|clock| clock.both && clock.zone != TimeZone::UTC)
+}
+
+/// Abbreviation of the zone at `time`, such as `JST`.
+#[cfg(feature = "tui")]
+pub fn zone_name(time: SystemTime) -> String {
+    zoned(time).strftime("%Z").to_string()
+}
+
+/// `HH:MM:SS`.
+#[cfg(feature = "tui")]
+pub fn time(time: SystemTime) -> String {
+    zoned(time).strftime("%H:%M:%S").to_string()
+}
+
+/// `YYYY-MM-DD`.
+pub fn date(time: SystemTime) -> String {
+    zoned(time).strftime("%Y-%m-%d").to_string()
+}
+
+/// `HH:MM:SS` in UTC, whatever the zone.
+#[cfg(feature = "tui")]
+pub fn utc_time(time: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(time).to_string()[11..19].to_string()
+}
+
+/// RFC 3339 with the zone's offset, for files.
+pub fn timestamp(time: SystemTime) -> String {
+    zoned(time).strftime("%Y-%m-%dT%H:%M:%S%:z").to_string()
+}
+
+/// UTC date and time fields as sent by receivers. `None` when they are out of range.
+pub fn from_utc(
+    (year, month, day): (i16, i8, i8),
+    (hour, minute, second): (i8, i8, f64),
+) -> Option<SystemTime> {
+    // A leap second is sent as second 60, which has no place on the UTC timeline.
+    let second = second.min(59.999_999_999);
+    let nanos = (second.fract() * 1e9) as i32;
+    let time = Time::new(hour, minute, second.trunc() as i8, nanos).ok()?;
+    let datetime = DateTime::from_parts(Date::new(year, month, day).ok()?, time);
+    let timestamp = datetime.to_zoned(TimeZone::UTC).ok()?.timestamp();
+    Some(timestamp.into())
+}
+
+/// A UTC time of day as sent with a fix, on the date of `near`, a receiver time close to
+/// it. Moved a day either way when that brings it closer, for fixes around midnight.
+#[cfg(feature = "export")]
+pub fn on_date_of(near: SystemTime, time: (i8, i8, f64)) -> Option<SystemTime> {
+    const HALF_DAY: Duration = Duration::from_secs(12 * 3600);
+    const DAY: Duration = Duration::from_secs(24 * 3600);
+    let date = Timestamp::try_from(near)
+        .ok()?
+        .to_zoned(TimeZone::UTC)
+        .date();
+    let at = from_utc((date.year(), date.month(), date.day()), time)?;
+    if at + HALF_DAY < near {
+        Some(at + DAY)
+    } else if at > near + HALF_DAY {
+        Some(at - DAY)
+    } else {
+        Some(at)
+    }
+}
+
+#[cfg(all(test, feature = "export"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fix_time_is_on_the_date_of_the_receiver_time() {
+        let near = from_utc((2024, 9, 1), (12, 0, 0.0)).unwrap();
+        assert_eq!(
+            on_date_of(near, (12, 0, 1.5)),
+            from_utc((2024, 9, 1), (12, 0, 1.5))
+        );
+    }
+
+    #[test]
+    fn fix_time_crosses_midnight() {
+        let before = from_utc((2024, 9, 1), (23, 59, 59.0)).unwrap();
+        assert_eq!(
+            on_date_of(before, (0, 0, 0.0)),
+            from_utc((2024, 9, 2), (0, 0, 0.0))
+        );
+        let after = from_utc((2024, 9, 2), (0, 0, 0.0)).unwrap();
+        assert_eq!(
+            on_date_of(after, (23, 59, 59.0)),
+            from_utc((2024, 9, 1), (23, 59, 59.0))
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Optional TOML configuration file given with `--config`.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _, Result};
+use serde::Deserialize;
+
+#[cfg(feature = "extract")]
+use crate::extract::ExtractConfig;
+use crate::{
+    alarm::AlarmPattern, battery::BatteryConfig, depth::DepthConfig, format::CoordinateFormat,
+    overlay::Overlay, propulsion::EngineConfig, threshold::Threshold, tide::TideStations,
+};
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Display names for XDR transducers, keyed by transducer id.
+    ///
+    /// ```toml
+    /// [transducers]
+    /// ENGINE_TEMP = "Engine temp °C"
+    /// ```
+    pub transducers: HashMap<String, String>,
+    pub display: Display,
+    /// Web map opened for the current position, with `{lat}` and `{lon}` placeholders.
+    ///
+    /// ```toml
+    /// map_url = "https://www.google.com/maps/search/?api=1&query={lat},{lon}"
+    /// ```
+    pub map_url: Option<String>,
+    /// Tide station database, relative to the config file. See [`crate::tide`].
+    ///
+    /// ```toml
+    /// tide_stations = "tides.toml"
+    /// ```
+    pub tide_stations: Option<PathBuf>,
+    /// GeoJSON files drawn under the track on the map, relative to the config file. See
+    /// [`crate::overlay`].
+    ///
+    /// ```toml
+    /// overlays = ["coastline.geojson", "moorings.geojson"]
+    /// ```
+    pub overlays: Vec<PathBuf>,
+    /// Translations of panel titles, labels, panel text and messages, keyed by the
+    /// English text. Placeholders `{}` in messages are filled in order.
+    ///
+    /// ```toml
+    /// [labels]
+    /// latitude = "緯度"
+    /// stationary = "停止中"
+    /// rise = "日の出"
+    /// "copied {}" = "{} をコピーしました"
+    /// "dropped {} B, {} duplicates, {} outliers" = "破棄 {} B、重複 {}、外れ値 {}"
+    /// ```
+    pub labels: HashMap<String, String>,
+    /// Custom values shown on the status screen. See [`crate::extract`].
+    #[cfg(feature = "extract")]
+    pub extract: Vec<ExtractConfig>,
+    /// Voltage transducers shown as batteries. See [`crate::battery`].
+    pub battery: Vec<BatteryConfig>,
+    /// XDR channels of the engine panel. See [`crate::propulsion`].
+    pub engine: EngineConfig,
+    /// Depth sounder installation. See [`crate::depth`].
+    pub depth: DepthConfig,
+    /// Colors of status values by range, keyed by the title of the value before any
+    /// `[labels]` translation. The first threshold the value is below applies. See
+    /// [`crate::threshold`].
+    ///
+    /// ```toml
+    /// [thresholds]
+    /// hdop = [
+    ///     { below = 2, color = "green" },
+    ///     { below = 5, color = "yellow" },
+    ///     { color = "red" },
+    /// ]
+    /// depth = [{ below = 3, color = "red" }]
+    /// ```
+    pub thresholds: HashMap<String, Vec<Threshold>>,
+    /// How alerts sound, by kind. See [`crate::alarm`].
+    ///
+    /// ```toml
+    /// [alarms.cpa]
+    /// beeps = 3
+    /// interval = 30
+    ///
+    /// [alarms.traffic]
+    /// count = 0
+    /// ```
+    pub alarms: HashMap<String, AlarmPattern>,
+    /// Named settings for a vessel or use, selected with `--profile` and layered over the
+    /// rest of the file.
+    ///
+    /// ```toml
+    /// [profiles.boat]
+    /// sources = ["/dev/ttyUSB0"]
+    /// options = ["--cpa-alarm", "1", "--stationary-speed", "0.3"]
+    ///
+    /// [profiles.boat.display]
+    /// coordinate_format = "dm"
+    /// ```
+    pub profiles: HashMap<String, Profile>,
+    /// Stations loaded from `tide_stations`.
+    #[serde(skip)]
+    pub tides: Option<TideStations>,
+    /// Lines loaded from `overlays`.
+    #[serde(skip)]
+    pub overlay: Overlay,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Display {
+    /// `decimal`, `dm` or `dms`.
+    pub coordinate_format: Option<CoordinateFormat>,
+    /// Decimal places of degrees, minutes or seconds depending on the format.
+    pub precision: Option<u8>,
+    /// Sentence addresses whose latest line is always shown on the raw log screen.
+    ///
+    /// ```toml
+    /// [display]
+    /// pinned = ["GNGGA", "GNRMC"]
+    /// ```
+    pub pinned: Vec<String>,
+    /// Zone times are shown and written in: `UTC`, `local` or an IANA name.
+    pub timezone: Option<String>,
+    /// Also show UTC where times are shown in another zone.
+    pub show_utc: bool,
+    /// Start with the dim red night palette. Toggling it with `m` saves it here.
+    pub night: bool,
+    /// Nautical miles between range rings on the map and radar, instead of a round
+    /// distance for the zoom.
+    ///
+    /// ```toml
+    /// [display]
+    /// range_rings = 0.5
+    /// ```
+    pub range_rings: Option<f64>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Used when no sources are given on the command line.
+    pub sources: Vec<String>,
+    /// Command line options, which those given on the command line override.
+    pub options: Vec<String>,
+    /// Replaces the values set here and adds pinned sentences.
+    pub display: Display,
+    pub map_url: Option<String>,
+    /// Added to the tables of the file, replacing entries with the same key.
+    pub transducers: HashMap<String, String>,
+    pub labels: HashMap<String, String>,
+    /// Shown after those of the file.
+    #[cfg(feature = "extract")]
+    pub extract: Vec<ExtractConfig>,
+    pub battery: Vec<BatteryConfig>,
+    /// Replace those of the file.
+    pub engine: Option<EngineConfig>,
+    pub depth: Option<DepthConfig>,
+    pub thresholds: HashMap<String, Vec<Threshold>>,
+    pub alarms: HashMap<String, AlarmPattern>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut config: Config =
+            toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))?;
+        #[cfg(feature = "extract")]
+        let mut extracts = config.extract.iter().chain(
+            config
+                .profiles
+                .values()
+                .flat_map(|profile| &profile.extract),
+        );
+        #[cfg(feature = "extract")]
+        if let Some(extract) = extracts.find(|extract| {
+            extract.regex.is_none() && (extract.sentence.is_none() || extract.field.is_none())
+        }) {
+            bail!(
+                "Extract {:?} needs a regex or a sentence and field",
+                extract.label
+            );
+        }
+        if let Some(stations) = &config.tide_stations {
+            let stations = path.parent().unwrap_or(Path::new(".")).join(stations);
+            config.tides = Some(TideStations::load(&stations)?);
+        }
+        for overlay in &config.overlays {
+            let overlay = path.parent().unwrap_or(Path::new(".")).join(overlay);
+            config.overlay.load(&overlay)?;
+        }
+        Ok(config)
+    }
+
+    /// Layers the profile `name` over the rest of the file. Returns the profile for its
+    /// sources and options.
+    pub fn select_profile(&mut self, name: &str) -> Result<Profile> {
+        let Some(mut profile) = self.profiles.remove(name) else {
+            let mut names = self.profiles.keys().map(String::as_str).collect::<Vec<_>>();
+            names.sort_unstable();
+            bail!(
+                "No profile {name:?} in the config, only {}",
+                names.join(", ")
+            );
+        };
+        let display = std::mem::take(&mut profile.display);
+        self.display.coordinate_format =
+            display.coordinate_format.or(self.display.coordinate_format);
+        self.display.precision = display.precision.or(self.display.precision);
+        self.display.pinned.extend(display.pinned);
+        self.display.timezone = display.timezone.or(self.display.timezone.take());
+        self.display.show_utc |= display.show_utc;
+        self.display.night |= display.night;
+        self.display.range_rings = display.range_rings.or(self.display.range_rings);
+        self.map_url = profile.map_url.take().or(self.map_url.take());
+        self.transducers
+            .extend(std::mem::take(&mut profile.transducers));
+        self.labels.extend(std::mem::take(&mut profile.labels));
+        self.thresholds
+            .extend(std::mem::take(&mut profile.thresholds));
+        self.alarms.extend(std::mem::take(&mut profile.alarms));
+        #[cfg(feature = "extract")]
+        self.extract.append(&mut profile.extract);
+        self.battery.append(&mut profile.battery);
+        if let Some(engine) = profile.engine.take() {
+            self.engine = engine;
+        }
+        if let Some(depth) = profile.depth.take() {
+            self.depth = depth;
+        }
+        Ok(profile)
+    }
+}
+
+/// Sets `key` in the `[display]` table of the config file at `path`, leaving the rest of
+/// the file, comments included, as written.
+#[cfg(feature = "tui")]
+pub fn set_display(path: &Path, key: &str, value: &str) -> Result<()> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut lines = text.lines().map(str::to_string).collect::<Vec<_>>();
+    let setting = format!("{key} = {value}");
+
+    match lines.iter().position(|line| line.trim() == "[display]") {
+        Some(header) => {
+            let end = lines[header + 1..]
+                .iter()
+                .position(|line| line.trim_start().starts_with('['))
+                .map_or(lines.len(), |i| header + 1 + i);
+            let existing = lines[header + 1..end].iter().position(|line| {
+                line.split_once('=')
+                    .is_some_and(|(name, _)| name.trim() == key)
+            });
+            match existing {
+                Some(i) => lines[header + 1 + i] = setting,
+                None => lines.insert(header + 1, setting),
+            }
+        }
+        None => {
+            lines.push(String::new());
+            lines.push("[display]".to_string());
+            lines.push(setting);
+        }
+    }
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,215 @@
+//! Control socket taking JSON commands, one per line, so that scripts can query and
+//! drive a running instance.
+//!
+//! ```text
+//! {"command":"status"}
+//! {"command":"set_smoothing","smoothing":"exponential","samples":10}
+//! {"command":"start_recording","path":"/var/log/nmea/raw.nmea"}
+//! {"command":"stop_recording"}
+//! {"command":"mark_waypoint","name":"buoy"}
+//! {"command":"acknowledge_alarms"}
+//! ```
+//!
+//! Every command is answered with a JSON line carrying `"ok"` and either the result or
+//! an `"error"`.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::RwLock,
+};
+
+use crate::{filter::Smoothing, record::Recording, status::NmeaStatus, waypoint::Waypoint};
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "command", rename_all = "snake_case", deny_unknown_fields)]
+enum Request {
+    Status,
+    SetSmoothing {
+        smoothing: Smoothing,
+        #[serde(default = "default_samples")]
+        samples: usize,
+    },
+    StartRecording {
+        path: PathBuf,
+    },
+    StopRecording,
+    MarkWaypoint {
+        name: Option<String>,
+    },
+    AcknowledgeAlarms,
+}
+
+fn default_samples() -> usize {
+    5
+}
+
+#[derive(Serialize, Debug)]
+struct Status {
+    lat: Option<f64>,
+    lon: Option<f64>,
+    alt: Option<f64>,
+    sog: Option<f64>,
+    cog: Option<f64>,
+    fix: Option<&'static str>,
+    active_source: Option<String>,
+    sources: Vec<SourceReport>,
+    recording: Option<PathBuf>,
+    satellites: usize,
+    #[cfg(feature = "ais")]
+    ais_targets: usize,
+    diagnostics: DiagnosticsReport,
+}
+
+/// The monitor's own usage; see the `diagnostics` module.
+#[derive(Serialize, Debug)]
+struct DiagnosticsReport {
+    /// Share of one core, in percent.
+    cpu: Option<f64>,
+    /// Resident memory in bytes.
+    memory: Option<u64>,
+    uptime_seconds: u64,
+    /// Lines read but not yet recorded.
+    read_queue: usize,
+    /// Lines waiting to be decoded.
+    decode_queue: usize,
+    queue_drops: u64,
+    /// Mean and maximum time a line took to decode over the last second.
+    decode_mean_ms: Option<f64>,
+    decode_max_ms: f64,
+}
+
+#[derive(Serialize, Debug)]
+struct SourceReport {
+    name: String,
+    state: String,
+    rate: f64,
+    errors: u64,
+}
+
+/// Accepts clients on a listener bound with [`crate::daemon::bind`] until an error
+/// occurs.
+pub async fn serve(
+    listener: UnixListener,
+    nmea: Arc<RwLock<NmeaStatus>>,
+    recording: Arc<Mutex<Recording>>,
+) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(client(stream, Arc::clone(&nmea), Arc::clone(&recording)));
+    }
+}
+
+async fn client(
+    stream: UnixStream,
+    nmea: Arc<RwLock<NmeaStatus>>,
+    recording: Arc<Mutex<Recording>>,
+) {
+    let (read, mut write) = stream.into_split();
+    let mut requests = BufReader::new(read).lines();
+    while let Ok(Some(request)) = requests.next_line().await {
+        let response = match serde_json::from_str::<Request>(&request) {
+            Ok(request) => match handle(request, &mut *nmea.write().await, &recording) {
+                Ok(Value::Null) => json!({ "ok": true }),
+                Ok(result) => json!({ "ok": true, "result": result }),
+                Err(e) => json!({ "ok": false, "error": format!("{e:#}") }),
+            },
+            Err(e) => json!({ "ok": false, "error": e.to_string() }),
+        };
+        if write
+            .write_all(format!("{response}\n").as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn handle(request: Request, nmea: &mut NmeaStatus, recording: &Mutex<Recording>) -> Result<Value> {
+    let recorder = || recording.lock().expect("recording lock poisoned");
+    match request {
+        Request::Status => Ok(serde_json::to_value(status(
+            nmea,
+            recorder().recorder.path(),
+        ))?),
+        Request::SetSmoothing { smoothing, samples } => {
+            nmea.set_smoothing(smoothing, samples);
+            Ok(Value::Null)
+        }
+        Request::StartRecording { path } => {
+            recorder().recorder.start(&path)?;
+            Ok(Value::Null)
+        }
+        Request::StopRecording => {
+            recorder().recorder.stop();
+            Ok(Value::Null)
+        }
+        Request::MarkWaypoint { name } => {
+            let (Some(&lat), Some(&lon)) = (nmea.lat.get(), nmea.lon.get()) else {
+                anyhow::bail!("No position");
+            };
+            let name = name.unwrap_or_else(|| nmea.waypoints.next_name());
+            nmea.waypoints.add(Waypoint {
+                name: name.clone(),
+                lat,
+                lon,
+                alt: nmea.alt.get().copied(),
+                created_at: SystemTime::now(),
+            });
+            Ok(json!({ "name": name, "lat": lat, "lon": lon }))
+        }
+        Request::AcknowledgeAlarms => Ok(json!({ "acknowledged": nmea.alarms.acknowledge() })),
+    }
+}
+
+fn status(nmea: &NmeaStatus, recording: Option<&Path>) -> Status {
+    let diagnostics = &nmea.diagnostics;
+    Status {
+        lat: nmea.lat.get().copied(),
+        lon: nmea.lon.get().copied(),
+        alt: nmea.alt.get().copied(),
+        sog: nmea.sog.get().copied(),
+        cog: nmea.cog.get().copied(),
+        fix: nmea.fix_type.get().copied(),
+        active_source: nmea
+            .active_source()
+            .map(|index| nmea.sources[index].name.clone()),
+        sources: nmea
+            .sources
+            .iter()
+            .map(|source| SourceReport {
+                name: source.name.clone(),
+                state: source.state.to_string(),
+                rate: source.rate(),
+                errors: source.errors,
+            })
+            .collect(),
+        recording: recording.map(Path::to_path_buf),
+        satellites: nmea.satellites.iter().count(),
+        #[cfg(feature = "ais")]
+        ais_targets: nmea.ais.iter().count(),
+        diagnostics: DiagnosticsReport {
+            cpu: diagnostics.cpu,
+            memory: diagnostics.memory.map(|(_, now)| now),
+            uptime_seconds: diagnostics.uptime().as_secs(),
+            read_queue: diagnostics.received.0,
+            decode_queue: nmea.queued.0,
+            queue_drops: nmea.queue_drops,
+            decode_mean_ms: diagnostics
+                .decode
+                .mean()
+                .map(|mean| mean.as_secs_f64() * 1e3),
+            decode_max_ms: diagnostics.decode.max.as_secs_f64() * 1e3,
+        },
+    }
+}
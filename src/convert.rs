@@ -0,0 +1,112 @@
+//! `convert`: decodes a log and writes its fixes as a GPX track, CSV or a GeoJSON line,
+//! so that logs can be opened in other tools.
+//!
+//! Every fix is kept, unlike the track shown live, and carries the receiver time when
+//! the log has RMC or ZDA dates: the time of the GGA or GNS itself on the last date.
+
+use std::{fmt::Write as _, fs::File, io::BufReader, path::Path, time::SystemTime};
+
+use anyhow::{Context as _, Result};
+use clap::ValueEnum;
+
+use crate::{
+    clock, config::Config, extended, geojson, gpx, input, source, status::NmeaStatus, Args,
+};
+
+#[derive(ValueEnum, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    Gpx,
+    Csv,
+    Geojson,
+}
+
+/// A fix as decoded from the log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fix {
+    pub time: Option<SystemTime>,
+    pub lat: f64,
+    pub lon: f64,
+    /// Altitude above mean sea level in meters.
+    pub alt: Option<f64>,
+    /// Speed over ground in knots and course over ground in degrees, as received.
+    pub sog: Option<f64>,
+    pub cog: Option<f64>,
+    pub fix_type: Option<&'static str>,
+}
+
+/// Writes the fixes of `log` to `output`, or stdout. Returns the number of fixes.
+pub fn run(
+    args: &Args,
+    config: &Config,
+    log: &Path,
+    format: ExportFormat,
+    output: Option<&Path>,
+) -> Result<usize> {
+    let fixes = decode(args, config, log)?;
+    let text = match format {
+        ExportFormat::Gpx => gpx::fixes_to_gpx(&fixes),
+        ExportFormat::Csv => to_csv(&fixes),
+        ExportFormat::Geojson => geojson::fixes_to_geojson(&fixes),
+    };
+    match output {
+        Some(path) => std::fs::write(path, text)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{text}"),
+    }
+    Ok(fixes.len())
+}
+
+/// Runs the log through the decoders and records the state after each line with a
+/// position.
+fn decode(args: &Args, config: &Config, log: &Path) -> Result<Vec<Fix>> {
+    let file = File::open(log).with_context(|| format!("Failed to open {}", log.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut nmea = NmeaStatus::new(args, config);
+    let mut fixes = Vec::<Fix>::new();
+    let mut buf = Vec::new();
+    while input::read_line_blocking(&mut reader, &mut buf)? > 0 {
+        let line = extended::strip(input::extract_text(&buf).0);
+        if !line.is_empty() && nmea.receive(0, line) {
+            nmea.handle_line(line);
+            if source::position(line).flatten().is_some() {
+                fixes.extend(fix(&nmea, line));
+            }
+        }
+        buf.clear();
+    }
+    Ok(fixes)
+}
+
+fn fix(nmea: &NmeaStatus, line: &str) -> Option<Fix> {
+    let position = nmea.position().filter(|position| !position.estimated)?;
+    Some(Fix {
+        time: nmea.fix_time(line).or_else(|| nmea.utc.get().copied()),
+        lat: position.lat,
+        lon: position.lon,
+        alt: nmea.alt.get().copied(),
+        sog: nmea.sog_raw.get().copied(),
+        cog: nmea.cog_raw.get().copied(),
+        fix_type: nmea.fix_type.get().copied(),
+    })
+}
+
+fn to_csv(fixes: &[Fix]) -> String {
+    let mut csv = String::from("time,lat,lon,alt,sog,cog,fix\n");
+    let number = |value: Option<f64>, decimals: usize| {
+        value.map_or_else(String::new, |v| format!("{v:.decimals$}"))
+    };
+    for fix in fixes {
+        let _ = writeln!(
+            csv,
+            "{},{:.7},{:.7},{},{},{},{}",
+            fix.time.map(clock::timestamp).unwrap_or_default(),
+            fix.lat,
+            fix.lon,
+            number(fix.alt, 1),
+            number(fix.sog, 1),
+            number(fix.cog, 1),
+            fix.fix_type.unwrap_or_default(),
+        );
+    }
+    csv
+}
@@ -0,0 +1,57 @@
+//! `cut`: copies the lines of a log between two receiver times of day, to trim large
+//! logs before sharing them.
+//!
+//! Each line belongs to the time of the last RMC or ZDA sentence before it, and the
+//! sentence itself. Lines before the first time are left out when a start is given.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context as _, Result};
+
+use crate::{input, replay};
+
+/// Copies the lines of `log` timed from `from` up to `to`, seconds of the day, to
+/// `output` or stdout. A range with `from` after `to` crosses midnight. Returns the
+/// number of lines copied.
+pub fn run(log: &Path, from: Option<f64>, to: Option<f64>, output: Option<&Path>) -> Result<u64> {
+    let file = File::open(log).with_context(|| format!("Failed to open {}", log.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("Failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let mut buf = Vec::new();
+    let mut time = None;
+    let mut copied = 0;
+    while input::read_line_blocking(&mut reader, &mut buf)? > 0 {
+        let (line, _) = input::extract_text(&buf);
+        time = replay::time_of_day(line).or(time);
+        if in_range(time, from, to) {
+            writer.write_all(&buf)?;
+            // The `\n` of a `\r\n` ending comes on its own and is not another line.
+            copied += u64::from(!line.is_empty());
+        }
+        buf.clear();
+    }
+    writer.flush()?;
+    Ok(copied)
+}
+
+fn in_range(time: Option<f64>, from: Option<f64>, to: Option<f64>) -> bool {
+    let Some(time) = time else {
+        return from.is_none();
+    };
+    match (from, to) {
+        (Some(from), Some(to)) if from > to => time >= from || time < to,
+        (from, to) => from.is_none_or(|from| time >= from) && to.is_none_or(|to| time < to),
+    }
+}
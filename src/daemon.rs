@@ -0,0 +1,128 @@
+//! Daemon mode: the engine runs without a TUI and serves its input to `attach` clients
+//! over a Unix socket.
+//!
+//! The protocol is line based. On connect the daemon sends its recent lines, marked as
+//! history so the client shows them in its log without applying them, then every line it
+//! applies. Lines sent by a client are written to the device if the daemon has a writable
+//! source.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{Context as _, Result};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::{broadcast, mpsc::UnboundedSender, Mutex},
+};
+use tracing::{info, warn};
+
+use crate::{engine::Engine, extended};
+
+/// Lines replayed to a client when it attaches.
+const BACKLOG_LEN: usize = 5000;
+
+type Backlog = Arc<Mutex<VecDeque<(SystemTime, String)>>>;
+
+/// `$XDG_RUNTIME_DIR/nmea-monitor.sock`, or in the temporary directory.
+pub fn default_socket() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map_or_else(std::env::temp_dir, PathBuf::from)
+        .join("nmea-monitor.sock")
+}
+
+/// Binds a Unix socket, first removing one left behind by an instance that did not shut
+/// down cleanly. A socket that still accepts connections is left alone.
+pub async fn bind(path: &Path) -> Result<UnixListener> {
+    if UnixStream::connect(path).await.is_err() {
+        let _ = std::fs::remove_file(path);
+    }
+    UnixListener::bind(path).with_context(|| format!("Failed to bind {}", path.display()))
+}
+
+/// Accepts clients on a listener bound with [`bind`] until an error occurs.
+pub async fn serve(listener: UnixListener, engine: &Engine) -> Result<()> {
+    let backlog = Backlog::default();
+    {
+        let backlog = Arc::clone(&backlog);
+        let mut lines = engine.lines.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match lines.recv().await {
+                    Ok(line) => {
+                        let mut backlog = backlog.lock().await;
+                        if backlog.len() == BACKLOG_LEN {
+                            backlog.pop_front();
+                        }
+                        backlog.push_back((SystemTime::now(), line));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        info!("client connected");
+        let (replay, lines) = {
+            let backlog = backlog.lock().await;
+            let replay = backlog
+                .iter()
+                .map(|(time, line)| extended::format_history(*time, line))
+                .collect();
+            (replay, engine.lines.subscribe())
+        };
+        tokio::spawn(client(stream, replay, lines, engine.sender.clone()));
+    }
+}
+
+async fn client(
+    stream: UnixStream,
+    replay: Vec<String>,
+    mut lines: broadcast::Receiver<String>,
+    sender: Option<UnboundedSender<String>>,
+) {
+    let (read, mut write) = stream.into_split();
+
+    tokio::spawn(async move {
+        let mut commands = BufReader::new(read).lines();
+        while let Ok(Some(command)) = commands.next_line().await {
+            if let Some(sender) = &sender {
+                let _ = sender.send(format!("{}\r\n", command.trim_end()));
+            }
+        }
+    });
+
+    for line in replay {
+        if write
+            .write_all(format!("{line}\n").as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+    loop {
+        let line = match lines.recv().await {
+            Ok(line) => line,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!(skipped, "client fell behind");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if write
+            .write_all(format!("{line}\n").as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
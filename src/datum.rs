@@ -0,0 +1,93 @@
+//! The geodetic datum from DTM sentences. Everything here assumes WGS84, so positions in
+//! another datum are labelled and alerted on.
+
+use crate::sentence::Sentence;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Datum {
+    /// Datum the positions are given in.
+    pub local: String,
+    pub subdivision: Option<String>,
+    /// Latitude and longitude offset from the reference datum in minutes, and altitude
+    /// offset in meters.
+    pub offset: (f64, f64, f64),
+    /// Datum the offsets are relative to.
+    pub reference: String,
+}
+
+impl Datum {
+    /// Local datum, subdivision, latitude offset, N/S, longitude offset, E/W, altitude
+    /// offset, reference datum.
+    pub fn parse(dtm: &Sentence) -> Option<Datum> {
+        let fields = &dtm.fields;
+        let local = fields.first().filter(|f| !f.is_empty())?.to_string();
+        let offset = |index: usize, negative: &str| {
+            let value = fields.get(index)?.parse::<f64>().ok()?;
+            Some(if fields.get(index + 1) == Some(&negative) {
+                -value
+            } else {
+                value
+            })
+        };
+        Some(Datum {
+            local,
+            subdivision: fields
+                .get(1)
+                .filter(|f| !f.is_empty())
+                .map(|f| f.to_string()),
+            offset: (
+                offset(2, "S").unwrap_or_default(),
+                offset(4, "W").unwrap_or_default(),
+                fields
+                    .get(6)
+                    .and_then(|f| f.parse().ok())
+                    .unwrap_or_default(),
+            ),
+            reference: fields
+                .get(7)
+                .filter(|f| !f.is_empty())
+                .map_or("W84", |f| f)
+                .to_string(),
+        })
+    }
+
+    /// Whether positions are plain WGS84.
+    pub fn is_wgs84(&self) -> bool {
+        self.local == "W84" && self.offset == (0.0, 0.0, 0.0)
+    }
+
+    /// Name of the local datum, with its subdivision.
+    pub fn name(&self) -> String {
+        let name = name(&self.local);
+        match &self.subdivision {
+            Some(subdivision) => format!("{name} {subdivision}"),
+            None => name.to_string(),
+        }
+    }
+
+    /// Explains why positions should not be taken as WGS84.
+    pub fn warning(&self) -> Option<String> {
+        if self.is_wgs84() {
+            return None;
+        }
+        let (lat, lon, alt) = self.offset;
+        Some(format!(
+            "positions are in datum {}, not WGS84 ({lat:+.4}' {lon:+.4}' {alt:+.1} m from {}); \
+             coordinates and exports are off",
+            self.name(),
+            name(&self.reference),
+        ))
+    }
+}
+
+/// The DTM datum codes; anything else is an IHO datum code, shown as is.
+fn name(code: &str) -> &str {
+    match code {
+        "W84" => "WGS84",
+        "W72" => "WGS72",
+        "S85" => "SGS85",
+        "P90" => "PE90",
+        "999" => "user defined",
+        code => code,
+    }
+}
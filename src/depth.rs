@@ -0,0 +1,108 @@
+//! Depth below the keel and the shallow-water alarm, with the trend of the depth over
+//! the last minute.
+//!
+//! Depth comes from DPT and DBT, or from NMEA 2000 and SeaTalk (`$STALK`), as measured
+//! below the transducer. The keel offset of the `[depth]` config, or the negative offset
+//! reported in DPT, turns it into depth below the keel.
+
+#[cfg(feature = "tui")]
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[cfg(feature = "tui")]
+use crate::history::Series;
+use crate::sentence::Sentence;
+
+/// Changes slower than this many meters a minute are shown as steady.
+#[cfg(feature = "tui")]
+const STEADY: f64 = 0.1;
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct DepthConfig {
+    /// Meters from the transducer to the keel, negative as in DPT.
+    ///
+    /// ```toml
+    /// [depth]
+    /// keel_offset = -1.2
+    /// ```
+    pub keel_offset: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct Keel {
+    config: DepthConfig,
+    /// Offset to the keel from the last DPT.
+    reported: Option<f64>,
+    /// Depth below the keel in meters that raises an alert.
+    shallow_alarm: Option<f64>,
+}
+
+impl Keel {
+    pub fn new(config: DepthConfig, shallow_alarm: Option<f64>) -> Keel {
+        Keel {
+            config,
+            reported: None,
+            shallow_alarm,
+        }
+    }
+
+    pub fn set_config(&mut self, config: DepthConfig) {
+        self.config = config;
+    }
+
+    /// Depth, offset, maximum range. Returns the depth below the transducer in meters.
+    pub fn update_dpt(&mut self, dpt: &Sentence) -> Option<f64> {
+        let number = |index: usize| dpt.fields.get(index)?.parse::<f64>().ok();
+        // A positive offset is to the waterline, which says nothing about the keel.
+        if let Some(offset) = number(1).filter(|offset| *offset < 0.0) {
+            self.reported = Some(offset);
+        }
+        number(0)
+    }
+
+    /// Meters from the transducer to the keel, if known.
+    pub fn offset(&self) -> Option<f64> {
+        self.config.keel_offset.or(self.reported)
+    }
+
+    /// Depth below the keel, or below the transducer while the offset is unknown.
+    pub fn below_keel(&self, depth: f64) -> f64 {
+        depth + self.offset().unwrap_or(0.0)
+    }
+
+    /// Depth below the keel when it is less than the alarm allows.
+    pub fn shallow(&self, depth: f64) -> Option<f64> {
+        let below = self.below_keel(depth);
+        (below < self.shallow_alarm?).then_some(below)
+    }
+}
+
+/// Change of the depth in meters a minute: the last 20 seconds against the 20 seconds a
+/// minute before.
+#[cfg(feature = "tui")]
+pub fn trend(depth: &Series) -> Option<f64> {
+    let mean = |from: u64, to: u64| {
+        let (sum, count) = depth
+            .recent(Duration::from_secs(to))
+            .filter(|(age, _)| *age >= Duration::from_secs(from))
+            .fold((0.0, 0), |(sum, count), (_, value)| {
+                (sum + value, count + 1)
+            });
+        (count > 0).then(|| sum / f64::from(count))
+    };
+    Some(mean(0, 20)? - mean(60, 80)?)
+}
+
+/// Arrow of the way the bottom moves: up as the water gets shallower.
+#[cfg(feature = "tui")]
+pub fn arrow(trend: f64) -> &'static str {
+    if trend <= -STEADY {
+        "↑"
+    } else if trend >= STEADY {
+        "↓"
+    } else {
+        "→"
+    }
+}
@@ -0,0 +1,191 @@
+//! MediaTek/Quectel `$PMTK` device messages, and the commands we sent to the device.
+
+use std::{collections::VecDeque, time::SystemTime};
+
+use crate::sentence::Sentence;
+
+/// Number of received messages and sent commands kept for display.
+const MESSAGES_LEN: usize = 50;
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct DeviceMessage {
+    pub raw: String,
+    /// Human readable meaning, when known.
+    pub description: Option<String>,
+    pub received_at: SystemTime,
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct SentCommand {
+    /// Sentence as sent, including `$` and checksum.
+    pub raw: String,
+    /// Address such as `PMTK220`, used to match acknowledgements.
+    pub address: String,
+    pub sent_at: SystemTime,
+    /// Acknowledgement or response from the device.
+    pub reply: Option<Reply>,
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct Reply {
+    pub text: String,
+    /// Whether the device accepted the command.
+    pub ok: bool,
+}
+
+#[derive(Default, Debug)]
+pub struct DeviceMessages {
+    received: VecDeque<DeviceMessage>,
+    sent: VecDeque<SentCommand>,
+}
+
+impl DeviceMessages {
+    /// Records a sentence written to the device.
+    pub fn sent(&mut self, raw: &str) {
+        let Some(sentence) = Sentence::parse(raw) else {
+            return;
+        };
+        push_front(
+            &mut self.sent,
+            SentCommand {
+                raw: raw.to_string(),
+                address: format!("{}{}", sentence.talker, sentence.kind),
+                sent_at: SystemTime::now(),
+                reply: None,
+            },
+        );
+    }
+
+    pub fn update(&mut self, raw: &str, sentence: &Sentence) {
+        let Some(number) = sentence
+            .kind
+            .strip_prefix("PMTK")
+            .and_then(|n| n.parse::<u16>().ok())
+        else {
+            return;
+        };
+
+        let description = match number {
+            1 => self.acknowledge(sentence),
+            10 => sentence.fields.first().map(|code| {
+                match *code {
+                    "001" => "startup",
+                    "002" => "EPO aiding requested",
+                    "003" => "transition to normal operation",
+                    _ => "unknown system message",
+                }
+                .to_string()
+            }),
+            11 => Some(format!("text: {}", sentence.fields.join(","))),
+            // Responses to 4xx/6xx queries are numbered 100 higher.
+            500..=599 | 700..=799 => {
+                let reply = format!("{},{}", sentence.kind, sentence.fields.join(","));
+                self.reply_to(
+                    &format!("PMTK{:03}", number - 100),
+                    Reply {
+                        text: reply,
+                        ok: true,
+                    },
+                );
+                Some(format!("response to PMTK{:03}", number - 100))
+            }
+            _ => None,
+        };
+
+        push_front(
+            &mut self.received,
+            DeviceMessage {
+                raw: raw.to_string(),
+                description,
+                received_at: SystemTime::now(),
+            },
+        );
+    }
+
+    /// `$PMTK001,cmd,flag`: acknowledgement of command `cmd`.
+    fn acknowledge(&mut self, ack: &Sentence) -> Option<String> {
+        let command = ack.fields.first()?.parse::<u16>().ok()?;
+        let result = match *ack.fields.get(1)? {
+            "0" => "invalid command",
+            "1" => "unsupported command",
+            "2" => "valid command, action failed",
+            "3" => "valid command, action succeeded",
+            _ => "unknown result",
+        };
+        let address = format!("PMTK{command:03}");
+        self.reply_to(
+            &address,
+            Reply {
+                text: result.to_string(),
+                ok: ack.fields.get(1) == Some(&"3"),
+            },
+        );
+        Some(format!("{address}: {result}"))
+    }
+
+    /// Attaches a reply to the latest unanswered command with `address`.
+    fn reply_to(&mut self, address: &str, reply: Reply) {
+        if let Some(command) = self
+            .sent
+            .iter_mut()
+            .find(|command| command.address == address && command.reply.is_none())
+        {
+            command.reply = Some(reply);
+        }
+    }
+
+    /// Received messages, newest first.
+    #[cfg(feature = "tui")]
+    pub fn received(&self) -> impl Iterator<Item = &DeviceMessage> {
+        self.received.iter()
+    }
+
+    /// Sent commands, newest first.
+    #[cfg(feature = "tui")]
+    pub fn sent_commands(&self) -> impl Iterator<Item = &SentCommand> {
+        self.sent.iter()
+    }
+}
+
+fn push_front<T>(queue: &mut VecDeque<T>, item: T) {
+    if queue.len() == MESSAGES_LEN {
+        queue.pop_back();
+    }
+    queue.push_front(item);
+}
+
+/// Frames a sentence body such as `PMTK220,1000` with `$`, checksum and CRLF, or `None`
+/// if there is no body to send.
+#[cfg(feature = "tui")]
+pub fn frame(body: &str) -> Option<String> {
+    let body = body.trim().trim_start_matches('$');
+    let body = body.split_once('*').map_or(body, |(body, _)| body).trim();
+    (!body.is_empty()).then(|| format!("${body}*{:02X}\r\n", crate::sentence::checksum(body)))
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_bodies() {
+        assert_eq!(
+            frame("PMTK220,1000").as_deref(),
+            Some("$PMTK220,1000*1F\r\n")
+        );
+        assert_eq!(
+            frame(" $PMTK220,1000*00 ").as_deref(),
+            Some("$PMTK220,1000*1F\r\n")
+        );
+    }
+
+    #[test]
+    fn empty_bodies_are_not_sent() {
+        for body in ["", "  ", "$", "$*00", "*"] {
+            assert_eq!(frame(body), None, "{body:?}");
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! The monitor's own resource usage and pipeline latency, for long unattended runs: CPU
+//! and memory from `/proc/self` on Linux, the depths of the queues between the tasks,
+//! and how long lines wait for and take to decode.
+
+use std::time::{Duration, Instant};
+
+/// Clock ticks per second of `/proc/self/stat` times, which is 100 on every Linux
+/// platform in use.
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS: f64 = 100.0;
+
+#[derive(Debug)]
+pub struct Diagnostics {
+    started: Instant,
+    /// Share of one core used since the previous sample, in percent.
+    pub cpu: Option<f64>,
+    /// Resident memory in bytes, at the first sample and now.
+    pub memory: Option<(u64, u64)>,
+    /// CPU seconds used at the previous sample.
+    cpu_time: Option<(Instant, f64)>,
+    /// Lines read but not yet recorded, out of the capacity.
+    pub received: (usize, usize),
+    /// Lines decoded but not yet taken by the slowest subscriber.
+    pub subscribers: usize,
+    /// Time lines spent in the decode queue, over the previous sample period.
+    pub wait: Latency,
+    /// Time decoding a line took, from waiting for the status to being done with it, over
+    /// the previous sample period. Lines rejected before decoding count too.
+    pub decode: Latency,
+    waiting: Latency,
+    decoding: Latency,
+}
+
+/// Mean and maximum of a number of durations.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Latency {
+    pub count: u32,
+    total: Duration,
+    pub max: Duration,
+}
+
+impl Latency {
+    fn add(&mut self, duration: Duration) {
+        self.count += 1;
+        self.total += duration;
+        self.max = self.max.max(duration);
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.total / self.count)
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Diagnostics {
+            started: Instant::now(),
+            cpu: None,
+            memory: None,
+            cpu_time: None,
+            received: (0, 0),
+            subscribers: 0,
+            wait: Latency::default(),
+            decode: Latency::default(),
+            waiting: Latency::default(),
+            decoding: Latency::default(),
+        }
+    }
+}
+
+impl Diagnostics {
+    /// Records a line that waited `wait` in the queue and took `decode` to decode or
+    /// reject.
+    pub fn decoded(&mut self, wait: Duration, decode: Duration) {
+        self.waiting.add(wait);
+        self.decoding.add(decode);
+    }
+
+    /// Measures CPU and memory and starts a new latency period. Called every second.
+    pub fn sample(&mut self) {
+        self.wait = std::mem::take(&mut self.waiting);
+        self.decode = std::mem::take(&mut self.decoding);
+
+        let now = Instant::now();
+        if let Some(cpu_time) = cpu_time() {
+            if let Some((at, previous)) = self.cpu_time {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                if elapsed > 0.0 {
+                    self.cpu = Some((cpu_time - previous) / elapsed * 100.0);
+                }
+            }
+            self.cpu_time = Some((now, cpu_time));
+        }
+        if let Some(memory) = memory() {
+            let first = self.memory.map_or(memory, |(first, _)| first);
+            self.memory = Some((first, memory));
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// CPU and resident memory in a few words, e.g. `cpu 1.2%, 14.3 MB`.
+    pub fn usage(&self) -> String {
+        let cpu = self
+            .cpu
+            .map_or_else(|| "-".to_string(), |cpu| format!("{cpu:.1}%"));
+        let memory = self.memory.map_or_else(
+            || "-".to_string(),
+            |(_, now)| format!("{:.1} MB", now as f64 / (1 << 20) as f64),
+        );
+        format!("cpu {cpu}, {memory}")
+    }
+}
+
+/// User and system CPU seconds used by the process.
+#[cfg(target_os = "linux")]
+fn cpu_time() -> Option<f64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // The command name in parentheses may contain spaces; fields follow its end.
+    let fields = stat.get(stat.rfind(')')? + 2..)?;
+    let mut fields = fields.split(' ').skip(11);
+    let user = fields.next()?.parse::<f64>().ok()?;
+    let system = fields.next()?.parse::<f64>().ok()?;
+    Some((user + system) / CLOCK_TICKS)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_time() -> Option<f64> {
+    None
+}
+
+/// Resident set size in bytes.
+#[cfg(target_os = "linux")]
+fn memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn memory() -> Option<u64> {
+    None
+}
@@ -0,0 +1,265 @@
+//! Digital Selective Calling (DSC/DSE) messages relayed by VHF radios.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime},
+};
+
+use tokio::time::Instant;
+
+use crate::sentence::Sentence;
+
+/// Number of calls kept for display.
+const CALLS_LEN: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    Routine,
+    Safety,
+    Urgency,
+    Distress,
+}
+
+impl Category {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Routine => "routine",
+            Self::Safety => "safety",
+            Self::Urgency => "urgency",
+            Self::Distress => "DISTRESS",
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct DscCall {
+    /// Calling station, or the vessel in distress for distress relays.
+    pub mmsi: u64,
+    pub category: Category,
+    /// Nature of distress for distress calls.
+    pub nature: Option<&'static str>,
+    pub position_request: bool,
+    pub position: Option<(f64, f64)>,
+    pub received_at: SystemTime,
+    heard_at: Instant,
+    /// The position was refined by a DSE already.
+    refined: bool,
+}
+
+impl DscCall {
+    /// Calls that should be brought to the user's attention.
+    pub fn is_alert(&self) -> bool {
+        matches!(self.category, Category::Distress | Category::Urgency)
+    }
+}
+
+#[derive(Debug)]
+pub struct DscCalls {
+    calls: VecDeque<DscCall>,
+    /// How long a call stays listed, and alerts.
+    expiry: Duration,
+}
+
+impl DscCalls {
+    pub fn new(expiry: Duration) -> DscCalls {
+        DscCalls {
+            calls: VecDeque::new(),
+            expiry,
+        }
+    }
+
+    pub fn update(&mut self, sentence: &Sentence) {
+        let expiry = self.expiry;
+        self.calls.retain(|call| call.heard_at.elapsed() < expiry);
+        match sentence.kind {
+            "DSC" => {
+                if let Some(call) = parse_dsc(sentence) {
+                    if self.calls.len() == CALLS_LEN {
+                        self.calls.pop_back();
+                    }
+                    self.calls.push_front(call);
+                }
+            }
+            "DSE" => self.apply_dse(sentence),
+            _ => {}
+        }
+    }
+
+    /// Refines the position of the latest call from the same station with the extra
+    /// minute decimals carried by DSE, once, as radios may repeat the DSE.
+    fn apply_dse(&mut self, dse: &Sentence) {
+        // total, number, query flag, mmsi, then (code, data) pairs
+        let Some(mmsi) = dse.fields.get(3).and_then(|f| f.parse::<u64>().ok()) else {
+            return;
+        };
+        let mmsi = mmsi / 10;
+        let Some(data) = dse.fields[4..]
+            .chunks_exact(2)
+            .find(|pair| pair[0] == "00")
+            .map(|pair| pair[1])
+            .filter(|data| data.len() == 8 && data.bytes().all(|b| b.is_ascii_digit()))
+        else {
+            return;
+        };
+        let Some(call) = self
+            .calls
+            .iter_mut()
+            .find(|call| call.mmsi == mmsi)
+            .filter(|call| !call.refined)
+        else {
+            return;
+        };
+        let Some((lat, lon)) = call.position.as_mut() else {
+            return;
+        };
+
+        let (Ok(lat_extra), Ok(lon_extra)) = (data[..4].parse::<f64>(), data[4..].parse::<f64>())
+        else {
+            return;
+        };
+        *lat += lat.signum() * lat_extra / 10_000.0 / 60.0;
+        *lon += lon.signum() * lon_extra / 10_000.0 / 60.0;
+        call.refined = true;
+    }
+
+    /// Calls heard within the expiry time, newest first.
+    pub fn iter(&self) -> impl Iterator<Item = &DscCall> {
+        self.calls
+            .iter()
+            .filter(|call| call.heard_at.elapsed() < self.expiry)
+    }
+}
+
+fn parse_dsc(dsc: &Sentence) -> Option<DscCall> {
+    // format, address, category, nature/telecommand 1, telecommand 2, position,
+    // time, mmsi in distress, nature in distress, acknowledgement, expansion
+    let format = dsc.fields.first()?;
+    let category = match *dsc.fields.get(2)? {
+        "12" => Category::Distress,
+        "10" => Category::Urgency,
+        "08" => Category::Safety,
+        _ if *format == "12" => Category::Distress,
+        _ => Category::Routine,
+    };
+
+    // Addresses are sent as ten digits with a trailing zero.
+    let address = dsc.fields.get(1)?.parse::<u64>().ok()? / 10;
+    let relayed = dsc
+        .fields
+        .get(7)
+        .and_then(|f| f.parse::<u64>().ok())
+        .map(|mmsi| mmsi / 10);
+
+    let nature_code = if *format == "12" {
+        dsc.fields.get(3)
+    } else {
+        dsc.fields.get(8)
+    };
+    let nature = match category {
+        Category::Distress => nature_code.copied().and_then(nature_of_distress),
+        _ => None,
+    };
+
+    Some(DscCall {
+        mmsi: relayed.unwrap_or(address),
+        category,
+        nature,
+        position_request: dsc.fields.get(3) == Some(&"21"),
+        position: dsc.fields.get(5).copied().and_then(parse_position),
+        received_at: SystemTime::now(),
+        heard_at: Instant::now(),
+        refined: false,
+    })
+}
+
+/// Decodes the ten digit DSC position: quadrant, latitude `ddmm` and longitude `dddmm`.
+fn parse_position(field: &str) -> Option<(f64, f64)> {
+    if field.len() != 10 || !field.bytes().all(|b| b.is_ascii_digit()) || field == "9999999999" {
+        return None;
+    }
+    let lat = field[1..3].parse::<f64>().ok()? + field[3..5].parse::<f64>().ok()? / 60.0;
+    let lon = field[5..8].parse::<f64>().ok()? + field[8..10].parse::<f64>().ok()? / 60.0;
+    match &field[..1] {
+        "0" => Some((lat, lon)),
+        "1" => Some((lat, -lon)),
+        "2" => Some((-lat, lon)),
+        "3" => Some((-lat, -lon)),
+        _ => None,
+    }
+}
+
+fn nature_of_distress(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "00" => "fire, explosion",
+        "01" => "flooding",
+        "02" => "collision",
+        "03" => "grounding",
+        "04" => "listing",
+        "05" => "sinking",
+        "06" => "disabled and adrift",
+        "07" => "undesignated",
+        "08" => "abandoning ship",
+        "09" => "piracy",
+        "10" => "man overboard",
+        "12" => "EPIRB emission",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn sentence<'a>(kind: &'a str, fields: &[&'a str]) -> Sentence<'a> {
+        Sentence {
+            talker: "CD",
+            kind,
+            fields: fields.to_vec(),
+        }
+    }
+
+    /// A distress call from 211234567 at 35°50'N 139°45'E.
+    fn distress() -> Sentence<'static> {
+        sentence(
+            "DSC",
+            &[
+                "12",
+                "2112345670",
+                "12",
+                "01",
+                "",
+                "0355013945",
+                "",
+                "",
+                "",
+                "",
+                "E",
+            ],
+        )
+    }
+
+    #[test]
+    fn dse_refines_the_position_once() {
+        let mut calls = DscCalls::new(HOUR);
+        calls.update(&distress());
+        let dse = sentence("DSE", &["1", "1", "A", "2112345670", "00", "30003000"]);
+        calls.update(&dse);
+        let (lat, lon) = calls.iter().next().unwrap().position.unwrap();
+        let expected = (35.0 + 50.3 / 60.0, 139.0 + 45.3 / 60.0);
+        assert!((lat - expected.0).abs() < 1e-9 && (lon - expected.1).abs() < 1e-9);
+        calls.update(&dse);
+        assert_eq!(calls.iter().next().unwrap().position, Some((lat, lon)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn calls_expire() {
+        let mut calls = DscCalls::new(HOUR);
+        calls.update(&distress());
+        assert_eq!(calls.iter().count(), 1);
+        tokio::time::advance(HOUR).await;
+        assert_eq!(calls.iter().count(), 0);
+    }
+}
@@ -0,0 +1,239 @@
+//! Reading, merging and decoding input, independent of the UI so that the TUI and daemon
+//! mode run the same engine.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    io::AsyncWriteExt as _,
+    sync::{broadcast, mpsc, RwLock},
+};
+use tracing::{debug, warn};
+
+#[cfg(feature = "net")]
+use crate::aprs;
+#[cfg(feature = "export")]
+use crate::geojson;
+use crate::{
+    capture::Capture,
+    config::Config,
+    extended,
+    queue::Queue,
+    record::{Recorder, Recording},
+    reload::{self, Reload},
+    replay::Replay,
+    source::{Dedup, Source},
+    status::NmeaStatus,
+    Args,
+};
+
+/// Lines buffered for slow subscribers of [`Engine::lines`].
+const LINES_CAPACITY: usize = 1024;
+
+/// Lines read ahead of recording. Readers wait when it is full.
+const RECEIVED_CAPACITY: usize = 1024;
+
+/// How often history is sampled and alerts are checked for triggering a capture.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct Engine {
+    pub nmea: Arc<RwLock<NmeaStatus>>,
+    /// Recording and capture of the raw input, written by the recording task.
+    pub recording: Arc<Mutex<Recording>>,
+    /// Sentences sent here are written to the device, when a source is writable.
+    pub sender: Option<mpsc::UnboundedSender<String>>,
+    /// Every line applied to the status, after deduplication and failover.
+    pub lines: broadcast::Sender<String>,
+    /// Reloading of the `--config` file.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub reload: Option<Reload>,
+}
+
+impl Engine {
+    /// Opens the sources and spawns the reader, merge and writer tasks.
+    pub async fn start(args: &Args, config: &Config) -> Engine {
+        let mut status = NmeaStatus::new(args, config);
+        let mut recorder =
+            Recorder::new(args.record_rotate, args.record_gzip, args.record_extended);
+        if let Some(path) = &args.record {
+            recorder.start(path).expect("Failed to start recording.");
+        }
+        let recording = Arc::new(Mutex::new(Recording {
+            recorder,
+            capture: args
+                .capture
+                .clone()
+                .map(|path| Capture::new(path, args.capture_window.into())),
+        }));
+        #[cfg(feature = "script")]
+        for path in &args.script {
+            status.scripts.load(path).expect("Failed to load script.");
+        }
+        for spec in &args.plugin {
+            status
+                .plugins
+                .enable(spec)
+                .expect("Failed to enable plugin.");
+        }
+        let sources = args.sources();
+        if let Some(speed) = args.replay {
+            let file = sources
+                .iter()
+                .enumerate()
+                .find_map(|(index, source)| match source {
+                    Source::File(path) => Some((index, path)),
+                    _ => None,
+                });
+            if let Some((index, path)) = file {
+                status.replay =
+                    Some(Replay::new(index, path, speed).expect("Failed to start replay."));
+            }
+        }
+        let nmea = Arc::new(RwLock::new(status));
+        let (lines, mut received) = mpsc::channel(RECEIVED_CAPACITY);
+        let (applied, _) = broadcast::channel(LINES_CAPACITY);
+
+        let mut writer = None;
+        for (index, source) in sources.iter().enumerate() {
+            let opened = source
+                .spawn(
+                    index,
+                    args.writable() && writer.is_none(),
+                    lines.clone(),
+                    Arc::clone(&nmea),
+                )
+                .await;
+            // With several sources, one failing is shown on the sources screen instead.
+            let opened = if sources.len() == 1 {
+                opened.expect("Failed to open source.")
+            } else {
+                opened.unwrap_or_default()
+            };
+            writer = writer.or(opened);
+        }
+        drop(lines);
+
+        let sender = writer.map(|mut writer| {
+            let (sender, mut outgoing) = mpsc::unbounded_channel::<String>();
+            let nmea = Arc::clone(&nmea);
+            tokio::spawn(async move {
+                while let Some(sentence) = outgoing.recv().await {
+                    match writer.write_all(sentence.as_bytes()).await {
+                        Ok(()) => {
+                            debug!(sentence = sentence.trim_end(), "sent");
+                            nmea.write().await.device.sent(sentence.trim_end());
+                        }
+                        Err(e) => warn!(sentence = sentence.trim_end(), "failed to send: {e}"),
+                    }
+                }
+            });
+            sender
+        });
+
+        // Lines are recorded as they arrive and queued for decoding, which may drop them.
+        // Neither takes the status lock, which the decoder holds.
+        let queue = Arc::new(Queue::new(args.queue_capacity as usize, args.drop_policy));
+        let backlog = Arc::new(AtomicUsize::new(0));
+        {
+            let recording = Arc::clone(&recording);
+            let queue = Arc::clone(&queue);
+            let backlog = Arc::clone(&backlog);
+            tokio::spawn(async move {
+                while let Some((index, line)) = received.recv().await {
+                    backlog.store(received.len(), Ordering::Relaxed);
+                    // History was recorded, if at all, where it was received.
+                    if extended::parse_history(&line).is_none() {
+                        recording
+                            .lock()
+                            .expect("recording lock poisoned")
+                            .write(index, &line);
+                    }
+                    queue.push((index, line)).await;
+                }
+            });
+        }
+
+        {
+            let nmea = Arc::clone(&nmea);
+            let applied = applied.clone();
+            let mut dedup = Dedup::new(args.dedup_window.into());
+            tokio::spawn(async move {
+                loop {
+                    let ((index, line), wait) = queue.pop().await;
+                    // Waiting for the status is part of what a line costs to decode.
+                    let started = Instant::now();
+                    let mut nmea = nmea.write().await;
+                    nmea.queued.0 = queue.len();
+                    nmea.queue_drops = queue.drops();
+                    nmea.diagnostics.received =
+                        (backlog.load(Ordering::Relaxed), RECEIVED_CAPACITY);
+                    if let Some((time, line)) = extended::parse_history(&line) {
+                        nmea.raw_log.push_at(time, line);
+                    } else if nmea.receive(index, &line) {
+                        if dedup.is_duplicate(index, &line) {
+                            nmea.duplicates += 1;
+                        } else {
+                            nmea.handle_line(&line);
+                            // Nobody listening is fine.
+                            let _ = applied.send(line);
+                            nmea.diagnostics.subscribers = applied.len();
+                        }
+                    }
+                    nmea.diagnostics.decoded(wait, started.elapsed());
+                }
+            });
+        }
+
+        {
+            let nmea = Arc::clone(&nmea);
+            let recording = Arc::clone(&recording);
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(TICK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let tick = {
+                        let mut nmea = nmea.write().await;
+                        let tick = nmea.tick();
+                        nmea.diagnostics.sample();
+                        tick
+                    };
+                    let mut recording = recording.lock().expect("recording lock poisoned");
+                    if tick.split {
+                        recording.recorder.split();
+                    }
+                    if let Some(capture) = &mut recording.capture {
+                        capture.check(tick.conditions);
+                    }
+                }
+            });
+        }
+
+        #[cfg(feature = "net")]
+        if let Some(config) = args.aprs() {
+            aprs::spawn(config, Arc::clone(&nmea));
+        }
+
+        #[cfg(feature = "export")]
+        if let Some((path, interval)) = args.geojson.clone().zip(args.geojson_interval) {
+            geojson::spawn(path, interval.into(), Arc::clone(&nmea));
+        }
+
+        let reload = args
+            .config
+            .clone()
+            .map(|path| reload::spawn(args.clone(), path, Arc::clone(&nmea)));
+
+        Engine {
+            nmea,
+            recording,
+            sender,
+            lines: applied,
+            reload,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Extended log format, recorded with `--record-extended`: each line is prefixed with
+//! the time it was received and the index of the source it came from:
+//!
+//! ```text
+//! @1729080000.123 1 $GPRMC,...
+//! ```
+//!
+//! File sources read both formats. Lines of an extended log are attributed to one source
+//! per recorded index, and replayed at the pace they were received.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Prefixes `line` with its receive time and source.
+pub fn format(time: SystemTime, source: usize, line: &str) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!(
+        "@{}.{:03} {source} {line}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Receive time, source index and the line itself, if `line` is in the extended format.
+pub fn parse(line: &str) -> Option<(SystemTime, usize, &str)> {
+    let rest = line.strip_prefix('@')?;
+    let (time, rest) = rest.split_once(' ')?;
+    let (source, line) = rest.split_once(' ')?;
+    let seconds = time
+        .parse::<f64>()
+        .ok()
+        .filter(|s| s.is_finite() && *s >= 0.0)?;
+    Some((
+        UNIX_EPOCH + Duration::from_secs_f64(seconds),
+        source.parse().ok()?,
+        line,
+    ))
+}
+
+/// Marks a line received at `time` as history, to be shown but not applied. An `attach`
+/// client gets the daemon's backlog like this, so stale values are not taken as live.
+pub fn format_history(time: SystemTime, line: &str) -> String {
+    format!("#{}", format(time, 0, line))
+}
+
+/// Receive time and the line itself, if `line` was marked with [`format_history`].
+pub fn parse_history(line: &str) -> Option<(SystemTime, &str)> {
+    let (time, _, line) = parse(line.strip_prefix('#')?)?;
+    Some((time, line))
+}
+
+/// The line without its extended prefix.
+pub fn strip(line: &str) -> &str {
+    parse(line).map_or(line, |(_, _, line)| line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_keeps_the_receive_time() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_729_080_000);
+        let line = format_history(time, "$GPRMC,1*00");
+        assert_eq!(parse_history(&line), Some((time, "$GPRMC,1*00")));
+        assert_eq!(parse(&line), None);
+        assert_eq!(parse_history("$GPRMC,1*00"), None);
+        assert_eq!(parse_history(&format(time, 0, "$GPRMC,1*00")), None);
+    }
+}
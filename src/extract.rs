@@ -0,0 +1,123 @@
+//! Custom values pulled out of matching lines, for proprietary sentences without
+//! explicit support.
+//!
+//! ```toml
+//! [[extract]]
+//! label = "RTK ratio"
+//! sentence = "PQTMEPE"
+//! field = 7
+//!
+//! [[extract]]
+//! label = "board temp"
+//! regex = '^\$PXYZ,TEMP,([-\d.]+)'
+//! unit = "°C"
+//! ```
+
+use std::time::Duration;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{raw_log, sentence::Sentence, status::StatusValue};
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ExtractConfig {
+    pub label: String,
+    /// The first capture group, or the whole match, is the value.
+    pub regex: Option<Pattern>,
+    /// Address of the sentences to take `field` from, such as `PQTMEPE` or `GNGGA`. Also
+    /// limits `regex` to these sentences.
+    pub sentence: Option<String>,
+    /// Position of the field after the address, starting at 1.
+    pub field: Option<usize>,
+    /// Appended to the value.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub unit: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Regex::new(&pattern).map(Pattern)
+    }
+}
+
+#[derive(Debug)]
+pub struct Extract {
+    pub config: ExtractConfig,
+    pub value: StatusValue<String>,
+}
+
+impl Extract {
+    fn value(&self, line: &str) -> Option<String> {
+        if let Some(address) = &self.config.sentence {
+            if raw_log::address(line).as_ref() != Some(address) {
+                return None;
+            }
+        }
+        if let Some(Pattern(regex)) = &self.config.regex {
+            let captures = regex.captures(line)?;
+            let found = captures.get(1).or_else(|| captures.get(0))?;
+            return Some(found.as_str().to_string());
+        }
+        let field = self.config.field?;
+        let sentence = Sentence::parse(line)?;
+        Some(sentence.fields.get(field.checked_sub(1)?)?.to_string())
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Extracts(Vec<Extract>);
+
+impl Extracts {
+    pub fn new(configs: &[ExtractConfig], timeout: Duration) -> Extracts {
+        Extracts(
+            configs
+                .iter()
+                .map(|config| Extract {
+                    config: config.clone(),
+                    value: StatusValue::new(timeout),
+                })
+                .collect(),
+        )
+    }
+
+    /// Replaces the extracts with those of a reloaded config, keeping the values of the
+    /// ones whose label is unchanged.
+    pub fn set_configs(&mut self, configs: &[ExtractConfig], timeout: Duration) {
+        let mut old = std::mem::take(&mut self.0);
+        self.0 = configs
+            .iter()
+            .map(|config| {
+                let value = old
+                    .iter()
+                    .position(|extract| extract.config.label == config.label)
+                    .map(|i| old.swap_remove(i).value)
+                    .unwrap_or_else(|| StatusValue::new(timeout));
+                Extract {
+                    config: config.clone(),
+                    value,
+                }
+            })
+            .collect();
+    }
+
+    pub fn update(&mut self, line: &str) {
+        for extract in &mut self.0 {
+            if let Some(value) = extract.value(line) {
+                extract.value.update(value);
+            }
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn iter(&self) -> impl Iterator<Item = &Extract> {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,115 @@
+//! Smoothing of derived and received values between decoding and display.
+//!
+//! Course and speed are filtered together as a velocity vector, so that course wraps
+//! around north correctly and a noisy course at low speed carries little weight.
+
+use std::collections::VecDeque;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::geo;
+
+#[derive(ValueEnum, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Smoothing {
+    /// Show values as received.
+    #[default]
+    None,
+    /// Exponential moving average with the weight of a `samples` long window.
+    Exponential,
+    /// Mean of the last `samples` values.
+    MovingAverage,
+}
+
+#[derive(Debug)]
+pub struct VelocityFilter {
+    smoothing: Smoothing,
+    samples: usize,
+    window: VecDeque<(f64, f64)>,
+    average: Option<(f64, f64)>,
+}
+
+impl VelocityFilter {
+    pub fn new(smoothing: Smoothing, samples: usize) -> VelocityFilter {
+        VelocityFilter {
+            smoothing,
+            samples: samples.max(1),
+            window: VecDeque::new(),
+            average: None,
+        }
+    }
+
+    /// Adds a speed (knots) and course (degrees) sample and returns the smoothed pair.
+    pub fn update(&mut self, sog: f64, cog: f64) -> (f64, f64) {
+        let velocity = geo::displacement(cog, sog);
+        let (x, y) = match self.smoothing {
+            Smoothing::None => return (sog, cog),
+            Smoothing::Exponential => {
+                let alpha = 2.0 / (self.samples as f64 + 1.0);
+                let (x, y) = self.average.map_or(velocity, |(x, y)| {
+                    (x + alpha * (velocity.0 - x), y + alpha * (velocity.1 - y))
+                });
+                self.average = Some((x, y));
+                (x, y)
+            }
+            Smoothing::MovingAverage => {
+                if self.window.len() == self.samples {
+                    self.window.pop_front();
+                }
+                self.window.push_back(velocity);
+                let n = self.window.len() as f64;
+                let (x, y) = self
+                    .window
+                    .iter()
+                    .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+                (x / n, y / n)
+            }
+        };
+        (x.hypot(y), x.atan2(y).to_degrees().rem_euclid(360.0))
+    }
+
+    /// Forgets past samples, e.g. when speed or course becomes unavailable.
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.average = None;
+    }
+}
+
+/// Climb rate from successive altitudes, exponentially smoothed over about `samples`
+/// readings.
+#[derive(Debug)]
+pub struct VerticalSpeed {
+    alpha: f64,
+    last: Option<(f64, Instant)>,
+    rate: Option<f64>,
+}
+
+impl VerticalSpeed {
+    pub fn new(samples: usize) -> VerticalSpeed {
+        VerticalSpeed {
+            alpha: 2.0 / (samples.max(1) as f64 + 1.0),
+            last: None,
+            rate: None,
+        }
+    }
+
+    /// Adds an altitude in meters and returns the smoothed climb rate in m/s.
+    pub fn update(&mut self, alt: f64) -> Option<f64> {
+        let now = Instant::now();
+        let Some((last_alt, last_at)) = self.last else {
+            self.last = Some((alt, now));
+            return None;
+        };
+        let dt = now.duration_since(last_at).as_secs_f64();
+        // Several sentences can carry the same fix.
+        if dt < 0.05 {
+            return self.rate;
+        }
+        let rate = (alt - last_alt) / dt;
+        self.rate = Some(self.rate.map_or(rate, |r| r + self.alpha * (rate - r)));
+        self.last = Some((alt, now));
+        self.rate
+    }
+}
@@ -0,0 +1,112 @@
+//! Display formatting of coordinates, shared by every screen.
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{config::Config, Args};
+
+#[derive(ValueEnum, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordinateFormat {
+    /// Signed decimal degrees, `35.681236`.
+    #[default]
+    Decimal,
+    /// Degrees and decimal minutes, `35°40.8742'N`.
+    Dm,
+    /// Degrees, minutes and seconds, `35°40'52.45"N`.
+    Dms,
+}
+
+#[derive(ValueEnum, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum VerticalSpeedUnit {
+    #[default]
+    MetersPerSecond,
+    FeetPerMinute,
+}
+
+impl VerticalSpeedUnit {
+    /// Formats a climb rate given in m/s with an arrow for its direction.
+    #[cfg(feature = "tui")]
+    pub fn format(self, rate: f64) -> String {
+        let arrow = if rate > 0.05 {
+            '↑'
+        } else if rate < -0.05 {
+            '↓'
+        } else {
+            '→'
+        };
+        match self {
+            Self::MetersPerSecond => format!("{arrow}{:.1} m/s", rate.abs()),
+            Self::FeetPerMinute => format!("{arrow}{:.0} ft/min", rate.abs() * 196.850_394),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Coordinates {
+    format: CoordinateFormat,
+    /// Decimal places of the last component (degrees, minutes or seconds).
+    precision: usize,
+}
+
+impl Coordinates {
+    /// Command line options take precedence over the config file.
+    pub fn new(args: &Args, config: &Config) -> Coordinates {
+        let format = args
+            .coordinate_format
+            .or(config.display.coordinate_format)
+            .unwrap_or_default();
+        let precision = args
+            .precision
+            .or(config.display.precision)
+            .unwrap_or(match format {
+                CoordinateFormat::Decimal => 6,
+                CoordinateFormat::Dm => 4,
+                CoordinateFormat::Dms => 2,
+            });
+        Coordinates {
+            format,
+            precision: usize::from(precision),
+        }
+    }
+
+    pub fn lat(&self, lat: f64) -> String {
+        self.format(lat, if lat < 0.0 { 'S' } else { 'N' })
+    }
+
+    pub fn lon(&self, lon: f64) -> String {
+        self.format(lon, if lon < 0.0 { 'W' } else { 'E' })
+    }
+
+    pub fn position(&self, lat: f64, lon: f64) -> String {
+        format!("{}, {}", self.lat(lat), self.lon(lon))
+    }
+
+    fn format(&self, value: f64, hemisphere: char) -> String {
+        let precision = self.precision;
+        let width = if precision == 0 { 2 } else { precision + 3 };
+        match self.format {
+            CoordinateFormat::Decimal => format!("{value:.precision$}"),
+            CoordinateFormat::Dm => {
+                let minutes = round(value.abs() * 60.0, precision);
+                let degrees = (minutes / 60.0).floor();
+                let minutes = minutes - degrees * 60.0;
+                format!("{degrees}°{minutes:0width$.precision$}'{hemisphere}")
+            }
+            CoordinateFormat::Dms => {
+                let seconds = round(value.abs() * 3600.0, precision);
+                let degrees = (seconds / 3600.0).floor();
+                let minutes = ((seconds - degrees * 3600.0) / 60.0).floor();
+                let seconds = seconds - degrees * 3600.0 - minutes * 60.0;
+                format!("{degrees}°{minutes:02}'{seconds:0width$.precision$}\"{hemisphere}")
+            }
+        }
+    }
+}
+
+/// Rounds before splitting into degrees and minutes so that minutes or seconds never
+/// display as 60.
+fn round(value: f64, precision: usize) -> f64 {
+    let scale = 10f64.powi(precision as i32);
+    (value * scale).round() / scale
+}
@@ -0,0 +1,125 @@
+//! Statistics over deliberately corrupted input, to harden the readers and decoders
+//! against noise: every line is accounted to the stage that rejected it, and lines
+//! that make a decoder panic are reported instead of ending the run.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use anyhow::{Context as _, Result};
+
+use crate::{
+    config::Config,
+    input, n2k, sbs,
+    sentence::{Rejection, Sentence},
+    status::NmeaStatus,
+    Args,
+};
+
+/// Number of panicking lines printed.
+const PANICS_SHOWN: usize = 10;
+
+#[derive(Default, Debug)]
+struct Stats {
+    bytes: u64,
+    /// Lines cut at [`input::MAX_LINE_LEN`].
+    overlong: u64,
+    /// Binary data or noise skipped around lines.
+    dropped_bytes: u64,
+    /// Lines with nothing left after dropping noise.
+    noise: u64,
+    framing: u64,
+    checksum: u64,
+    /// Well-formed sentences the `nmea` crate failed to parse.
+    parse: u64,
+    /// Well-formed sentences and NMEA 2000 frames that were applied.
+    accepted: u64,
+    /// Lines that made decoding panic.
+    panics: Vec<String>,
+}
+
+/// Reads `path` (`-` for stdin) like a source would and prints where lines were
+/// rejected.
+pub fn run(args: &Args, config: &Config, path: &Path) -> Result<()> {
+    let reader: Box<dyn Read> = if path == Path::new("-") {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path).with_context(|| format!("Failed to open {}", path.display()))?)
+    };
+    let mut reader = BufReader::new(reader);
+    let mut nmea = NmeaStatus::new(args, config);
+    let mut stats = Stats::default();
+
+    // Panics are counted and shown at the end rather than printed as they happen.
+    panic::set_hook(Box::new(|_| {}));
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let len = input::read_line_blocking(&mut reader, &mut buf)?;
+        if len == 0 {
+            break;
+        }
+        stats.bytes += len as u64;
+        if input::is_cut(&buf) {
+            stats.overlong += 1;
+        }
+        // Blank lines, such as the `\n` of a `\r\n` ending, are not noise.
+        if buf.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let (line, dropped) = input::extract_text(&buf);
+        stats.dropped_bytes += dropped as u64;
+        if line.is_empty() {
+            stats.noise += 1;
+            continue;
+        }
+
+        match Sentence::check(line) {
+            Err(Rejection::Framing) if n2k::parse(line).is_none() && sbs::parse(line).is_none() => {
+                stats.framing += 1;
+                continue;
+            }
+            Err(Rejection::Checksum) => {
+                stats.checksum += 1;
+                continue;
+            }
+            Ok(_) if matches!(nmea::parse_str(line), Err(nmea::Error::ParsingError(_))) => {
+                stats.parse += 1;
+            }
+            _ => stats.accepted += 1,
+        }
+
+        let applied = panic::catch_unwind(AssertUnwindSafe(|| {
+            if nmea.receive(0, line) {
+                nmea.handle_line(line);
+            }
+        }));
+        if applied.is_err() {
+            stats.panics.push(line.to_string());
+        }
+    }
+    let _ = panic::take_hook();
+
+    let lines = stats.noise + stats.framing + stats.checksum + stats.parse + stats.accepted;
+    println!("{} bytes, {lines} lines", stats.bytes);
+    println!(
+        "reader:   {} bytes of noise dropped, {} lines cut at {} bytes, {} lines of only noise",
+        stats.dropped_bytes,
+        stats.overlong,
+        input::MAX_LINE_LEN,
+        stats.noise
+    );
+    println!("framing:  {} rejected", stats.framing);
+    println!("checksum: {} rejected", stats.checksum);
+    println!("parse:    {} rejected", stats.parse);
+    println!("accepted: {}", stats.accepted);
+    println!("panics:   {}", stats.panics.len());
+    for line in stats.panics.iter().take(PANICS_SHOWN) {
+        println!("  {line}");
+    }
+    Ok(())
+}
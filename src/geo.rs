@@ -0,0 +1,80 @@
+//! Small-area geometry helpers. Distances are in meters, angles in degrees.
+
+/// Mean earth radius.
+pub const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// One nautical mile.
+pub const NAUTICAL_MILE: f64 = 1852.0;
+
+/// One knot in meters per second.
+pub const KNOT: f64 = NAUTICAL_MILE / 3600.0;
+
+/// Projects `point` onto a local east/north plane centered at `origin`.
+///
+/// Equirectangular, which is plenty for the few kilometers the map shows.
+pub fn local_xy((origin_lat, origin_lon): (f64, f64), (lat, lon): (f64, f64)) -> (f64, f64) {
+    let x = (lon - origin_lon).to_radians() * origin_lat.to_radians().cos() * EARTH_RADIUS;
+    let y = (lat - origin_lat).to_radians() * EARTH_RADIUS;
+    (x, y)
+}
+
+/// Great-circle distance between two positions, for when they may be far apart.
+pub fn distance((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (lon2 - lon1).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Inverse of [`local_xy`].
+pub fn from_local_xy((origin_lat, origin_lon): (f64, f64), (x, y): (f64, f64)) -> (f64, f64) {
+    let lat = origin_lat + (y / EARTH_RADIUS).to_degrees();
+    let lon = origin_lon + (x / (EARTH_RADIUS * origin_lat.to_radians().cos())).to_degrees();
+    (lat, lon)
+}
+
+/// East/north displacement of travelling `distance` along `course`.
+pub fn displacement(course: f64, distance: f64) -> (f64, f64) {
+    let course = course.to_radians();
+    (distance * course.sin(), distance * course.cos())
+}
+
+/// Course in degrees and length of an east/north vector; the inverse of
+/// [`displacement`].
+pub fn course_and_length((x, y): (f64, f64)) -> (f64, f64) {
+    (x.atan2(y).to_degrees().rem_euclid(360.0), x.hypot(y))
+}
+
+/// Bearing relative to `heading`, from -180° to port to 180° to starboard.
+#[cfg(feature = "tui")]
+pub fn relative_bearing(bearing: f64, heading: f64) -> f64 {
+    let relative = (bearing - heading).rem_euclid(360.0);
+    if relative > 180.0 {
+        relative - 360.0
+    } else {
+        relative
+    }
+}
+
+/// Rotates an east/north vector so that `heading` points up (+y).
+#[cfg(feature = "tui")]
+pub fn rotate((x, y): (f64, f64), heading: f64) -> (f64, f64) {
+    let (sin, cos) = heading.to_radians().sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+/// Closest approach of a body at relative position `(px, py)` moving with relative
+/// velocity `(vx, vy)` (m/s). Returns the distance at closest approach and the time until
+/// it in seconds, which is negative when the closest approach has already passed.
+#[cfg(feature = "ais")]
+pub fn closest_approach((px, py): (f64, f64), (vx, vy): (f64, f64)) -> (f64, f64) {
+    let speed_squared = vx * vx + vy * vy;
+    let time = if speed_squared > 0.0 {
+        -(px * vx + py * vy) / speed_squared
+    } else {
+        0.0
+    };
+    let t = time.max(0.0);
+    ((px + vx * t).hypot(py + vy * t), time)
+}
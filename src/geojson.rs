@@ -0,0 +1,111 @@
+//! GeoJSON export: the track as a LineString and the current position as a Point,
+//! written with `--geojson` on exit and, with `--geojson-interval`, rewritten while
+//! running for web maps to poll.
+//!
+//! The file is replaced atomically, so a reader never sees it half written.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::{clock, convert::Fix, status::NmeaStatus};
+
+pub fn write(path: &Path, nmea: &NmeaStatus) -> Result<()> {
+    replace(path, &to_geojson(nmea))
+}
+
+/// Rewrites `path` every `interval` until the program exits. Failures are retried on
+/// the next round; the last one is kept in the status.
+pub fn spawn(path: PathBuf, interval: Duration, nmea: Arc<RwLock<NmeaStatus>>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            let text = to_geojson(&*nmea.read().await);
+            let result = replace(&path, &text);
+            if let Err(e) = &result {
+                warn!("{e:#}");
+            }
+            nmea.write().await.geojson_error = result.err().map(|e| format!("{e:#}"));
+        }
+    });
+}
+
+fn to_geojson(nmea: &NmeaStatus) -> String {
+    let coordinates = nmea
+        .track
+        .iter()
+        .map(|&(lat, lon)| json!([lon, lat]))
+        .collect::<Vec<_>>();
+    let mut features = vec![json!({
+        "type": "Feature",
+        "geometry": { "type": "LineString", "coordinates": coordinates },
+        "properties": { "name": "track" },
+    })];
+    if let Some(position) = nmea.position() {
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": point(position.lon, position.lat, nmea.alt.get().copied()),
+            },
+            "properties": {
+                "name": "position",
+                "time": nmea.utc.get().copied().map(clock::timestamp),
+                "estimated": position.estimated,
+                "sog": nmea.sog.get(),
+                "cog": nmea.cog.get(),
+                "heading": nmea.hdg.get(),
+            },
+        }));
+    }
+    collection(features)
+}
+
+/// One LineString feature of the fixes, with their times in the `coordTimes` property
+/// that most GeoJSON tools read. For `convert`.
+pub fn fixes_to_geojson(fixes: &[Fix]) -> String {
+    let coordinates = fixes
+        .iter()
+        .map(|fix| point(fix.lon, fix.lat, fix.alt))
+        .collect::<Vec<_>>();
+    let times = fixes
+        .iter()
+        .map(|fix| fix.time.map(clock::timestamp))
+        .collect::<Vec<_>>();
+    collection(vec![json!({
+        "type": "Feature",
+        "geometry": { "type": "LineString", "coordinates": coordinates },
+        "properties": { "coordTimes": times },
+    })])
+}
+
+fn point(lon: f64, lat: f64, alt: Option<f64>) -> Value {
+    match alt {
+        Some(alt) => json!([lon, lat, alt]),
+        None => json!([lon, lat]),
+    }
+}
+
+fn collection(features: Vec<Value>) -> String {
+    let collection = json!({ "type": "FeatureCollection", "features": features });
+    format!("{collection:#}\n")
+}
+
+/// Writes `text` to a temporary file next to `path` and renames it over `path`.
+fn replace(path: &Path, text: &str) -> Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    std::fs::write(&temporary, text)
+        .with_context(|| format!("Failed to write {}", temporary.display()))?;
+    std::fs::rename(&temporary, path)
+        .with_context(|| format!("Failed to replace {}", path.display()))
+}
@@ -0,0 +1,94 @@
+//! GNS fix data, which multi-constellation receivers send instead of GGA, with a mode
+//! character per constellation.
+
+use crate::sentence::Sentence;
+
+/// Constellations in the order of the GNS mode characters (NMEA 4.11).
+const CONSTELLATIONS: [&str; 6] = ["GPS", "GLONASS", "Galileo", "BeiDou", "QZSS", "NavIC"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gns {
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// Altitude above mean sea level in meters.
+    pub alt: Option<f64>,
+    pub satellites: Option<u32>,
+    pub hdop: Option<f64>,
+    /// Fix mode of each constellation that reported one.
+    pub modes: Vec<(&'static str, &'static str)>,
+}
+
+impl Gns {
+    /// Time, latitude, N/S, longitude, E/W, mode, satellites, HDOP, altitude, geoidal
+    /// separation, age of differential data, station id, navigational status.
+    pub fn parse(gns: &Sentence) -> Option<Gns> {
+        let fields = &gns.fields;
+        let number = |index: usize| fields.get(index).and_then(|f| f.parse::<f64>().ok());
+        let modes = fields
+            .get(5)?
+            .chars()
+            .zip(CONSTELLATIONS)
+            .map(|(mode, constellation)| (constellation, mode_name(mode)))
+            .collect::<Vec<_>>();
+        let fixed = modes.iter().any(|&(_, mode)| mode != "no fix");
+        Some(Gns {
+            lat: coordinate(fields, 1, 2, ['N', 'S']).filter(|_| fixed),
+            lon: coordinate(fields, 3, 3, ['E', 'W']).filter(|_| fixed),
+            alt: number(8).filter(|_| fixed),
+            satellites: fields.get(6).and_then(|f| f.parse().ok()),
+            hdop: number(7),
+            modes,
+        })
+    }
+
+    /// The best mode of any constellation, named like the GGA fix types.
+    pub fn fix_type(&self) -> &'static str {
+        const RANKED: [(&str, &str); 8] = [
+            ("RTK", "Rtk"),
+            ("float RTK", "FloatRtk"),
+            ("precise", "Pps"),
+            ("differential", "DGps"),
+            ("autonomous", "Gps"),
+            ("estimated", "Estimated"),
+            ("manual", "Manual"),
+            ("simulator", "Simulation"),
+        ];
+        RANKED
+            .iter()
+            .find(|(mode, _)| self.modes.iter().any(|(_, m)| m == mode))
+            .map_or("Invalid", |&(_, fix)| fix)
+    }
+}
+
+fn mode_name(mode: char) -> &'static str {
+    match mode {
+        'A' => "autonomous",
+        'D' => "differential",
+        'P' => "precise",
+        'R' => "RTK",
+        'F' => "float RTK",
+        'E' => "estimated",
+        'M' => "manual",
+        'S' => "simulator",
+        _ => "no fix",
+    }
+}
+
+/// `ddmm.mmmm` (or `dddmm.mmmm` with three degree digits) at `fields[index]` with its
+/// hemisphere after it, in signed degrees.
+fn coordinate(
+    fields: &[&str],
+    index: usize,
+    degree_digits: usize,
+    [positive, negative]: [char; 2],
+) -> Option<f64> {
+    let raw = fields.get(index)?;
+    let degrees = raw.get(..degree_digits)?.parse::<f64>().ok()?;
+    let minutes = raw.get(degree_digits..)?.parse::<f64>().ok()?;
+    let value = degrees + minutes / 60.0;
+    match fields.get(index + 1)?.chars().next()? {
+        c if c == positive => Some(value),
+        c if c == negative => Some(-value),
+        _ => None,
+    }
+}
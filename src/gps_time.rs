@@ -0,0 +1,120 @@
+//! Sanity checks of receiver times.
+//!
+//! GPS time runs ahead of UTC by the leap seconds inserted since 1980. Receivers are
+//! meant to correct for them, but some report GPS time until they have received the
+//! current count from the almanac, and some always do; `--gps-time` corrects those.
+//!
+//! GPS broadcasts the week number in 10 bits, so it wraps every 1024 weeks (about 19.6
+//! years). Receivers with firmware older than the last wrap report dates that far in the
+//! past. That is detected against the last date that was not, so that a wrong system
+//! clock or a replayed log does not count; only before any date has passed is the system
+//! clock the reference.
+
+use std::time::{Duration, SystemTime};
+
+/// GPS − UTC, unchanged since 2017-01-01.
+pub const LEAP_SECONDS: u64 = 18;
+
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Weeks between wraps of the broadcast week number.
+pub const ROLLOVER_WEEKS: u32 = 1024;
+
+/// How far from a whole number of wraps a date may be and still count as one, allowing
+/// for a system clock that is somewhat off.
+const ROLLOVER_TOLERANCE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug)]
+pub struct TimeCheck {
+    /// Reported times are GPS time.
+    gps_time: bool,
+    /// Wraps of the week number the last reported date is behind by.
+    pub rollovers: Option<u32>,
+    /// The last reported time that was not behind.
+    good: Option<SystemTime>,
+}
+
+impl TimeCheck {
+    pub fn new(gps_time: bool) -> TimeCheck {
+        TimeCheck {
+            gps_time,
+            rollovers: None,
+            good: None,
+        }
+    }
+
+    /// UTC from a reported time, checking it for a week number rollover.
+    pub fn apply(&mut self, reported: SystemTime) -> SystemTime {
+        let utc = if self.gps_time {
+            reported - Duration::from_secs(LEAP_SECONDS)
+        } else {
+            reported
+        };
+        self.rollovers = rollovers(utc, self.good.unwrap_or_else(SystemTime::now));
+        if self.rollovers.is_none() {
+            self.good = Some(utc);
+        }
+        utc
+    }
+}
+
+/// Broadcast GPS week number of `utc`, wrapped to 10 bits like in ALM sentences.
+#[cfg(feature = "tui")]
+pub fn week(utc: SystemTime) -> Option<u32> {
+    // 1980-01-06, the start of week 0.
+    let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(315_964_800);
+    let since = utc.duration_since(epoch).ok()? + Duration::from_secs(LEAP_SECONDS);
+    Some((since.as_secs() / WEEK.as_secs()) as u32 % ROLLOVER_WEEKS)
+}
+
+/// Whole wraps of the week number that `utc` is behind `now`, if it is close to one.
+fn rollovers(utc: SystemTime, now: SystemTime) -> Option<u32> {
+    let behind = now.duration_since(utc).ok()?;
+    let period = WEEK * ROLLOVER_WEEKS;
+    let count = (behind.as_secs_f64() / period.as_secs_f64()).round() as u32;
+    let off = behind.abs_diff(period * count);
+    (count > 0 && off < ROLLOVER_TOLERANCE).then_some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    /// 2024-09-01 00:00 UTC.
+    const DATE: u64 = 1_725_148_800;
+
+    #[test]
+    fn rollover_is_a_whole_number_of_wraps() {
+        let wrap = (WEEK * ROLLOVER_WEEKS).as_secs();
+        assert_eq!(rollovers(at(DATE - wrap), at(DATE)), Some(1));
+        assert_eq!(rollovers(at(DATE - 2 * wrap + 3600), at(DATE)), Some(2));
+        assert_eq!(rollovers(at(DATE - wrap / 2), at(DATE)), None);
+        assert_eq!(rollovers(at(DATE - 3600), at(DATE)), None);
+        assert_eq!(rollovers(at(DATE + 3600), at(DATE)), None);
+    }
+
+    #[test]
+    fn dates_are_checked_against_the_last_good_one() {
+        let wrap = (WEEK * ROLLOVER_WEEKS).as_secs();
+        let mut check = TimeCheck::new(false);
+        check.apply(at(DATE));
+        assert_eq!(check.rollovers, None);
+        check.apply(at(DATE - wrap + 1));
+        assert_eq!(check.rollovers, Some(1));
+        // The bad date does not become the reference.
+        check.apply(at(DATE - wrap + 2));
+        assert_eq!(check.rollovers, Some(1));
+        check.apply(at(DATE + 1));
+        assert_eq!(check.rollovers, None);
+    }
+
+    #[test]
+    fn gps_time_is_corrected() {
+        let mut check = TimeCheck::new(true);
+        assert_eq!(check.apply(at(DATE + LEAP_SECONDS)), at(DATE));
+    }
+}
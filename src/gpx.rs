@@ -0,0 +1,82 @@
+//! GPX export of the track and waypoints, written with `--gpx` on exit. The track is
+//! simplified to `--gpx-tolerance`; the map keeps every fix.
+
+use std::{fmt::Write as _, path::Path};
+
+use anyhow::{Context as _, Result};
+
+use crate::{clock, convert::Fix, simplify, status::NmeaStatus};
+
+pub fn write(path: &Path, nmea: &NmeaStatus, tolerance: f64) -> Result<()> {
+    std::fs::write(path, to_gpx(nmea, tolerance))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+const HEADER: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+    "\n",
+    r#"<gpx version="1.1" creator="nmea-monitor" xmlns="http://www.topografix.com/GPX/1/1">"#,
+    "\n",
+);
+
+fn to_gpx(nmea: &NmeaStatus, tolerance: f64) -> String {
+    let mut gpx = String::from(HEADER);
+
+    for waypoint in nmea.waypoints.iter() {
+        let _ = writeln!(
+            gpx,
+            r#"  <wpt lat="{:.7}" lon="{:.7}">"#,
+            waypoint.lat, waypoint.lon
+        );
+        if let Some(alt) = waypoint.alt {
+            let _ = writeln!(gpx, "    <ele>{alt:.1}</ele>");
+        }
+        let _ = writeln!(
+            gpx,
+            "    <time>{}</time>",
+            clock::timestamp(waypoint.created_at)
+        );
+        let _ = writeln!(gpx, "    <name>{}</name>", escape(&waypoint.name));
+        gpx.push_str("  </wpt>\n");
+    }
+
+    gpx.push_str("  <trk>\n");
+    for segment in nmea.track.segments() {
+        gpx.push_str("    <trkseg>\n");
+        for (lat, lon) in simplify::simplify(&segment, tolerance) {
+            let _ = writeln!(gpx, r#"      <trkpt lat="{lat:.7}" lon="{lon:.7}"/>"#);
+        }
+        gpx.push_str("    </trkseg>\n");
+    }
+    gpx.push_str("  </trk>\n</gpx>\n");
+    gpx
+}
+
+/// A track of every fix with its altitude and time, for `convert`.
+pub fn fixes_to_gpx(fixes: &[Fix]) -> String {
+    let mut gpx = String::from(HEADER);
+    gpx.push_str("  <trk>\n    <trkseg>\n");
+    for fix in fixes {
+        let _ = writeln!(
+            gpx,
+            r#"      <trkpt lat="{:.7}" lon="{:.7}">"#,
+            fix.lat, fix.lon
+        );
+        if let Some(alt) = fix.alt {
+            let _ = writeln!(gpx, "        <ele>{alt:.1}</ele>");
+        }
+        if let Some(time) = fix.time {
+            let _ = writeln!(gpx, "        <time>{}</time>", clock::timestamp(time));
+        }
+        gpx.push_str("      </trkpt>\n");
+    }
+    gpx.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
+    gpx
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
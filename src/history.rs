@@ -0,0 +1,277 @@
+//! Time series of selected values, sampled once a second, for windowed statistics and
+//! charts.
+
+use std::{collections::VecDeque, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::geo;
+
+/// How long samples are kept.
+pub const RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// Span that statistics are computed over.
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Window {
+    #[default]
+    Minute,
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    FiveMinutes,
+    /// Since the program started.
+    Session,
+}
+
+impl Window {
+    #[cfg(feature = "tui")]
+    pub fn next(self) -> Window {
+        match self {
+            Self::Minute => Self::FiveMinutes,
+            Self::FiveMinutes => Self::Session,
+            Self::Session => Self::Minute,
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Minute => "last 1 min",
+            Self::FiveMinutes => "last 5 min",
+            Self::Session => "session",
+        }
+    }
+
+    fn duration(self) -> Option<Duration> {
+        match self {
+            Self::Minute => Some(Duration::from_secs(60)),
+            Self::FiveMinutes => Some(Duration::from_secs(5 * 60)),
+            Self::Session => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Summary {
+    pub min: f64,
+    pub max: f64,
+    sum: f64,
+    count: u64,
+}
+
+impl Summary {
+    fn new(value: f64) -> Summary {
+        Summary {
+            min: value,
+            max: value,
+            sum: value,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Series {
+    samples: VecDeque<(Instant, f64)>,
+    /// Totals since the start, kept beyond [`RETENTION`].
+    session: Option<Summary>,
+}
+
+impl Series {
+    pub fn push(&mut self, at: Instant, value: Option<f64>) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|(t, _)| at.duration_since(*t) > RETENTION)
+        {
+            self.samples.pop_front();
+        }
+        let Some(value) = value.filter(|value| value.is_finite()) else {
+            return;
+        };
+        self.samples.push_back((at, value));
+        match &mut self.session {
+            Some(session) => session.add(value),
+            None => self.session = Some(Summary::new(value)),
+        }
+    }
+
+    /// Samples within `span` of now, oldest first, with their age.
+    pub fn recent(&self, span: Duration) -> impl Iterator<Item = (Duration, f64)> + '_ {
+        let now = Instant::now();
+        self.samples
+            .iter()
+            .map(move |&(at, value)| (now.duration_since(at), value))
+            .filter(move |(age, _)| *age <= span)
+    }
+
+    /// Pairs of `(self, other)` values sampled at the same time, within `span` of now.
+    #[cfg(feature = "tui")]
+    pub fn join<'a>(
+        &'a self,
+        other: &'a Series,
+        span: Duration,
+    ) -> impl Iterator<Item = (f64, f64)> + 'a {
+        let now = Instant::now();
+        let mut other = other.samples.iter().peekable();
+        self.samples
+            .iter()
+            .filter(move |(at, _)| now.duration_since(*at) <= span)
+            .filter_map(move |&(at, value)| {
+                while other.next_if(|(t, _)| *t < at).is_some() {}
+                other.next_if(|(t, _)| *t == at).map(|&(_, o)| (value, o))
+            })
+    }
+
+    pub fn summary(&self, window: Window) -> Option<Summary> {
+        let Some(span) = window.duration() else {
+            return self.session;
+        };
+        self.recent(span)
+            .fold(None, |summary: Option<Summary>, (_, value)| match summary {
+                Some(mut summary) => {
+                    summary.add(value);
+                    Some(summary)
+                }
+                None => Some(Summary::new(value)),
+            })
+    }
+}
+
+/// Number of bins a [`Histogram`] grows to. Higher values are counted in the last bin.
+const MAX_BINS: usize = 100;
+
+/// Samples counted in bins of equal width starting at zero, for the whole session.
+#[derive(Debug)]
+pub struct Histogram {
+    pub width: f64,
+    pub bins: Vec<u64>,
+}
+
+impl Histogram {
+    fn new(width: f64) -> Histogram {
+        Histogram {
+            width,
+            bins: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        let index = ((value / self.width).floor().max(0.0) as usize).min(MAX_BINS - 1);
+        if self.bins.len() <= index {
+            self.bins.resize(index + 1, 0);
+        }
+        self.bins[index] += 1;
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn total(&self) -> u64 {
+        self.bins.iter().sum()
+    }
+}
+
+/// Values sampled by [`History::sample`].
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub sog: Option<f64>,
+    pub alt: Option<f64>,
+    pub hdop: Option<f64>,
+    pub satellites: Option<f64>,
+    pub depth: Option<f64>,
+    pub stw: Option<f64>,
+    pub wind_speed: Option<f64>,
+    /// Standard deviations of latitude, longitude and altitude error, from GST.
+    pub sigma_lat: Option<f64>,
+    pub sigma_lon: Option<f64>,
+    pub sigma_alt: Option<f64>,
+    /// `(lat, lon)` of a fix while moving, for the distance travelled.
+    pub position: Option<(f64, f64)>,
+    /// Lines received in total, for rates.
+    pub lines: u64,
+}
+
+#[derive(Debug)]
+pub struct History {
+    pub sog: Series,
+    pub alt: Series,
+    pub hdop: Series,
+    pub satellites: Series,
+    pub depth: Series,
+    pub stw: Series,
+    pub wind_speed: Series,
+    pub sigma_lat: Series,
+    pub sigma_lon: Series,
+    pub sigma_alt: Series,
+    /// Lines per second between consecutive samples.
+    pub rate: Series,
+    /// Distance travelled since the start in meters, sampled with each fix.
+    pub travelled: Series,
+    /// Seconds spent at each speed over ground.
+    pub sog_histogram: Histogram,
+    last: Option<(Instant, u64)>,
+    last_position: Option<(f64, f64)>,
+    distance: f64,
+}
+
+impl History {
+    /// `speed_bin` is the width of the speed histogram's bins in knots.
+    pub fn new(speed_bin: f64) -> History {
+        History {
+            sog: Series::default(),
+            alt: Series::default(),
+            hdop: Series::default(),
+            satellites: Series::default(),
+            depth: Series::default(),
+            stw: Series::default(),
+            wind_speed: Series::default(),
+            sigma_lat: Series::default(),
+            sigma_lon: Series::default(),
+            sigma_alt: Series::default(),
+            rate: Series::default(),
+            travelled: Series::default(),
+            sog_histogram: Histogram::new(speed_bin),
+            last: None,
+            last_position: None,
+            distance: 0.0,
+        }
+    }
+
+    pub fn sample(&mut self, sample: Sample) {
+        let now = Instant::now();
+        if let Some(sog) = sample.sog.filter(|sog| sog.is_finite()) {
+            self.sog_histogram.add(sog);
+        }
+        self.sog.push(now, sample.sog);
+        self.alt.push(now, sample.alt);
+        self.hdop.push(now, sample.hdop);
+        self.satellites.push(now, sample.satellites);
+        self.depth.push(now, sample.depth);
+        self.stw.push(now, sample.stw);
+        self.wind_speed.push(now, sample.wind_speed);
+        self.sigma_lat.push(now, sample.sigma_lat);
+        self.sigma_lon.push(now, sample.sigma_lon);
+        self.sigma_alt.push(now, sample.sigma_alt);
+        let rate = self.last.map(|(at, lines)| {
+            (sample.lines - lines) as f64 / now.duration_since(at).as_secs_f64()
+        });
+        self.rate.push(now, rate);
+        self.last = Some((now, sample.lines));
+        if let Some(position) = sample.position {
+            if let Some(last) = self.last_position {
+                self.distance += geo::distance(last, position);
+            }
+            self.last_position = Some(position);
+        }
+        self.travelled
+            .push(now, sample.position.map(|_| self.distance));
+    }
+}
@@ -0,0 +1,50 @@
+//! Translated labels from the `[labels]` table of the config file.
+//!
+//! Text is looked up by its English original, so anything without a translation is
+//! shown as is.
+
+#[cfg(feature = "tui")]
+use std::{borrow::Cow, fmt::Display};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+static LABELS: RwLock<Option<Arc<HashMap<String, String>>>> = RwLock::new(None);
+
+/// Sets the translations, replacing earlier ones when the config is reloaded.
+pub fn init(labels: HashMap<String, String>) {
+    *LABELS.write().expect("labels lock poisoned") = Some(Arc::new(labels));
+}
+
+/// The current translations. A reload replaces them while earlier lookups finish.
+#[cfg(feature = "tui")]
+fn labels() -> Option<Arc<HashMap<String, String>>> {
+    LABELS.read().expect("labels lock poisoned").clone()
+}
+
+/// The translation of `text`, or `text` itself without copying it, which is the common
+/// case as labels are drawn on every frame.
+#[cfg(feature = "tui")]
+pub fn tr(text: &str) -> Cow<'_, str> {
+    match labels().and_then(|labels| labels.get(text).cloned()) {
+        Some(translation) => Cow::Owned(translation),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Translates `template` and replaces its `{}` placeholders with `args` in order, so
+/// that translations can put them where the language needs them.
+#[cfg(feature = "tui")]
+pub fn format(template: &str, args: &[&dyn Display]) -> String {
+    let template = tr(template);
+    let mut parts = template.split("{}");
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
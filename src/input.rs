@@ -0,0 +1,160 @@
+//! Byte-level line handling for sources that mix NMEA text with binary protocols
+//! (UBX, RTCM) or line noise.
+
+use std::io::{self, BufRead};
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt as _};
+
+/// Lines longer than this are cut, so binary data without newlines cannot grow the buffer
+/// without bound.
+pub const MAX_LINE_LEN: u64 = 4096;
+
+/// Reads up to and including the next `\n` or `\r` into `buf`, but no more than
+/// [`MAX_LINE_LEN`] bytes. Returns the number of bytes read, 0 at the end of input.
+///
+/// Some devices, and Windows serial drivers set up for them, end lines with a bare `\r`;
+/// with `\r\n` the `\n` comes through as an empty line.
+pub async fn read_line<R>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<usize>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut read = 0;
+    loop {
+        let (used, done) = take(reader.fill_buf().await?, read, buf);
+        reader.consume(used);
+        read += used;
+        if done {
+            return Ok(read);
+        }
+    }
+}
+
+/// [`read_line`] for the offline commands, which read files without the runtime.
+pub fn read_line_blocking<R: BufRead>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<usize> {
+    let mut read = 0;
+    loop {
+        let (used, done) = take(reader.fill_buf()?, read, buf);
+        reader.consume(used);
+        read += used;
+        if done {
+            return Ok(read);
+        }
+    }
+}
+
+/// Whether a line read with [`read_line`] was cut at [`MAX_LINE_LEN`] rather than ended.
+pub fn is_cut(line: &[u8]) -> bool {
+    line.len() as u64 == MAX_LINE_LEN && !matches!(line.last(), Some(b'\n' | b'\r'))
+}
+
+/// Appends what belongs to the current line from `available` to `buf`, `read` bytes of
+/// the line having been read before. Returns the bytes used and whether the line is done.
+fn take(available: &[u8], read: usize, buf: &mut Vec<u8>) -> (usize, bool) {
+    if available.is_empty() {
+        return (0, true);
+    }
+    let max = MAX_LINE_LEN as usize;
+    let available = &available[..available.len().min(max - read)];
+    let (used, done) = match available.iter().position(|b| matches!(b, b'\n' | b'\r')) {
+        Some(end) => (end + 1, true),
+        None => (available.len(), read + available.len() == max),
+    };
+    buf.extend_from_slice(&available[..used]);
+    (used, done)
+}
+
+/// Extracts the text of a raw line, returning it with the number of bytes dropped.
+///
+/// The line is cut to its last run of printable ASCII. When that run was preceded by
+/// garbage, it is further cut to start at the first `$` or `!`, since binary data often
+/// happens to contain printable bytes just before a sentence.
+pub fn extract_text(line: &[u8]) -> (&str, usize) {
+    let trimmed = line
+        .iter()
+        .rposition(|b| !matches!(b, b'\r' | b'\n'))
+        .map_or(&line[..0], |end| &line[..=end]);
+
+    let start = trimmed
+        .iter()
+        .rposition(|b| !is_printable(*b))
+        .map_or(0, |i| i + 1);
+    let mut text = &trimmed[start..];
+    if start > 0 {
+        text = text
+            .iter()
+            .position(|b| matches!(b, b'$' | b'!'))
+            .map_or(&text[..0], |i| &text[i..]);
+    }
+
+    let dropped = trimmed.len() - text.len();
+    // Printable ASCII is always valid UTF-8.
+    (std::str::from_utf8(text).unwrap_or_default(), dropped)
+}
+
+fn is_printable(b: u8) -> bool {
+    (0x20..=0x7E).contains(&b) || b == b'\t'
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::BufReader;
+
+    use super::*;
+
+    async fn lines(input: &[u8]) -> Vec<Vec<u8>> {
+        // A small buffer so that lines span several reads.
+        let mut reader = BufReader::with_capacity(16, input);
+        let mut lines = Vec::new();
+        loop {
+            let mut buf = Vec::new();
+            if read_line(&mut reader, &mut buf).await.unwrap() == 0 {
+                return lines;
+            }
+            lines.push(buf);
+        }
+    }
+
+    #[tokio::test]
+    async fn crlf_leaves_an_empty_line() {
+        assert_eq!(
+            lines(b"$GPGGA,1\r\n$GPRMC,2\r\n").await,
+            [&b"$GPGGA,1\r"[..], b"\n", b"$GPRMC,2\r", b"\n"]
+        );
+    }
+
+    #[tokio::test]
+    async fn bare_cr_ends_a_line() {
+        assert_eq!(
+            lines(b"$GPGGA,1\r$GPRMC,2\rtail").await,
+            [&b"$GPGGA,1\r"[..], b"$GPRMC,2\r", b"tail"]
+        );
+    }
+
+    #[tokio::test]
+    async fn long_lines_are_cut() {
+        let max = MAX_LINE_LEN as usize;
+        let mut input = vec![b'x'; max + 10];
+        input.push(b'\n');
+        let lines = lines(&input).await;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), max);
+        assert_eq!(lines[1], [&[b'x'; 10][..], b"\n"].concat());
+        assert!(is_cut(&lines[0]));
+        assert!(!is_cut(&lines[1]));
+    }
+
+    #[tokio::test]
+    async fn blocking_reads_the_same_lines() {
+        let input = b"$GPGGA,1\r\n$GPRMC,2\rtail";
+        let mut reader = std::io::BufReader::with_capacity(16, &input[..]);
+        let mut blocking = Vec::new();
+        loop {
+            let mut buf = Vec::new();
+            if read_line_blocking(&mut reader, &mut buf).unwrap() == 0 {
+                break;
+            }
+            blocking.push(buf);
+        }
+        assert_eq!(blocking, lines(input).await);
+    }
+}
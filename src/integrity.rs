@@ -0,0 +1,80 @@
+//! RAIM fault detection from GBS sentences: the receiver's expected position error and
+//! the satellite it suspects of failing.
+
+use std::time::Duration;
+
+use crate::{sentence::Sentence, status::StatusValue};
+
+/// What GBS reports about a satellite that failed the integrity check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fault {
+    pub satellite: u32,
+    /// Probability that the fault was missed.
+    pub missed: Option<f64>,
+    /// Estimated range bias of the satellite in meters.
+    pub bias: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct Integrity {
+    /// Expected latitude, longitude and altitude error in meters.
+    pub expected: StatusValue<(f64, f64, f64)>,
+    /// The flagged satellite, or `None` while all pass.
+    pub fault: StatusValue<Option<Fault>>,
+    /// Horizontal expected error in meters above which integrity is lost.
+    limit: Option<f64>,
+}
+
+impl Integrity {
+    pub fn new(timeout: Duration, limit: Option<f64>) -> Integrity {
+        Integrity {
+            expected: StatusValue::new(timeout),
+            fault: StatusValue::new(timeout),
+            limit,
+        }
+    }
+
+    /// Time, expected error in latitude, longitude and altitude, failed satellite,
+    /// probability of missed detection, bias estimate, standard deviation of the bias.
+    pub fn update(&mut self, gbs: &Sentence) {
+        let fields = &gbs.fields;
+        let number = |index: usize| fields.get(index).and_then(|f| f.parse::<f64>().ok());
+        self.expected.update(
+            number(1)
+                .zip(number(2))
+                .zip(number(3))
+                .map(|((lat, lon), alt)| (lat, lon, alt)),
+        );
+        let fault = fields
+            .get(4)
+            .and_then(|f| f.parse().ok())
+            .map(|satellite| Fault {
+                satellite,
+                missed: number(5),
+                bias: number(6),
+            });
+        self.fault.update(Some(fault));
+    }
+
+    /// Expected horizontal error in meters.
+    pub fn horizontal(&self) -> Option<f64> {
+        self.expected.get().map(|&(lat, lon, _)| lat.hypot(lon))
+    }
+
+    /// Why the position cannot be trusted, if it cannot.
+    pub fn problem(&self) -> Option<String> {
+        if let Some(Some(fault)) = self.fault.get() {
+            return Some(format!(
+                "RAIM flagged satellite {}{}",
+                fault.satellite,
+                fault
+                    .bias
+                    .map_or_else(String::new, |bias| format!(", bias {bias:.1} m"))
+            ));
+        }
+        let (limit, horizontal) = self.limit.zip(self.horizontal())?;
+        (horizontal > limit).then(|| {
+            format!("RAIM expected error {horizontal:.1} m is over the {limit:.1} m limit")
+        })
+    }
+}
@@ -0,0 +1,241 @@
+//! Constant-velocity Kalman filter estimating position and velocity from fixes and
+//! COG/SOG.
+//!
+//! The state lives on a local east/north plane around the first fix. The two axes are
+//! independent under this model, so each is filtered separately as a position/velocity
+//! pair, which keeps the matrices at 2×2.
+
+use tokio::time::Instant;
+
+use crate::geo;
+
+/// Fixes further than this from the plane origin restart the filter around them.
+const MAX_DISTANCE: f64 = 20_000.0;
+
+/// Assumed velocity noise of SOG/COG in m/s.
+const VELOCITY_NOISE: f64 = 0.2;
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Copy, Debug)]
+pub struct Estimate {
+    pub lat: f64,
+    pub lon: f64,
+    /// Knots.
+    pub sog: f64,
+    /// Degrees true.
+    pub cog: f64,
+    /// One-sigma horizontal position uncertainty in meters.
+    pub sigma: f64,
+}
+
+#[derive(Debug)]
+pub struct Kalman {
+    /// Standard deviation of a position fix in meters.
+    position_noise: f64,
+    /// Spectral density of the random acceleration in m²/s³.
+    acceleration: f64,
+    state: Option<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    origin: (f64, f64),
+    east: Axis,
+    north: Axis,
+    updated_at: Instant,
+}
+
+/// Position and velocity along one axis with their covariance.
+#[derive(Clone, Copy, Debug)]
+struct Axis {
+    position: f64,
+    velocity: f64,
+    covariance: [[f64; 2]; 2],
+}
+
+impl Axis {
+    fn new(position: f64, position_variance: f64) -> Axis {
+        Axis {
+            position,
+            velocity: 0.0,
+            // Nothing is known about velocity until the fixes or COG/SOG tell us.
+            covariance: [[position_variance, 0.0], [0.0, 100.0]],
+        }
+    }
+
+    /// Advances the state by `dt` seconds with white-noise acceleration `q`.
+    fn predict(&mut self, dt: f64, q: f64) {
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        self.position += self.velocity * dt;
+        self.covariance = [
+            [
+                p00 + dt * (p10 + p01) + dt * dt * p11 + q * dt.powi(3) / 3.0,
+                p01 + dt * p11 + q * dt * dt / 2.0,
+            ],
+            [p10 + dt * p11 + q * dt * dt / 2.0, p11 + q * dt],
+        ];
+    }
+
+    /// Incorporates a measurement of state component `index` (0 position, 1 velocity)
+    /// with variance `r`.
+    fn correct(&mut self, index: usize, measurement: f64, r: f64) {
+        let p = self.covariance;
+        let innovation = measurement - [self.position, self.velocity][index];
+        let s = p[index][index] + r;
+        let gain = [p[0][index] / s, p[1][index] / s];
+        self.position += gain[0] * innovation;
+        self.velocity += gain[1] * innovation;
+        for (i, row) in self.covariance.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = p[i][j] - gain[i] * p[index][j];
+            }
+        }
+    }
+}
+
+impl Kalman {
+    pub fn new(position_noise: f64, acceleration: f64) -> Kalman {
+        Kalman {
+            position_noise,
+            acceleration,
+            state: None,
+        }
+    }
+
+    pub fn update_position(&mut self, lat: f64, lon: f64) {
+        let r = self.position_noise.powi(2);
+        if let Some(state) = self.predicted() {
+            let (x, y) = geo::local_xy(state.origin, (lat, lon));
+            if x.hypot(y) < MAX_DISTANCE {
+                state.east.correct(0, x, r);
+                state.north.correct(0, y, r);
+                return;
+            }
+        }
+        self.state = Some(State {
+            origin: (lat, lon),
+            east: Axis::new(0.0, r),
+            north: Axis::new(0.0, r),
+            updated_at: Instant::now(),
+        });
+    }
+
+    /// Incorporates speed (knots) and course (degrees) over ground.
+    pub fn update_velocity(&mut self, sog: f64, cog: f64) {
+        let Some(state) = self.predicted() else {
+            return;
+        };
+        let (vx, vy) = geo::displacement(cog, sog * geo::KNOT);
+        let r = VELOCITY_NOISE.powi(2);
+        state.east.correct(1, vx, r);
+        state.north.correct(1, vy, r);
+    }
+
+    /// The state predicted to now, if the filter has been started.
+    fn predicted(&mut self) -> Option<&mut State> {
+        let state = self.state.as_mut()?;
+        let now = Instant::now();
+        let dt = now.duration_since(state.updated_at).as_secs_f64();
+        state.east.predict(dt, self.acceleration);
+        state.north.predict(dt, self.acceleration);
+        state.updated_at = now;
+        Some(state)
+    }
+
+    /// Current estimate, extrapolated from the last update.
+    pub fn estimate(&self) -> Option<Estimate> {
+        let state = self.state.as_ref()?;
+        let dt = state.updated_at.elapsed().as_secs_f64();
+        let (mut east, mut north) = (state.east, state.north);
+        east.predict(dt, self.acceleration);
+        north.predict(dt, self.acceleration);
+
+        let (lat, lon) = geo::from_local_xy(state.origin, (east.position, north.position));
+        Some(Estimate {
+            lat,
+            lon,
+            sog: east.velocity.hypot(north.velocity) / geo::KNOT,
+            cog: east
+                .velocity
+                .atan2(north.velocity)
+                .to_degrees()
+                .rem_euclid(360.0),
+            sigma: (east.covariance[0][0] + north.covariance[0][0]).sqrt(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Pretends `seconds` passed since the last update.
+    fn elapse(kalman: &mut Kalman, seconds: f64) {
+        let state = kalman.state.as_mut().expect("filter not started");
+        state.updated_at -= Duration::from_secs_f64(seconds);
+    }
+
+    #[test]
+    fn correcting_shrinks_the_covariance() {
+        let mut axis = Axis::new(0.0, 25.0);
+        let mut corrected = Vec::new();
+        for i in 1..=10 {
+            axis.predict(1.0, 0.01);
+            let predicted = axis.covariance[0][0];
+            axis.correct(0, f64::from(i), 25.0);
+            assert!(axis.covariance[0][0] < predicted);
+            corrected.push(axis.covariance[0][0]);
+        }
+        assert!(corrected.windows(2).skip(1).all(|pair| pair[1] <= pair[0]));
+        assert!(axis.covariance[0][0] < 25.0);
+        assert!(axis.covariance[1][1] < 100.0);
+        assert!(
+            (axis.velocity - 1.0).abs() < 0.3,
+            "velocity {}",
+            axis.velocity
+        );
+    }
+
+    #[test]
+    fn converges_on_a_constant_velocity_track() {
+        let origin = (35.0, 139.0);
+        // Five knots due east.
+        let (sog, cog) = (5.0, 90.0);
+        let speed = sog * geo::KNOT;
+        let mut kalman = Kalman::new(5.0, 0.01);
+        for second in 0..60 {
+            if second > 0 {
+                elapse(&mut kalman, 1.0);
+            }
+            let (lat, lon) = geo::from_local_xy(origin, (speed * f64::from(second), 0.0));
+            kalman.update_position(lat, lon);
+            kalman.update_velocity(sog, cog);
+        }
+
+        let estimate = kalman.estimate().unwrap();
+        let (x, y) = geo::local_xy(origin, (estimate.lat, estimate.lon));
+        assert!((x - speed * 59.0).abs() < 5.0, "east {x}");
+        assert!(y.abs() < 5.0, "north {y}");
+        assert!((estimate.sog - sog).abs() < 0.2, "sog {}", estimate.sog);
+        assert!((estimate.cog - cog).abs() < 2.0, "cog {}", estimate.cog);
+        assert!(estimate.sigma < 5.0, "sigma {}", estimate.sigma);
+    }
+
+    #[test]
+    fn restarts_on_a_distant_fix() {
+        let mut kalman = Kalman::new(5.0, 0.01);
+        kalman.update_position(35.0, 139.0);
+        kalman.update_velocity(5.0, 90.0);
+        let far = geo::from_local_xy((35.0, 139.0), (0.0, MAX_DISTANCE * 1.5));
+        kalman.update_position(far.0, far.1);
+
+        let state = kalman.state.as_ref().unwrap();
+        assert_eq!(state.origin, far);
+        assert_eq!(state.east.velocity, 0.0);
+        let estimate = kalman.estimate().unwrap();
+        assert!((estimate.lat - far.0).abs() < 1e-9);
+        assert!((estimate.lon - far.1).abs() < 1e-9);
+    }
+}
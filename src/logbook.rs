@@ -0,0 +1,80 @@
+//! Free-text logbook entries appended to a file given with `--logbook`.
+//!
+//! Files ending in `.json` or `.jsonl` get one JSON object per line, anything else a
+//! Markdown table.
+
+use std::{fs::OpenOptions, io::Write as _, path::Path, time::SystemTime};
+
+use anyhow::{Context as _, Result};
+
+use crate::clock;
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub time: SystemTime,
+    pub position: Option<(f64, f64)>,
+    pub cog: Option<f64>,
+    pub sog: Option<f64>,
+    pub text: String,
+}
+
+pub fn append(path: &Path, entry: &Entry) -> Result<()> {
+    let json = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("json" | "jsonl")
+    );
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let new = file.metadata().map(|m| m.len() == 0).unwrap_or(false);
+
+    let time = clock::timestamp(entry.time);
+    let text = if json {
+        let number = |v: Option<f64>, decimals: usize| {
+            v.map_or_else(|| "null".to_string(), |v| format!("{v:.decimals$}"))
+        };
+        format!(
+            "{{\"time\":\"{time}\",\"lat\":{},\"lon\":{},\"cog\":{},\"sog\":{},\"text\":\"{}\"}}\n",
+            number(entry.position.map(|(lat, _)| lat), 7),
+            number(entry.position.map(|(_, lon)| lon), 7),
+            number(entry.cog, 1),
+            number(entry.sog, 1),
+            json_escape(&entry.text),
+        )
+    } else {
+        let header = if new {
+            "| time | position | cog | sog | entry |\n|---|---|---|---|---|\n"
+        } else {
+            ""
+        };
+        let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        format!(
+            "{header}| {time} | {} | {} | {} | {} |\n",
+            or_dash(
+                entry
+                    .position
+                    .map(|(lat, lon)| format!("{lat:.5}, {lon:.5}"))
+            ),
+            or_dash(entry.cog.map(|cog| format!("{cog:.0}°"))),
+            or_dash(entry.sog.map(|sog| format!("{sog:.1} kn"))),
+            entry.text.replace('|', "\\|"),
+        )
+    };
+    file.write_all(text.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
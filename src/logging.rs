@@ -0,0 +1,23 @@
+//! Internal events for post-mortem debugging, such as sources failing and sinks giving
+//! up, written to `--log-file` so that they stay out of the terminal UI. Without it the
+//! events are discarded.
+
+use std::{fs::OpenOptions, path::Path, sync::Mutex};
+
+use anyhow::{Context as _, Result};
+use tracing::level_filters::LevelFilter;
+
+/// Appends events of `level` and above to `path`.
+pub fn init(path: &Path, level: LevelFilter) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_max_level(level)
+        .init();
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Maidenhead grid locators used by radio amateurs, e.g. `PM95vq`.
+
+/// Encodes a position as a locator of `pairs` character pairs (2 to 4): field, square,
+/// subsquare and extended square.
+pub fn locator(lat: f64, lon: f64, pairs: usize) -> String {
+    // Offsets from the south-west corner in units of the smallest subdivision in use.
+    let mut lon = (lon + 180.0).clamp(0.0, 359.999_999) / 20.0;
+    let mut lat = (lat + 90.0).clamp(0.0, 179.999_999) / 10.0;
+    let mut locator = String::with_capacity(pairs * 2);
+    for pair in 0..pairs.clamp(2, 4) {
+        let (base, divisions) = match pair {
+            0 => (b'A', 18.0),
+            1 | 3 => (b'0', 10.0),
+            _ => (b'a', 24.0),
+        };
+        let (lon_index, lat_index) = if pair == 0 {
+            (lon.floor(), lat.floor())
+        } else {
+            lon = lon.fract() * divisions;
+            lat = lat.fract() * divisions;
+            (lon.floor(), lat.floor())
+        };
+        locator.push(char::from(base + lon_index as u8));
+        locator.push(char::from(base + lat_index as u8));
+    }
+    locator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_known_locators() {
+        // Munich.
+        assert_eq!(locator(48.1467, 11.6083, 3), "JN58td");
+        // Sydney.
+        assert_eq!(locator(-33.8675, 151.2070, 4), "QF56od41");
+        // Washington, D.C.
+        assert_eq!(locator(38.8951, -77.0364, 2), "FM18");
+    }
+
+    #[test]
+    fn clamps_pairs_and_poles() {
+        assert_eq!(locator(48.1467, 11.6083, 1), "JN58");
+        assert_eq!(locator(48.1467, 11.6083, 9), locator(48.1467, 11.6083, 4));
+        assert_eq!(locator(90.0, 180.0, 3), "RR99xx");
+        assert_eq!(locator(-90.0, -180.0, 3), "AA00aa");
+    }
+}
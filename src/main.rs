@@ -1,32 +1,589 @@
-use std::{fmt::Display, sync::Arc, time::Duration};
+mod accuracy;
+#[cfg(feature = "ais")]
+mod ais;
+mod alarm;
+mod almanac;
+#[cfg(feature = "net")]
+mod aprs;
+mod autopilot;
+mod barometer;
+mod battery;
+mod bench;
+#[cfg(feature = "tui")]
+mod browser;
+mod capture;
+mod check;
+#[cfg(feature = "tui")]
+mod clipboard;
+mod clock;
+mod config;
+#[cfg(unix)]
+mod control;
+#[cfg(feature = "export")]
+mod convert;
+mod cut;
+#[cfg(unix)]
+mod daemon;
+mod datum;
+mod depth;
+mod device;
+mod diagnostics;
+mod dsc;
+mod engine;
+mod extended;
+#[cfg(feature = "extract")]
+mod extract;
+mod filter;
+mod format;
+mod fuzz;
+mod geo;
+#[cfg(feature = "export")]
+mod geojson;
+mod gns;
+mod gps_time;
+#[cfg(feature = "export")]
+mod gpx;
+mod history;
+mod i18n;
+mod input;
+mod integrity;
+mod kalman;
+#[cfg(feature = "tui")]
+mod logbook;
+mod logging;
+mod maidenhead;
+mod mob;
+mod motion;
+mod n2k;
+mod outlier;
+mod overlay;
+mod plugin;
+mod propulsion;
+mod queue;
+mod raw_log;
+mod record;
+#[cfg(feature = "tui")]
+mod reference;
+mod reload;
+mod replay;
+mod satellite;
+mod sbs;
+#[cfg(feature = "script")]
+mod script;
+mod seatalk;
+mod sensors;
+mod sentence;
+mod shutdown;
+#[cfg(feature = "export")]
+mod simplify;
+mod source;
+mod stats;
+mod status;
+#[cfg(feature = "tui")]
+mod sun;
+#[cfg(unix)]
+mod systemd;
+mod threshold;
+mod tide;
+mod track;
+mod traffic;
+mod trip;
+#[cfg(feature = "tui")]
+mod ui;
+mod validate;
+mod waypoint;
+mod wind;
+
+use std::{fmt::Display, path::PathBuf, sync::Arc};
 
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
-use crossterm::event::{Event, EventStream, KeyCode, KeyEvent};
-use futures::StreamExt as _;
-use nmea::{sentences::FixType, ParseResult};
-use ratatui::{
-    layout::{Constraint, Flex, Layout, Rect},
-    prelude::Backend,
-    text::Text,
-    widgets::{Block, Paragraph},
-    Frame, Terminal,
-};
-use tokio::{
-    fs::File,
-    io::{AsyncBufReadExt, AsyncRead, BufReader},
-    sync::RwLock,
-    time::Instant,
+use clap::{Parser, Subcommand, ValueEnum};
+use tracing::{info, level_filters::LevelFilter, warn};
+
+#[cfg(feature = "net")]
+use crate::aprs::AprsConfig;
+#[cfg(feature = "export")]
+use crate::convert::ExportFormat;
+use crate::{
+    config::Config,
+    engine::Engine,
+    filter::Smoothing,
+    format::{CoordinateFormat, VerticalSpeedUnit},
+    queue::DropPolicy,
+    record::Rotation,
+    source::Source,
 };
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Clone, Debug)]
+// A profile's options come first, so that those given after them win.
+#[command(args_override_self = true)]
 struct Args {
-    source: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Files or serial ports (`/dev/ttyUSB0`, `COM3`), `-` for stdin, `tcp://host:port`,
+    /// `udp://bind-address:port`, `unix:///path` or `sbs://host:port` for an ADS-B
+    /// receiver's BaseStation feed. Lines from all sources are merged. Position comes from
+    /// the first source that is still reporting one; the others are backups in the order
+    /// given.
+    sources: Vec<Source>,
     #[clap(short, long, default_value_t = Default::default())]
     r#type: SourceType,
 
+    /// TOML configuration file. Edits are applied while running: the file is reloaded
+    /// when it changes, with `R`, and on SIGHUP in daemon mode.
+    #[clap(short, long)]
+    config: Option<PathBuf>,
+
+    /// Profile from the `[profiles]` of the config file, such as `boat` or `rtk-base`,
+    /// with its display settings, panels, options and sources.
+    #[clap(long, requires = "config")]
+    profile: Option<String>,
+
     #[clap(long, default_value = "1s")]
     timeout: humantime::Duration,
+
+    /// Decode this log without the TUI and print the final state as JSON, or compare it
+    /// with `--expect`. Exits with 1 when it differs.
+    #[clap(long)]
+    check: Option<PathBuf>,
+
+    /// Expected state for `--check`. Only the fields present are compared.
+    #[clap(long, requires = "check")]
+    expect: Option<PathBuf>,
+
+    /// Decode this log as fast as possible and report the throughput and sentence counts.
+    #[clap(long)]
+    bench: Option<PathBuf>,
+
+    /// Read a corrupted stream (`-` for stdin) and report how many lines each stage
+    /// rejected, and any lines that made decoding panic.
+    #[clap(long)]
+    fuzz_stats: Option<PathBuf>,
+
+    /// Record the raw input from all sources to this file.
+    #[clap(long)]
+    record: Option<PathBuf>,
+
+    /// Start a new timestamped recording file after a duration (`1h`) or size (`100MB`).
+    #[clap(long)]
+    record_rotate: Option<Rotation>,
+
+    /// Compress completed recording files with gzip. Needs `--record-rotate`.
+    #[clap(long, requires = "record_rotate")]
+    record_gzip: bool,
+
+    /// Prefix recorded lines with their receive time and source index, so that a
+    /// recording of several sources replays with its timing and attribution.
+    #[clap(long)]
+    record_extended: bool,
+
+    /// Append internal events, such as sources failing, parse errors and sinks giving up,
+    /// to this file for debugging.
+    #[clap(long)]
+    log_file: Option<PathBuf>,
+
+    /// Least severe events written to `--log-file`: `error`, `warn`, `info`, `debug` or
+    /// `trace`. Parse errors are `debug`.
+    #[clap(long, default_value = "info")]
+    log_level: LevelFilter,
+
+    /// Keep the last `--capture-window` of raw input in memory and write it to a
+    /// timestamped file next to this path when an alert appears, the fix is lost or `t`
+    /// is pressed.
+    #[clap(long)]
+    capture: Option<PathBuf>,
+
+    /// How much raw input `--capture` keeps.
+    #[clap(long, default_value = "5m")]
+    capture_window: humantime::Duration,
+
+    /// Rhai script defining `on_sentence(line, sentence)`, which can show values and raise
+    /// alerts. Can be repeated. See the `script` module.
+    #[cfg(feature = "script")]
+    #[clap(long)]
+    script: Vec<PathBuf>,
+
+    /// Enable a compiled-in plugin, `name` or `name=argument`. Can be repeated; an
+    /// unknown name lists the available ones.
+    #[clap(long)]
+    plugin: Vec<String>,
+
+    /// Accept JSON commands on this Unix socket. See the `control` module.
+    #[clap(long)]
+    control: Option<PathBuf>,
+
+    /// Run without the TUI, serving the input to `attach` clients on `--socket`. Under
+    /// systemd, readiness, status and watchdog are reported with `Type=notify`.
+    #[clap(long)]
+    daemon: bool,
+
+    /// Unix socket of the daemon. Defaults to `nmea-monitor.sock` in
+    /// `$XDG_RUNTIME_DIR` or the temporary directory.
+    #[clap(long, global = true)]
+    socket: Option<PathBuf>,
+
+    /// Switch to a backup source when the active one has not reported a position for
+    /// this long.
+    #[clap(long, default_value = "3s")]
+    failover: humantime::Duration,
+
+    /// Identical sentences arriving from different sources within this window are
+    /// counted once.
+    #[clap(long, default_value = "200ms")]
+    dedup_window: humantime::Duration,
+
+    /// Lines waiting to be decoded before `--drop-policy` applies. Recording comes before
+    /// the queue and never drops lines.
+    #[clap(long, default_value_t = 4096, value_parser = clap::value_parser!(u64).range(1..))]
+    queue_capacity: u64,
+
+    /// What to do with input that decoding cannot keep up with, such as a flood of UDP
+    /// from several receivers: wait for it, or drop the oldest or newest lines.
+    #[clap(long, default_value_t = Default::default(), value_enum)]
+    drop_policy: DropPolicy,
+
+    /// Open the first file or TCP source read-write so sentences can be sent to the
+    /// device (e.g. a serial port). Do not use with log files.
+    #[clap(long)]
+    writable: bool,
+
+    /// How coordinates are displayed. Overrides the config file.
+    #[clap(long)]
+    coordinate_format: Option<CoordinateFormat>,
+
+    /// Decimal places of the degrees, minutes or seconds of displayed coordinates.
+    /// Overrides the config file.
+    #[clap(long)]
+    precision: Option<u8>,
+
+    /// RMC and ZDA times are GPS time rather than UTC, as some receivers send; the leap
+    /// seconds are subtracted.
+    #[clap(long)]
+    gps_time: bool,
+
+    /// Zone times are shown and written in: `UTC`, `local` for the system's zone, or an
+    /// IANA name such as `Asia/Tokyo`. Overrides the config file.
+    #[clap(long)]
+    timezone: Option<String>,
+
+    /// Also show UTC where times are shown in another zone.
+    #[clap(long)]
+    show_utc: bool,
+
+    /// Web map opened with `o`. `{lat}` and `{lon}` are replaced with decimal degrees.
+    /// Overrides the config file.
+    #[clap(long)]
+    map_url: Option<String>,
+
+    /// Always show the latest line of this sentence address (`GNGGA`) on the raw log
+    /// screen. Can be repeated; adds to the config file.
+    #[clap(long)]
+    pin: Vec<String>,
+
+    /// Width of the bins of the speed histogram on the statistics screen, in knots.
+    #[clap(long, default_value_t = 1.0, value_parser = parse_positive)]
+    speed_bin: f64,
+
+    /// How long a status value is highlighted after it changes; `0s` turns it off.
+    #[clap(long, default_value = "300ms")]
+    flash: humantime::Duration,
+
+    /// Time shown on the charts screen, up to an hour.
+    #[clap(long, default_value = "10m", value_parser = parse_chart_span)]
+    chart_span: humantime::Duration,
+
+    /// Append logbook entries (`l`) to this file: JSON lines if it ends in `.json` or
+    /// `.jsonl`, a Markdown table otherwise.
+    #[clap(long)]
+    logbook: Option<PathBuf>,
+
+    /// Write the track and waypoints to this GPX file on exit.
+    #[cfg(feature = "export")]
+    #[clap(long)]
+    gpx: Option<PathBuf>,
+
+    /// Write the track and current position to this GeoJSON file on exit.
+    #[cfg(feature = "export")]
+    #[clap(long)]
+    geojson: Option<PathBuf>,
+
+    /// Also rewrite `--geojson` this often while running, for web maps to poll.
+    #[cfg(feature = "export")]
+    #[clap(long, requires = "geojson")]
+    geojson_interval: Option<humantime::Duration>,
+
+    /// Start a new track segment, and a new recording file, when the date changes.
+    #[clap(long)]
+    split_daily: bool,
+
+    /// Start a new track segment, and a new recording file, when no fix came for this
+    /// long.
+    #[clap(long)]
+    split_gap: Option<humantime::Duration>,
+
+    /// Leave out track points of the GPX file that are within this many meters of the
+    /// simplified line. 0 keeps every point.
+    #[cfg(feature = "export")]
+    #[clap(long, default_value_t = 1.0)]
+    gpx_tolerance: f64,
+
+    /// Smoothing applied to the displayed speed and course over ground. Press `r` to
+    /// show raw values.
+    #[clap(long, default_value_t = Default::default(), value_enum)]
+    smoothing: Smoothing,
+
+    /// Unit of the climb rate shown next to altitude.
+    #[clap(long, default_value_t = Default::default(), value_enum)]
+    vertical_speed_unit: VerticalSpeedUnit,
+
+    /// Number of samples the smoothing averages over. Also used for climb rate.
+    #[clap(long, default_value_t = 5)]
+    smoothing_samples: usize,
+
+    /// Below this speed in knots the receiver is at rest after `--stationary-after`, and
+    /// the track and distance travelled pause until it moves.
+    #[clap(long, default_value_t = 0.5)]
+    stationary_speed: f64,
+
+    /// How long speed must stay below `--stationary-speed` to pause the track.
+    #[clap(long, default_value = "10s")]
+    stationary_after: humantime::Duration,
+
+    /// A fix further than this many meters from the previous one, per second of receiver
+    /// time between them, is dropped as an outlier. 0 accepts every fix.
+    #[clap(long, default_value_t = 500.0)]
+    outlier_distance: f64,
+
+    /// Show a position and velocity estimate from a constant-velocity Kalman filter
+    /// alongside the received values.
+    #[clap(long)]
+    kalman: bool,
+
+    /// Standard deviation of received fixes for the Kalman filter, in meters.
+    #[clap(long, default_value_t = 5.0)]
+    kalman_position_noise: f64,
+
+    /// Random acceleration the Kalman filter allows for, in m²/s³. Higher values follow
+    /// manoeuvres faster but smooth less.
+    #[clap(long, default_value_t = 0.5)]
+    kalman_acceleration: f64,
+
+    /// Keep showing a position dead-reckoned from the last speed and course for up to
+    /// this long after the fix is lost. It is marked as estimated.
+    #[clap(long)]
+    dead_reckoning: Option<humantime::Duration>,
+
+    /// Length of the displayed Maidenhead locator: 4, 6 or 8 characters.
+    #[clap(long, default_value_t = 6, value_parser = parse_locator_length)]
+    locator_length: u8,
+
+    /// How long a satellite stays listed after it was last reported in a GSV group.
+    #[clap(long, default_value = "10s")]
+    satellite_expiry: humantime::Duration,
+
+    /// AIS targets passing closer than this many nautical miles raise an alert.
+    #[cfg(feature = "ais")]
+    #[clap(long, default_value_t = 0.5)]
+    cpa_alarm: f64,
+
+    /// Only alert on closest approaches happening within this time.
+    #[cfg(feature = "ais")]
+    #[clap(long, default_value = "20m")]
+    tcpa_alarm: humantime::Duration,
+
+    /// How long an AIS target stays listed after its last message.
+    #[cfg(feature = "ais")]
+    #[clap(long, default_value = "10m")]
+    ais_expiry: humantime::Duration,
+
+    /// How long a DSC call stays listed, and alerts, after it was received.
+    #[clap(long, default_value = "30m")]
+    dsc_expiry: humantime::Duration,
+
+    /// Alert when positions from two talkers or sources for the same fix, by its UTC time,
+    /// are further apart than this many meters.
+    #[clap(long, default_value_t = 50.0)]
+    divergence_limit: f64,
+
+    /// Alert when the expected horizontal error reported in GBS exceeds this many
+    /// meters. A satellite flagged by RAIM always raises an alert.
+    #[clap(long)]
+    integrity_limit: Option<f64>,
+
+    /// Alert when the barometric pressure falls faster than this many hPa in three
+    /// hours.
+    #[clap(long)]
+    pressure_drop_alarm: Option<f64>,
+
+    /// Alert when the depth below the keel is less than this many meters. The keel
+    /// offset is set in the config file or taken from DPT.
+    #[clap(long)]
+    shallow_alarm: Option<f64>,
+
+    /// How long an aircraft stays listed on the traffic screen after its last report.
+    #[clap(long, default_value = "20s")]
+    traffic_expiry: humantime::Duration,
+
+    /// Beacon the position to APRS-IS under this callsign, with SSID.
+    #[cfg(feature = "net")]
+    #[clap(long, requires = "aprs_pass")]
+    aprs_call: Option<String>,
+
+    /// APRS-IS passcode for `--aprs-call`.
+    #[cfg(feature = "net")]
+    #[clap(long)]
+    aprs_pass: Option<String>,
+
+    /// APRS-IS server as `host:port`.
+    #[cfg(feature = "net")]
+    #[clap(long, default_value = "rotate.aprs2.net:14580")]
+    aprs_server: String,
+
+    /// APRS symbol table and code of the beacons.
+    #[cfg(feature = "net")]
+    #[clap(long, default_value = "/>", value_parser = parse_aprs_symbol)]
+    aprs_symbol: String,
+
+    /// Play the first file source at the pace of its RMC and ZDA times, sped up by this
+    /// factor, instead of reading it at once. Playback can be bookmarked with `k` and
+    /// jumped back to from the sources screen.
+    #[clap(long, value_parser = parse_positive)]
+    replay: Option<f64>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum Command {
+    /// Open the TUI on a running daemon. Sentences typed on the device screen are sent
+    /// through the daemon.
+    Attach,
+    /// Copy the lines of a log between two receiver times taken from RMC and ZDA, such
+    /// as `cut input.nmea --from 12:00 --to 12:30 -o slice.nmea`.
+    Cut {
+        log: PathBuf,
+        /// First time of day to copy, `hh:mm` or `hh:mm:ss` UTC. From the start of the
+        /// log when left out.
+        #[clap(long, value_parser = parse_time_of_day)]
+        from: Option<f64>,
+        /// Time of day to stop at, exclusive. To the end of the log when left out.
+        #[clap(long, value_parser = parse_time_of_day)]
+        to: Option<f64>,
+        /// Write here instead of stdout.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Convert the fixes of a log to a GPX track, CSV or a GeoJSON line.
+    #[cfg(feature = "export")]
+    Convert {
+        log: PathBuf,
+        #[clap(short, long, value_enum)]
+        format: ExportFormat,
+        /// Write here instead of stdout.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check the framing, checksum and parseability of every line of a log, printing
+    /// the bad ones with their line numbers. Exits with 1 when there are more errors
+    /// than allowed.
+    Validate {
+        log: PathBuf,
+        /// Errors tolerated before failing.
+        #[clap(long, default_value_t = 0)]
+        max_errors: u64,
+    },
+    /// Print a summary of a log: duration, distance, speed, bounding box, time in each
+    /// fix type and sentence counts.
+    Stats { log: PathBuf },
+}
+
+impl Args {
+    fn sources(&self) -> Vec<Source> {
+        if let Some(Command::Attach) = self.command {
+            #[cfg(unix)]
+            return vec![Source::Unix(self.socket())];
+        }
+        match self.r#type {
+            SourceType::File if !self.sources.is_empty() => self.sources.clone(),
+            _ => vec![Source::Stdin],
+        }
+    }
+
+    /// Whether to open a source for writing to the device.
+    fn writable(&self) -> bool {
+        self.writable || matches!(self.command, Some(Command::Attach))
+    }
+
+    #[cfg(feature = "net")]
+    fn aprs(&self) -> Option<AprsConfig> {
+        Some(AprsConfig {
+            call: self.aprs_call.clone()?,
+            pass: self.aprs_pass.clone()?,
+            server: self.aprs_server.clone(),
+            symbol: self.aprs_symbol.clone(),
+        })
+    }
+
+    #[cfg(unix)]
+    fn socket(&self) -> PathBuf {
+        self.socket.clone().unwrap_or_else(daemon::default_socket)
+    }
+}
+
+fn parse_positive(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(value),
+        Ok(_) => Err("must be positive".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_locator_length(s: &str) -> Result<u8, String> {
+    match s.parse::<u8>() {
+        Ok(length @ (4 | 6 | 8)) => Ok(length),
+        Ok(_) => Err("must be 4, 6 or 8".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// `hh:mm` or `hh:mm:ss` in seconds of the day.
+fn parse_time_of_day(s: &str) -> Result<f64, String> {
+    let parts = s
+        .split(':')
+        .map(|part| part.parse::<f64>().map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes] => (hours, minutes, 0.0),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return Err("expected hh:mm or hh:mm:ss".to_string()),
+    };
+    if !(0.0..24.0).contains(&hours)
+        || !(0.0..60.0).contains(&minutes)
+        || !(0.0..60.0).contains(&seconds)
+    {
+        return Err("out of range".to_string());
+    }
+    Ok(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+#[cfg(feature = "net")]
+fn parse_aprs_symbol(s: &str) -> Result<String, String> {
+    if s.chars().count() != 2 {
+        return Err("must be a table and a code, like /> for a car".to_string());
+    }
+    Ok(s.to_string())
+}
+
+fn parse_chart_span(s: &str) -> Result<humantime::Duration, String> {
+    let span = s
+        .parse::<humantime::Duration>()
+        .map_err(|e| e.to_string())?;
+    if *span > history::RETENTION {
+        return Err(format!(
+            "history is kept for {}",
+            humantime::format_duration(history::RETENTION)
+        ));
+    }
+    Ok(span)
 }
 
 #[derive(ValueEnum, Default, PartialEq, Eq, Clone, Copy, Debug)]
@@ -47,180 +604,185 @@ impl Display for SourceType {
 
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
-
-    let nmea = Arc::new(RwLock::new(NmeaStatus::new(args.timeout.into())));
+    let mut args = Args::parse();
 
-    {
-        let nmea = Arc::clone(&nmea);
+    let mut config = args
+        .config
+        .as_deref()
+        .map(Config::load)
+        .transpose()
+        .expect("Failed to load config.")
+        .unwrap_or_default();
+    if let Some(name) = args.profile.clone() {
+        let profile = config
+            .select_profile(&name)
+            .expect("Failed to select profile.");
+        let mut command_line = std::env::args_os();
+        args = Args::parse_from(
+            command_line
+                .next()
+                .into_iter()
+                .chain(profile.options.into_iter().map(Into::into))
+                .chain(command_line),
+        );
+        if args.sources.is_empty() {
+            args.sources = profile
+                .sources
+                .iter()
+                .map(|source| {
+                    let Ok(source) = source.parse();
+                    source
+                })
+                .collect();
+        }
+    }
+    init_globals(&args, &config).expect("Invalid time zone.");
+    if let Some(path) = &args.log_file {
+        logging::init(path, args.log_level).expect("Failed to open log file.");
+    }
 
-        let source: Box<dyn AsyncRead + Unpin + Send> = match (args.source, args.r#type) {
-            (Some(source), SourceType::File) => {
-                Box::new(File::open(source).await.expect("Failed to open file."))
-            }
-            _ => Box::new(tokio::io::stdin()),
-        };
+    if let Some(log) = &args.check {
+        let passed =
+            check::run(&args, &config, log, args.expect.as_deref()).expect("Failed to check log.");
+        std::process::exit(if passed { 0 } else { 1 });
+    }
 
-        let mut stdin = BufReader::with_capacity(128, source).lines();
+    if let Some(Command::Cut {
+        log,
+        from,
+        to,
+        output,
+    }) = &args.command
+    {
+        let copied = cut::run(log, *from, *to, output.as_deref()).expect("Failed to cut log.");
+        eprintln!("{copied} lines");
+        return;
+    }
 
-        tokio::spawn(async move {
-            loop {
-                let line = stdin.next_line().await.unwrap();
-                if let Some(line) = line {
-                    if let Ok(parsed) = nmea::parse_str(line.trim_end()) {
-                        match parsed {
-                            ParseResult::GGA(gga) => {
-                                let mut nmea = nmea.write().await;
-                                nmea.lat.update(gga.latitude);
-                                nmea.lon.update(gga.longitude);
-                                nmea.alt.update(gga.altitude.map(From::from));
-                                nmea.fix_type.update(gga.fix_type.map(|t| match t {
-                                    FixType::Invalid => "Invalid",
-                                    FixType::Gps => "Gps",
-                                    FixType::DGps => "DGps",
-                                    FixType::Pps => "Pps",
-                                    FixType::Rtk => "Rtk",
-                                    FixType::FloatRtk => "FloatRtk",
-                                    FixType::Estimated => "Estimated",
-                                    FixType::Manual => "Manual",
-                                    FixType::Simulation => "Simulation",
-                                }));
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-        });
+    #[cfg(feature = "export")]
+    if let Some(Command::Convert {
+        log,
+        format,
+        output,
+    }) = &args.command
+    {
+        let fixes = convert::run(&args, &config, log, *format, output.as_deref())
+            .expect("Failed to convert log.");
+        eprintln!("{fixes} fixes");
+        return;
     }
 
-    let terminal = ratatui::init();
+    if let Some(Command::Validate { log, max_errors }) = &args.command {
+        let passed = validate::run(log, *max_errors).expect("Failed to validate log.");
+        std::process::exit(if passed { 0 } else { 1 });
+    }
 
-    let result = run(terminal, nmea).await;
+    if let Some(Command::Stats { log }) = &args.command {
+        stats::run(&args, &config, log).expect("Failed to read log.");
+        return;
+    }
 
-    ratatui::restore();
+    if let Some(log) = &args.bench {
+        bench::run(&args, &config, log).expect("Failed to run benchmark.");
+        return;
+    }
 
-    result.expect("Failed to run app.");
-}
+    if let Some(path) = &args.fuzz_stats {
+        fuzz::run(&args, &config, path).expect("Failed to read input.");
+        return;
+    }
 
-#[derive(Default, Debug)]
-struct NmeaStatus {
-    lat: StatusValue<f64>,
-    lon: StatusValue<f64>,
-    alt: StatusValue<f64>,
-    hdg: StatusValue<f64>,
-    sog: StatusValue<f64>,
-    cog: StatusValue<f64>,
-    fix_type: StatusValue<&'static str>,
-}
+    // A terminal closing sends SIGHUP too, which should still end the UI.
+    shutdown::install(args.config.is_some() && (args.daemon || cfg!(not(feature = "tui"))));
+    info!(
+        version = env!("CARGO_PKG_VERSION"),
+        daemon = args.daemon,
+        "starting"
+    );
+    let engine = Engine::start(&args, &config).await;
 
-impl NmeaStatus {
-    pub fn new(timeout: Duration) -> NmeaStatus {
-        NmeaStatus {
-            lat: StatusValue::new(timeout),
-            lon: StatusValue::new(timeout),
-            alt: StatusValue::new(timeout),
-            hdg: StatusValue::new(timeout),
-            sog: StatusValue::new(timeout),
-            cog: StatusValue::new(timeout),
-            fix_type: StatusValue::new(timeout),
-        }
+    // Bound here so that a bad path is reported before the UI takes the terminal.
+    #[cfg(unix)]
+    if let Some(path) = &args.control {
+        let listener = daemon::bind(path)
+            .await
+            .expect("Failed to bind control socket.");
+        let nmea = Arc::clone(&engine.nmea);
+        let recording = Arc::clone(&engine.recording);
+        tokio::spawn(async move {
+            if let Err(e) = control::serve(listener, nmea, recording).await {
+                warn!("control socket failed: {e:#}");
+            }
+        });
     }
-}
-
-async fn run(mut terminal: Terminal<impl Backend>, nmea: Arc<RwLock<NmeaStatus>>) -> Result<()> {
-    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / 60.0));
-    let mut events = EventStream::new();
 
-    while tokio::select! {
-        _ = interval.tick() => {
-            let nmea = nmea.read().await;
-            terminal.draw(|frame| draw(frame, &nmea)).expect("Failed to draw terminal.");
-            true
+    let reason = if args.daemon {
+        #[cfg(unix)]
+        {
+            let socket = args.socket();
+            let listener = daemon::bind(&socket).await.expect("Failed to bind socket.");
+            // Clients can connect from here on, so systemd may start the units after us.
+            tokio::spawn(systemd::run(Arc::clone(&engine.nmea)));
+            let reason = tokio::select! {
+                result = daemon::serve(listener, &engine) => {
+                    result.expect("Failed to serve socket.");
+                    None
+                }
+                reason = shutdown::stopped() => reason,
+            };
+            systemd::notify("STOPPING=1");
+            let _ = std::fs::remove_file(&socket);
+            reason
         }
-        Some(Ok(event)) = events.next() => {
-            !matches!(event, Event::Key(KeyEvent { code: KeyCode::Esc, ..}))
+        #[cfg(not(unix))]
+        panic!("Daemon mode needs Unix sockets.");
+    } else {
+        #[cfg(feature = "tui")]
+        {
+            ui::run(&args, config, &engine).await
         }
-    } {}
+        // Without the UI, run headless like the daemon without its socket.
+        #[cfg(not(feature = "tui"))]
+        shutdown::stopped().await
+    };
 
-    Ok(())
-}
-
-fn draw(frame: &mut Frame, nmea: &NmeaStatus) {
-    let [lat, lon, alt, hdg, sog, cog, fix] = Layout::horizontal([
-        Constraint::Length(20), // lat
-        Constraint::Length(20), // lon
-        Constraint::Length(20), // alt
-        Constraint::Length(20), // hdg
-        Constraint::Length(20), // sog
-        Constraint::Length(20), // cog
-        Constraint::Length(20), // status
-    ])
-    .flex(Flex::Start)
-    .areas(frame.area());
-
-    render_statistics(frame, lat, "latitude", nmea.lat.clone());
-    render_statistics(frame, lon, "longitude", nmea.lon.clone());
-    render_statistics(frame, alt, "altitude", nmea.alt.clone());
-    render_statistics(frame, hdg, "heading", nmea.hdg.clone());
-    render_statistics(frame, sog, "sog", nmea.sog.clone());
-    render_statistics(frame, cog, "cog", nmea.cog.clone());
-    render_statistics(frame, fix, "fix", nmea.fix_type.clone());
-}
-
-fn render_statistics<'a, T>(frame: &mut Frame, area: Rect, title: &str, value: T)
-where
-    T: Into<Text<'a>>,
-{
-    let block = Block::new().title(title);
-    frame.render_widget(Paragraph::new(value).block(block), area);
-}
-
-#[derive(Clone, Debug)]
-struct StatusValue<T> {
-    inner: Option<T>,
-    updated_at: Instant,
-    timeout: Duration,
-}
-
-impl<T> Default for StatusValue<T> {
-    fn default() -> Self {
-        StatusValue {
-            inner: None,
-            updated_at: Instant::now(),
-            timeout: Duration::from_secs(5),
-        }
+    if let Some(path) = &args.control {
+        let _ = std::fs::remove_file(path);
     }
-}
 
-impl<T> StatusValue<T> {
-    pub fn new(timeout: Duration) -> StatusValue<T> {
-        StatusValue {
-            timeout,
-            ..Default::default()
-        }
+    let recording = || engine.recording.lock().expect("recording lock poisoned");
+    recording().recorder.flush();
+
+    #[cfg(feature = "export")]
+    if let Some(path) = &args.gpx {
+        gpx::write(path, &*engine.nmea.read().await, args.gpx_tolerance)
+            .expect("Failed to write GPX.");
     }
 
-    pub fn update(&mut self, next: impl Into<Option<T>>) {
-        self.inner = next.into();
-        self.updated_at = Instant::now();
+    #[cfg(feature = "export")]
+    if let Some(path) = &args.geojson {
+        geojson::write(path, &*engine.nmea.read().await).expect("Failed to write GeoJSON.");
     }
 
-    pub fn get(&self) -> Option<&T> {
-        self.inner
-            .as_ref()
-            .filter(|_| self.updated_at.elapsed() < self.timeout)
+    if let Some(reason) = reason {
+        let nmea = engine.nmea.read().await;
+        let summary = shutdown::summary(&nmea, recording().recorder.path());
+        info!(%reason, summary, "stopped");
+        eprintln!("{reason}: {summary}");
+        std::process::exit(reason.exit_code());
     }
+    info!("stopped");
 }
 
-impl<T> From<StatusValue<T>> for Text<'_>
-where
-    T: ToString,
-{
-    fn from(value: StatusValue<T>) -> Self {
-        match value.get() {
-            Some(v) => Text::from(v.to_string()),
-            None => Text::from("value"),
-        }
-    }
+/// Sets the time zone and labels, which every screen and file uses. Called again when
+/// the config is reloaded.
+fn init_globals(args: &Args, config: &Config) -> Result<()> {
+    clock::init(
+        args.timezone
+            .as_deref()
+            .or(config.display.timezone.as_deref()),
+        args.show_utc || config.display.show_utc,
+    )?;
+    i18n::init(config.labels.clone());
+    Ok(())
 }
@@ -0,0 +1,37 @@
+//! Man overboard: the position marked with `M`, and the bearing, distance and time back
+//! to it until the mark is cancelled with `X`.
+
+#[cfg(feature = "tui")]
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::geo;
+
+#[derive(Clone, Debug)]
+pub struct Mob {
+    pub position: (f64, f64),
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub marked: SystemTime,
+}
+
+impl Mob {
+    #[cfg(feature = "tui")]
+    pub fn new(position: (f64, f64)) -> Mob {
+        Mob {
+            position,
+            marked: SystemTime::now(),
+        }
+    }
+
+    /// Bearing in degrees and distance in meters from `own` back to the mark.
+    pub fn back_from(&self, own: (f64, f64)) -> (f64, f64) {
+        geo::course_and_length(geo::local_xy(own, self.position))
+    }
+
+    /// Time since the mark, to the second.
+    #[cfg(feature = "tui")]
+    pub fn elapsed(&self) -> Duration {
+        let elapsed = self.marked.elapsed().unwrap_or_default();
+        Duration::from_secs(elapsed.as_secs())
+    }
+}
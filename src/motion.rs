@@ -0,0 +1,41 @@
+//! Whether the receiver is at rest, so that the track does not fill with the scribble of
+//! position noise around a fixed point.
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+#[derive(Debug)]
+pub struct Motion {
+    /// Knots below which the receiver may be at rest.
+    threshold: f64,
+    /// How long speed must stay below the threshold.
+    delay: Duration,
+    below_since: Option<Instant>,
+}
+
+impl Motion {
+    pub fn new(threshold: f64, delay: Duration) -> Motion {
+        Motion {
+            threshold,
+            delay,
+            below_since: None,
+        }
+    }
+
+    pub fn update(&mut self, sog: Option<f64>) {
+        match sog {
+            Some(sog) if sog < self.threshold => {
+                self.below_since.get_or_insert_with(Instant::now);
+            }
+            _ => self.below_since = None,
+        }
+    }
+
+    /// Speed has been below the threshold for the delay. Movement resumes the track at
+    /// once.
+    pub fn stationary(&self) -> bool {
+        self.below_since
+            .is_some_and(|since| since.elapsed() >= self.delay)
+    }
+}
@@ -0,0 +1,109 @@
+//! NMEA 2000 PGNs received as text from USB gateways.
+//!
+//! Two formats are understood:
+//!
+//! - Yacht Devices RAW: `17:33:21.107 R 09F80100 2F 30 70 00 2F 30 70 00`
+//! - Actisense N2K ASCII: `A173321.107 01FF2 1F801 2F3070002F307000`
+//!
+//! Only single-frame PGNs are decoded, which covers position, COG/SOG, depth and wind.
+
+use crate::geo;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum N2kMessage {
+    /// PGN 129025.
+    Position { lat: f64, lon: f64 },
+    /// PGN 129026. Course in degrees, speed in knots.
+    CogSog { cog: Option<f64>, sog: Option<f64> },
+    /// PGN 128267. Depth below the transducer in meters.
+    Depth { depth: f64 },
+    /// PGN 130306. Angle in degrees, speed in knots.
+    Wind {
+        angle: f64,
+        speed: f64,
+        apparent: bool,
+    },
+}
+
+/// Parses a gateway line and decodes the PGNs we know about.
+pub fn parse(line: &str) -> Option<N2kMessage> {
+    let (pgn, data) = parse_yd_raw(line).or_else(|| parse_actisense_ascii(line))?;
+    decode(pgn, &data)
+}
+
+fn parse_yd_raw(line: &str) -> Option<(u32, Vec<u8>)> {
+    let mut parts = line.split_ascii_whitespace();
+    let _time = parts.next().filter(|t| t.contains(':'))?;
+    let _direction = parts.next().filter(|d| matches!(*d, "R" | "T"))?;
+    let id = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let data = parts
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((pgn_from_can_id(id), data))
+}
+
+fn parse_actisense_ascii(line: &str) -> Option<(u32, Vec<u8>)> {
+    let mut parts = line.split_ascii_whitespace();
+    let _time = parts.next().filter(|t| t.starts_with('A'))?;
+    let _address = parts.next().filter(|a| a.len() == 5)?;
+    let pgn = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let hex = parts.next()?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let data = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((pgn, data))
+}
+
+/// Extracts the PGN from a 29-bit CAN identifier. PDU1 PGNs (PF < 240) carry the
+/// destination address in PS, which is not part of the PGN.
+fn pgn_from_can_id(id: u32) -> u32 {
+    let pgn = (id >> 8) & 0x3FFFF;
+    if (pgn >> 8) & 0xFF < 240 {
+        pgn & !0xFF
+    } else {
+        pgn
+    }
+}
+
+fn decode(pgn: u32, data: &[u8]) -> Option<N2kMessage> {
+    match pgn {
+        129025 => Some(N2kMessage::Position {
+            lat: f64::from(i32_at(data, 0)?) * 1e-7,
+            lon: f64::from(i32_at(data, 4)?) * 1e-7,
+        }),
+        129026 => Some(N2kMessage::CogSog {
+            cog: u16_at(data, 2).map(|c| (f64::from(c) * 1e-4).to_degrees()),
+            sog: u16_at(data, 4).map(|s| f64::from(s) * 0.01 / geo::KNOT),
+        }),
+        128267 => Some(N2kMessage::Depth {
+            depth: f64::from(u32_at(data, 1)?) * 0.01,
+        }),
+        130306 => Some(N2kMessage::Wind {
+            speed: f64::from(u16_at(data, 1)?) * 0.01 / geo::KNOT,
+            angle: (f64::from(u16_at(data, 3)?) * 1e-4).to_degrees(),
+            apparent: data.get(5)? & 0x07 == 2,
+        }),
+        _ => None,
+    }
+}
+
+// Little-endian field readers returning `None` for the "not available" value.
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    let v = u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?);
+    (v != u16::MAX).then_some(v)
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    let v = u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?);
+    (v != u32::MAX).then_some(v)
+}
+
+fn i32_at(data: &[u8], at: usize) -> Option<i32> {
+    let v = i32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?);
+    (v != i32::MAX).then_some(v)
+}
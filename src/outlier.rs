@@ -0,0 +1,135 @@
+//! Rejection of physically impossible position jumps, such as multipath glitches or a
+//! receiver briefly reporting a stale fix.
+
+use tokio::time::Instant;
+
+use crate::geo;
+
+/// Consecutive outliers after which the position is believed to have really moved, so
+/// that fixes after a relocation are not all rejected.
+const RESET_AFTER: u32 = 5;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// When a fix was made: by the receiver's UTC time of day in seconds, or for NMEA 2000
+/// positions, which carry none, by arrival.
+#[derive(Clone, Copy, Debug)]
+enum FixTime {
+    Receiver(f64),
+    Arrival(Instant),
+}
+
+#[derive(Debug)]
+pub struct OutlierFilter {
+    /// Meters a fix may be from the previous one per second since it.
+    max_distance: f64,
+    last: Option<((f64, f64), FixTime)>,
+    consecutive: u32,
+    /// Fixes rejected so far.
+    pub count: u64,
+}
+
+impl OutlierFilter {
+    /// `max_distance` of zero accepts every fix.
+    pub fn new(max_distance: f64) -> OutlierFilter {
+        OutlierFilter {
+            max_distance,
+            last: None,
+            consecutive: 0,
+            count: 0,
+        }
+    }
+
+    /// Whether `position`, made at the UTC `time` of day if known, is plausible after the
+    /// last plausible fix. Times are often whole seconds and fixes from several talkers
+    /// share them, so fixes are allowed at least one second apart.
+    pub fn accept(&mut self, position: (f64, f64), time: Option<(i8, i8, f64)>) -> bool {
+        let now = match time {
+            Some((hour, minute, second)) => {
+                FixTime::Receiver(f64::from(hour) * 3600.0 + f64::from(minute) * 60.0 + second)
+            }
+            None => FixTime::Arrival(Instant::now()),
+        };
+        if let Some((last, at)) = self.last {
+            let seconds = match (at, now) {
+                (FixTime::Receiver(at), FixTime::Receiver(now)) => {
+                    (now - at).rem_euclid(SECONDS_PER_DAY)
+                }
+                (FixTime::Arrival(at), FixTime::Arrival(now)) => {
+                    now.duration_since(at).as_secs_f64()
+                }
+                // A source with times after one without, which is not checked.
+                _ => f64::INFINITY,
+            }
+            .max(1.0);
+            if self.max_distance > 0.0
+                && geo::distance(last, position) > self.max_distance * seconds
+                && self.consecutive < RESET_AFTER
+            {
+                self.consecutive += 1;
+                self.count += 1;
+                return false;
+            }
+        }
+        self.last = Some((position, now));
+        self.consecutive = 0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// About 111 m north of the equator per 0.001°.
+    fn north(thousandths: f64) -> (f64, f64) {
+        (thousandths / 1000.0, 0.0)
+    }
+
+    #[test]
+    fn jump_faster_than_the_limit_is_rejected() {
+        let mut filter = OutlierFilter::new(50.0);
+        assert!(filter.accept(north(0.0), Some((12, 0, 0.0))));
+        assert!(!filter.accept(north(1.0), Some((12, 0, 1.0))));
+        assert_eq!(filter.count, 1);
+    }
+
+    #[test]
+    fn distance_allowed_grows_with_receiver_time() {
+        let mut filter = OutlierFilter::new(50.0);
+        assert!(filter.accept(north(0.0), Some((12, 0, 0.0))));
+        // 111 m in 3 s, however fast the log is read.
+        assert!(filter.accept(north(1.0), Some((12, 0, 3.0))));
+        // Across midnight.
+        assert!(filter.accept(north(0.0), Some((23, 59, 59.0))));
+        assert!(filter.accept(north(1.0), Some((0, 0, 2.0))));
+        assert_eq!(filter.count, 0);
+    }
+
+    #[test]
+    fn rejected_fix_does_not_move_the_reference() {
+        let mut filter = OutlierFilter::new(50.0);
+        assert!(filter.accept(north(0.0), Some((12, 0, 0.0))));
+        assert!(!filter.accept(north(10.0), Some((12, 0, 1.0))));
+        assert!(filter.accept(north(0.1), Some((12, 0, 2.0))));
+    }
+
+    #[test]
+    fn position_that_stays_away_is_accepted_after_a_few_fixes() {
+        let mut filter = OutlierFilter::new(50.0);
+        assert!(filter.accept(north(0.0), Some((12, 0, 0.0))));
+        for second in 1..=RESET_AFTER {
+            assert!(!filter.accept(north(100.0), Some((12, 0, f64::from(second)))));
+        }
+        assert!(filter.accept(north(100.0), Some((12, 0, 6.0))));
+        assert!(filter.accept(north(100.0), Some((12, 0, 7.0))));
+        assert_eq!(filter.count, u64::from(RESET_AFTER));
+    }
+
+    #[test]
+    fn zero_accepts_everything() {
+        let mut filter = OutlierFilter::new(0.0);
+        assert!(filter.accept(north(0.0), Some((12, 0, 0.0))));
+        assert!(filter.accept(north(100.0), Some((12, 0, 1.0))));
+    }
+}
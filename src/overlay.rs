@@ -0,0 +1,74 @@
+//! Reference lines drawn under the track on the map, such as coastlines, runway outlines
+//! or property boundaries, from GeoJSON files listed as `overlays` in the config.
+//!
+//! Lines and polygon rings are drawn as lines and points as points, from any mix of
+//! feature collections, features and geometries. Properties are ignored.
+
+use std::path::Path;
+
+use anyhow::{bail, Context as _, Result};
+use serde_json::Value;
+
+#[derive(Default, Clone, Debug)]
+pub struct Overlay {
+    /// Polylines of latitude and longitude, closed for polygon rings. A single position is
+    /// a point.
+    pub lines: Vec<Vec<(f64, f64)>>,
+}
+
+impl Overlay {
+    /// Adds the geometries of the GeoJSON file at `path`.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let value: Value = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if !value["type"].is_string() {
+            bail!("{} is not GeoJSON", path.display());
+        }
+        self.collect(&value);
+        Ok(())
+    }
+
+    fn collect(&mut self, value: &Value) {
+        let coordinates = &value["coordinates"];
+        match value["type"].as_str() {
+            Some("FeatureCollection") => {
+                for feature in list(&value["features"]) {
+                    self.collect(feature);
+                }
+            }
+            Some("Feature") => self.collect(&value["geometry"]),
+            Some("GeometryCollection") => {
+                for geometry in list(&value["geometries"]) {
+                    self.collect(geometry);
+                }
+            }
+            Some("Point") => self.lines.extend(position(coordinates).map(|p| vec![p])),
+            Some("MultiPoint") => self.lines.extend(positions(coordinates).map(|p| vec![p])),
+            Some("LineString") => self.lines.push(positions(coordinates).collect()),
+            Some("MultiLineString" | "Polygon") => self
+                .lines
+                .extend(list(coordinates).map(|line| positions(line).collect())),
+            Some("MultiPolygon") => self.lines.extend(
+                list(coordinates)
+                    .flat_map(list)
+                    .map(|ring| positions(ring).collect()),
+            ),
+            _ => {}
+        }
+    }
+}
+
+fn list(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+/// Latitude and longitude of a GeoJSON position, which is longitude first.
+fn position(value: &Value) -> Option<(f64, f64)> {
+    Some((value[1].as_f64()?, value[0].as_f64()?))
+}
+
+fn positions(value: &Value) -> impl Iterator<Item = (f64, f64)> + '_ {
+    list(value).filter_map(position)
+}
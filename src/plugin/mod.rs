@@ -0,0 +1,149 @@
+//! Sentence decoders and output sinks compiled into the binary and enabled with
+//! `--plugin name` or `--plugin name=argument`.
+//!
+//! Rust has no stable ABI for loading trait objects from shared libraries, so plugins
+//! are modules in this directory behind a `plugin-*` cargo feature. Vendor support that
+//! lives out of tree implements [`Plugin`] in such a module and adds itself to
+//! [`REGISTRY`]; the rest of the program only sees the trait.
+
+#[cfg(feature = "plugin-quectel")]
+mod quectel;
+#[cfg(feature = "plugin-udp-forward")]
+mod udp_forward;
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tracing::warn;
+
+use crate::{sentence::Sentence, status::StatusValue};
+
+pub trait Plugin: Send + Sync {
+    /// Decodes a sentence into named values shown in the status grid.
+    fn decode(&mut self, _sentence: &Sentence) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Receives every line applied to the status, to pass it on elsewhere.
+    fn write(&mut self, _line: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct Registration {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Creates the plugin from the argument after `=`, if any.
+    pub create: fn(Option<&str>) -> Result<Box<dyn Plugin>>,
+}
+
+/// Plugins compiled into this binary.
+pub const REGISTRY: &[Registration] = &[
+    #[cfg(feature = "plugin-quectel")]
+    quectel::REGISTRATION,
+    #[cfg(feature = "plugin-udp-forward")]
+    udp_forward::REGISTRATION,
+];
+
+struct Enabled {
+    name: &'static str,
+    plugin: Box<dyn Plugin>,
+}
+
+pub struct Plugins {
+    enabled: Vec<Enabled>,
+    values: Vec<(String, StatusValue<String>)>,
+    timeout: Duration,
+    /// Latest error, with the plugin it came from.
+    pub error: Option<String>,
+}
+
+impl std::fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Plugins")
+            .field(
+                "enabled",
+                &self.enabled.iter().map(|e| e.name).collect::<Vec<_>>(),
+            )
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Plugins {
+    /// Decoded values expire after `timeout` like received values.
+    pub fn new(timeout: Duration) -> Plugins {
+        Plugins {
+            enabled: Vec::new(),
+            values: Vec::new(),
+            timeout,
+            error: None,
+        }
+    }
+
+    /// Enables the plugin named in `spec`, `name` or `name=argument`.
+    pub fn enable(&mut self, spec: &str) -> Result<()> {
+        let (name, argument) = match spec.split_once('=') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (spec, None),
+        };
+        let registration = REGISTRY
+            .iter()
+            .find(|registration| registration.name == name)
+            .ok_or_else(|| {
+                let available = REGISTRY
+                    .iter()
+                    .map(|registration| {
+                        format!("{} ({})", registration.name, registration.description)
+                    })
+                    .collect::<Vec<_>>();
+                anyhow!(
+                    "Unknown plugin {name}; available: {}",
+                    if available.is_empty() {
+                        "none".to_string()
+                    } else {
+                        available.join(", ")
+                    }
+                )
+            })?;
+        self.enabled.push(Enabled {
+            name: registration.name,
+            plugin: (registration.create)(argument)?,
+        });
+        Ok(())
+    }
+
+    pub fn on_line(&mut self, line: &str) {
+        let sentence = Sentence::parse(line);
+        for enabled in &mut self.enabled {
+            if let Err(e) = enabled.plugin.write(line) {
+                let error = format!("{}: {e:#}", enabled.name);
+                if self.error.as_ref() != Some(&error) {
+                    warn!("plugin failed: {error}");
+                }
+                self.error = Some(error);
+            }
+            let Some(sentence) = &sentence else {
+                continue;
+            };
+            for (name, value) in enabled.plugin.decode(sentence) {
+                let index = match self.values.iter().position(|(n, _)| *n == name) {
+                    Some(index) => index,
+                    None => {
+                        self.values.push((name, StatusValue::new(self.timeout)));
+                        self.values.len() - 1
+                    }
+                };
+                self.values[index].1.update(value);
+            }
+        }
+    }
+
+    /// Decoded values, in the order they were first seen.
+    #[cfg(feature = "tui")]
+    pub fn values(&self) -> impl Iterator<Item = (&str, Option<&String>)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.get()))
+    }
+}
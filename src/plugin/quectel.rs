@@ -0,0 +1,37 @@
+//! Quectel LG69T/LC29H proprietary sentences.
+
+use anyhow::{bail, Result};
+
+use super::{Plugin, Registration};
+use crate::sentence::Sentence;
+
+pub const REGISTRATION: Registration = Registration {
+    name: "quectel",
+    description: "estimated position error from $PQTMEPE",
+    create,
+};
+
+fn create(argument: Option<&str>) -> Result<Box<dyn Plugin>> {
+    if argument.is_some() {
+        bail!("The quectel plugin takes no argument");
+    }
+    Ok(Box::new(Quectel))
+}
+
+struct Quectel;
+
+impl Plugin for Quectel {
+    /// `$PQTMEPE,<ver>,<north>,<east>,<down>,<2d>,<3d>`, errors in meters.
+    fn decode(&mut self, sentence: &Sentence) -> Vec<(String, String)> {
+        if sentence.kind != "PQTMEPE" {
+            return Vec::new();
+        }
+        [("EPE 2D", 4), ("EPE 3D", 5)]
+            .into_iter()
+            .filter_map(|(name, index)| {
+                let meters = sentence.fields.get(index)?.parse::<f64>().ok()?;
+                Some((name.to_string(), format!("{meters:.2} m")))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,34 @@
+//! Forwards every line as a UDP datagram, e.g. to a chart plotter on the network.
+
+use std::net::UdpSocket;
+
+use anyhow::{Context as _, Result};
+
+use super::{Plugin, Registration};
+
+pub const REGISTRATION: Registration = Registration {
+    name: "udp-forward",
+    description: "send every line to udp-forward=host:port",
+    create,
+};
+
+fn create(argument: Option<&str>) -> Result<Box<dyn Plugin>> {
+    let address = argument.context("udp-forward needs a destination, udp-forward=host:port")?;
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket
+        .connect(address)
+        .with_context(|| format!("Failed to resolve {address}"))?;
+    socket.set_nonblocking(true)?;
+    Ok(Box::new(UdpForward { socket }))
+}
+
+struct UdpForward {
+    socket: UdpSocket,
+}
+
+impl Plugin for UdpForward {
+    fn write(&mut self, line: &str) -> Result<()> {
+        self.socket.send(format!("{line}\r\n").as_bytes())?;
+        Ok(())
+    }
+}
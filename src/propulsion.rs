@@ -0,0 +1,122 @@
+//! Engine speed from RPM sentences, and fuel and engine hours from XDR channels chosen
+//! in the config file, with the range left at the current consumption.
+//!
+//! ```toml
+//! [engine]
+//! fuel_rate = "FUELRATE"
+//! fuel_level = "FUEL"
+//! hours = "ENGINEHOURS"
+//! ```
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[cfg(feature = "tui")]
+use crate::sensors::{Reading, Sensors};
+use crate::{sentence::Sentence, status::StatusValue};
+
+/// XDR transducer ids of the engine channels.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineConfig {
+    /// Fuel flow, type `R`.
+    pub fuel_rate: Option<String>,
+    /// Fuel on board, type `V`.
+    pub fuel_level: Option<String>,
+    /// Engine hours, any type, in hours.
+    pub hours: Option<String>,
+    /// Tachometer, type `T`, for engines that send no RPM sentences.
+    pub rpm: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Propulsion {
+    config: EngineConfig,
+    /// Revolutions per minute of the first engine, from RPM.
+    rpm: StatusValue<f64>,
+}
+
+impl Propulsion {
+    pub fn new(config: EngineConfig, timeout: Duration) -> Propulsion {
+        Propulsion {
+            config,
+            rpm: StatusValue::new(timeout),
+        }
+    }
+
+    pub fn set_config(&mut self, config: EngineConfig) {
+        self.config = config;
+    }
+
+    /// Source (`E` engine, `S` shaft), number, speed, pitch, status.
+    pub fn update(&mut self, rpm: &Sentence) {
+        let fields = &rpm.fields;
+        if fields.first() == Some(&"E")
+            && matches!(fields.get(1), Some(&("0" | "1")))
+            && fields.get(4) != Some(&"V")
+        {
+            self.rpm
+                .update(fields.get(2).and_then(|f| f.parse::<f64>().ok()));
+        }
+    }
+
+    /// Whether there is anything to show.
+    #[cfg(feature = "tui")]
+    pub fn is_present(&self, sensors: &Sensors) -> bool {
+        self.rpm.get().is_some()
+            || [
+                &self.config.fuel_rate,
+                &self.config.fuel_level,
+                &self.config.hours,
+                &self.config.rpm,
+            ]
+            .into_iter()
+            .any(|id| reading(sensors, id).is_some())
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn rpm(&self, sensors: &Sensors) -> Option<f64> {
+        self.rpm
+            .get()
+            .copied()
+            .or_else(|| reading(sensors, &self.config.rpm).map(|r| r.value))
+    }
+
+    /// Fuel flow in liters per hour. XDR flow rates are in liters per second.
+    #[cfg(feature = "tui")]
+    pub fn fuel_rate(&self, sensors: &Sensors) -> Option<f64> {
+        let reading = reading(sensors, &self.config.fuel_rate)?;
+        match reading.unit.as_str() {
+            "L" => Some(reading.value * 3600.0),
+            _ => None,
+        }
+    }
+
+    /// Fuel on board in liters. XDR volumes are in cubic meters.
+    #[cfg(feature = "tui")]
+    pub fn fuel_level(&self, sensors: &Sensors) -> Option<f64> {
+        let reading = reading(sensors, &self.config.fuel_level)?;
+        match reading.unit.as_str() {
+            "M" => Some(reading.value * 1000.0),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn hours(&self, sensors: &Sensors) -> Option<f64> {
+        reading(sensors, &self.config.hours).map(|r| r.value)
+    }
+
+    /// Nautical miles the fuel on board lasts at `sog` knots and the current flow.
+    #[cfg(feature = "tui")]
+    pub fn range(&self, sensors: &Sensors, sog: f64) -> Option<f64> {
+        let rate = self.fuel_rate(sensors).filter(|&rate| rate > 0.0)?;
+        Some(self.fuel_level(sensors)? / rate * sog)
+    }
+}
+
+#[cfg(feature = "tui")]
+fn reading<'a>(sensors: &'a Sensors, id: &Option<String>) -> Option<&'a Reading> {
+    sensors.get(id.as_deref()?)
+}
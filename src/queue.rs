@@ -0,0 +1,156 @@
+//! Bounded queue between recording and decoding, with `--queue-capacity` and
+//! `--drop-policy` deciding what happens when decoding falls behind the input.
+//!
+//! Lines are recorded before they are queued, so a recording is complete whatever the
+//! policy; only the display can lose lines.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use tokio::sync::Notify;
+
+use crate::source::Received;
+
+#[derive(ValueEnum, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DropPolicy {
+    /// Wait for decoding, slowing down the readers.
+    #[default]
+    Never,
+    /// Drop the oldest queued line, so that the display stays current.
+    Oldest,
+    /// Drop the line that does not fit.
+    Newest,
+}
+
+#[derive(Debug)]
+pub struct Queue {
+    /// Lines with the time they were queued.
+    lines: Mutex<VecDeque<(Instant, Received)>>,
+    capacity: usize,
+    policy: DropPolicy,
+    /// Notified when a line was pushed.
+    pushed: Notify,
+    /// Notified when a line was popped.
+    popped: Notify,
+    /// Lines dropped, counted here so that dropping takes no other lock.
+    drops: AtomicU64,
+}
+
+impl Queue {
+    pub fn new(capacity: usize, policy: DropPolicy) -> Queue {
+        Queue {
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            policy,
+            pushed: Notify::new(),
+            popped: Notify::new(),
+            drops: AtomicU64::new(0),
+        }
+    }
+
+    /// Queues `line`, dropping a line instead of waiting when the queue is full, unless
+    /// the policy never drops.
+    pub async fn push(&self, line: Received) {
+        loop {
+            {
+                let mut lines = self.lines.lock().expect("queue lock poisoned");
+                let full = lines.len() >= self.capacity;
+                match self.policy {
+                    DropPolicy::Never if full => {}
+                    DropPolicy::Newest if full => {
+                        self.drops.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    _ => {
+                        if full {
+                            lines.pop_front();
+                            self.drops.fetch_add(1, Ordering::Relaxed);
+                        }
+                        lines.push_back((Instant::now(), line));
+                        self.pushed.notify_one();
+                        return;
+                    }
+                }
+            }
+            self.popped.notified().await;
+        }
+    }
+
+    /// The next line and how long it was queued, waiting for one if the queue is empty.
+    pub async fn pop(&self) -> (Received, Duration) {
+        loop {
+            let next = self.lines.lock().expect("queue lock poisoned").pop_front();
+            if let Some((queued, line)) = next {
+                self.popped.notify_one();
+                return (line, queued.elapsed());
+            }
+            self.pushed.notified().await;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.lock().expect("queue lock poisoned").len()
+    }
+
+    /// Lines dropped since the start.
+    pub fn drops(&self) -> u64 {
+        self.drops.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str) -> Received {
+        (0, text.to_string())
+    }
+
+    async fn filled(policy: DropPolicy) -> Queue {
+        let queue = Queue::new(2, policy);
+        for text in ["a", "b"] {
+            queue.push(line(text)).await;
+        }
+        queue
+    }
+
+    #[tokio::test]
+    async fn never_waits_for_room() {
+        let queue = filled(DropPolicy::Never).await;
+        let push = tokio::time::timeout(Duration::from_millis(50), queue.push(line("c")));
+        assert!(push.await.is_err());
+
+        assert_eq!(queue.pop().await.0, line("a"));
+        queue.push(line("c")).await;
+        assert_eq!(queue.pop().await.0, line("b"));
+        assert_eq!(queue.pop().await.0, line("c"));
+        assert_eq!(queue.drops(), 0);
+    }
+
+    #[tokio::test]
+    async fn oldest_makes_room() {
+        let queue = filled(DropPolicy::Oldest).await;
+        queue.push(line("c")).await;
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop().await.0, line("b"));
+        assert_eq!(queue.pop().await.0, line("c"));
+        assert_eq!(queue.drops(), 1);
+    }
+
+    #[tokio::test]
+    async fn newest_is_dropped() {
+        let queue = filled(DropPolicy::Newest).await;
+        queue.push(line("c")).await;
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop().await.0, line("a"));
+        assert_eq!(queue.pop().await.0, line("b"));
+        assert_eq!(queue.drops(), 1);
+    }
+}
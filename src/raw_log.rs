@@ -0,0 +1,88 @@
+//! Scrollback of the raw lines applied to the status.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::SystemTime,
+};
+
+use crate::sentence::Sentence;
+
+/// Number of lines kept.
+const RAW_LOG_LEN: usize = 5000;
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct LogLine {
+    /// Increases by one for every line, so lines can be referred to while old ones are
+    /// dropped.
+    pub number: u64,
+    pub received_at: SystemTime,
+    pub text: String,
+}
+
+#[derive(Default, Debug)]
+pub struct RawLog {
+    lines: VecDeque<LogLine>,
+    next: u64,
+    /// Latest line of every sentence address, such as `GNGGA`.
+    latest: HashMap<String, LogLine>,
+    /// The line before the latest of every address.
+    previous: HashMap<String, LogLine>,
+}
+
+impl RawLog {
+    pub fn push(&mut self, line: &str) {
+        self.push_at(SystemTime::now(), line);
+    }
+
+    /// Adds a line received earlier, such as one of a daemon's backlog.
+    pub fn push_at(&mut self, received_at: SystemTime, line: &str) {
+        if self.lines.len() == RAW_LOG_LEN {
+            self.lines.pop_front();
+        }
+        let line = LogLine {
+            number: self.next,
+            received_at,
+            text: line.to_string(),
+        };
+        if let Some(address) = address(&line.text) {
+            if let Some(previous) = self.latest.insert(address.clone(), line.clone()) {
+                self.previous.insert(address, previous);
+            }
+        }
+        self.lines.push_back(line);
+        self.next += 1;
+    }
+
+    /// Lines, oldest first.
+    #[cfg(feature = "tui")]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogLine> + ExactSizeIterator {
+        self.lines.iter()
+    }
+
+    /// Position of the line with `number` in [`RawLog::iter`], if it is still kept.
+    #[cfg(feature = "tui")]
+    pub fn index_of(&self, number: u64) -> Option<usize> {
+        let first = self.lines.front()?.number;
+        let index = usize::try_from(number.checked_sub(first)?).ok()?;
+        (index < self.lines.len()).then_some(index)
+    }
+
+    /// Latest line with the sentence `address`, however old.
+    #[cfg(feature = "tui")]
+    pub fn latest(&self, address: &str) -> Option<&LogLine> {
+        self.latest.get(address)
+    }
+
+    /// The line with `address` before the latest one.
+    #[cfg(feature = "tui")]
+    pub fn previous(&self, address: &str) -> Option<&LogLine> {
+        self.previous.get(address)
+    }
+}
+
+/// Talker and sentence type of a line, such as `GNGGA` or `PMTK001`.
+pub fn address(line: &str) -> Option<String> {
+    let sentence = Sentence::parse(line)?;
+    Some(format!("{}{}", sentence.talker, sentence.kind))
+}
@@ -0,0 +1,283 @@
+//! Recording of the raw input, as received from all sources.
+//!
+//! With rotation, each segment gets a timestamped name derived from the given path
+//! (`raw.nmea` becomes `raw-20240901T120000Z.nmea`), and completed segments can be
+//! compressed with gzip in the background.
+//!
+//! With `--record-extended`, lines are written in the [extended format](crate::extended)
+//! with their receive time and source.
+
+use std::{
+    borrow::Cow,
+    fs::{File, OpenOptions},
+    io::{self, LineWriter, Write as _},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Context as _, Result};
+use flate2::{write::GzEncoder, Compression};
+use tracing::{info, warn};
+
+use crate::{capture::Capture, extended};
+
+/// When to start a new segment.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rotation {
+    /// After the segment has been open this long.
+    Time(Duration),
+    /// Once the segment reaches this many bytes.
+    Size(u64),
+}
+
+impl FromStr for Rotation {
+    type Err = anyhow::Error;
+
+    /// `1h`, `30m` or `100MB`, `512KB`, `1GB`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        for (suffix, unit) in [("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10), ("B", 1)] {
+            if let Some(number) = upper.strip_suffix(suffix) {
+                let number = number.trim().parse::<u64>()?;
+                let size = number
+                    .checked_mul(unit)
+                    .with_context(|| format!("{s} is too large"))?;
+                return Ok(Self::Size(size));
+            }
+        }
+        Ok(Self::Time(s.parse::<humantime::Duration>()?.into()))
+    }
+}
+
+#[derive(Debug)]
+struct Segment {
+    path: PathBuf,
+    file: LineWriter<File>,
+    opened_at: Instant,
+    written: u64,
+}
+
+/// Where every line goes as it is received, before it is queued for decoding. Kept apart
+/// from the status so that recording never waits for the decoder.
+#[derive(Debug)]
+pub struct Recording {
+    pub recorder: Recorder,
+    /// Recent raw input kept for writing out when something goes wrong, with `--capture`.
+    pub capture: Option<Capture>,
+}
+
+impl Recording {
+    pub fn write(&mut self, source: usize, line: &str) {
+        self.recorder.write(source, line);
+        if let Some(capture) = &mut self.capture {
+            capture.push(line);
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Recorder {
+    rotation: Option<Rotation>,
+    gzip: bool,
+    /// Prefix lines with their receive time and source.
+    extended: bool,
+    /// Path given when recording started; segment names derive from it.
+    base: Option<PathBuf>,
+    /// The recording was split into trips, so segments are named as with rotation.
+    split: bool,
+    segment: Option<Segment>,
+}
+
+impl Recorder {
+    pub fn new(rotation: Option<Rotation>, gzip: bool, extended: bool) -> Recorder {
+        Recorder {
+            rotation,
+            gzip,
+            extended,
+            ..Default::default()
+        }
+    }
+
+    /// Starts appending to `path`, ending any recording in progress.
+    pub fn start(&mut self, path: &Path) -> Result<()> {
+        self.stop();
+        self.base = Some(path.to_path_buf());
+        self.open()
+    }
+
+    /// Starts a new segment for a new trip.
+    pub fn split(&mut self) {
+        if self.segment.is_none() {
+            return;
+        }
+        self.close();
+        self.split = true;
+        if let Err(e) = self.open() {
+            warn!("recording stopped: {e:#}");
+            self.base = None;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.close();
+        self.base = None;
+    }
+
+    /// Writes out buffered lines and syncs the file to disk, before an exit.
+    pub fn flush(&mut self) {
+        if let Some(segment) = &mut self.segment {
+            let _ = segment.file.flush();
+            let _ = segment.file.get_ref().sync_all();
+        }
+    }
+
+    /// File being recorded to.
+    pub fn path(&self) -> Option<&Path> {
+        self.segment.as_ref().map(|segment| segment.path.as_path())
+    }
+
+    /// Records `line`, received from the source at index `source`.
+    pub fn write(&mut self, source: usize, line: &str) {
+        if self.due() {
+            self.close();
+            // Keep the recording stopped if the next segment cannot be created.
+            if let Err(e) = self.open() {
+                warn!("recording stopped: {e:#}");
+                self.base = None;
+            }
+        }
+        let Some(segment) = &mut self.segment else {
+            return;
+        };
+        let line = if self.extended {
+            Cow::Owned(extended::format(SystemTime::now(), source, line))
+        } else {
+            Cow::Borrowed(line)
+        };
+        // Stop rather than fail every line when the disk is full or gone.
+        if let Err(e) = writeln!(segment.file, "{line}") {
+            warn!(path = %segment.path.display(), "recording stopped: {e}");
+            self.stop();
+            return;
+        }
+        segment.written += line.len() as u64 + 1;
+    }
+
+    fn due(&self) -> bool {
+        match (&self.segment, self.rotation) {
+            (Some(segment), Some(Rotation::Time(period))) => segment.opened_at.elapsed() >= period,
+            (Some(segment), Some(Rotation::Size(size))) => segment.written >= size,
+            _ => false,
+        }
+    }
+
+    fn open(&mut self) -> Result<()> {
+        let Some(base) = &self.base else {
+            return Ok(());
+        };
+        let path = if self.rotation.is_some() || self.split {
+            segment_path(base, SystemTime::now())
+        } else {
+            base.clone()
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let written = file.metadata().map_or(0, |m| m.len());
+        info!(path = %path.display(), "recording");
+        self.segment = Some(Segment {
+            path,
+            file: LineWriter::new(file),
+            opened_at: Instant::now(),
+            written,
+        });
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        let Some(segment) = self.segment.take() else {
+            return;
+        };
+        drop(segment.file);
+        if self.gzip && self.rotation.is_some() {
+            let path = segment.path;
+            std::thread::spawn(move || match compress(&path) {
+                Ok(()) => {
+                    let _ = std::fs::remove_file(&path);
+                }
+                Err(e) => warn!(path = %path.display(), "failed to compress: {e}"),
+            });
+        }
+    }
+}
+
+/// `dir/raw.nmea` → `dir/raw-20240901T120000Z.nmea`, with a counter added when segments
+/// rotate faster than once a second.
+pub fn segment_path(base: &Path, time: SystemTime) -> PathBuf {
+    let stamp = humantime::format_rfc3339_seconds(time)
+        .to_string()
+        .replace(['-', ':'], "");
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = base
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (0..)
+        .map(|n| {
+            let suffix = if n == 0 {
+                String::new()
+            } else {
+                format!("-{n}")
+            };
+            base.with_file_name(format!("{stem}-{stamp}{suffix}{extension}"))
+        })
+        .find(|path| !path.exists() && !path.with_extension(gz_extension(path)).exists())
+        .expect("Ran out of segment names.")
+}
+
+/// Extension of the compressed copy of `path`.
+fn gz_extension(path: &Path) -> String {
+    match path.extension() {
+        Some(extension) => format!("{}.gz", extension.to_string_lossy()),
+        None => "gz".to_string(),
+    }
+}
+
+/// Writes `path.gz`.
+fn compress(path: &Path) -> io::Result<()> {
+    let mut input = File::open(path)?;
+    let output = File::create(path.with_extension(gz_extension(path)))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes_and_durations() {
+        assert_eq!(
+            "512KB".parse::<Rotation>().unwrap(),
+            Rotation::Size(512 << 10)
+        );
+        assert_eq!(
+            "100 mb".parse::<Rotation>().unwrap(),
+            Rotation::Size(100 << 20)
+        );
+        assert_eq!(
+            "30m".parse::<Rotation>().unwrap(),
+            Rotation::Time(Duration::from_secs(30 * 60))
+        );
+    }
+
+    #[test]
+    fn rejects_sizes_that_overflow() {
+        assert!("17179869184GB".parse::<Rotation>().is_err());
+        assert!(format!("{}B", u64::MAX).parse::<Rotation>().is_ok());
+    }
+}
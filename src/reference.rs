@@ -0,0 +1,450 @@
+//! Built-in reference of the fields of common sentences, for labelling raw lines.
+
+use crate::sentence::Sentence;
+
+/// What a field holds, and so how its value is decoded.
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Text,
+    /// `hhmmss.ss` UTC.
+    Time,
+    /// `ddmmyy`.
+    Date,
+    /// `ddmm.mmmm`.
+    Latitude,
+    /// `dddmm.mmmm`.
+    Longitude,
+    Number(&'static str),
+    /// Single-letter codes and their meanings.
+    Code(&'static [(&'static str, &'static str)]),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Field {
+    name: &'static str,
+    kind: Kind,
+}
+
+const fn field(name: &'static str, kind: Kind) -> Field {
+    Field { name, kind }
+}
+
+struct Reference {
+    description: &'static str,
+    fields: &'static [Field],
+    /// Group of fields repeated after `fields` until the end of the sentence.
+    repeat: &'static [Field],
+}
+
+const NS: Kind = Kind::Code(&[("N", "north"), ("S", "south")]);
+const EW: Kind = Kind::Code(&[("E", "east"), ("W", "west")]);
+const VALIDITY: Kind = Kind::Code(&[("A", "valid"), ("V", "invalid")]);
+const MODE: Kind = Kind::Code(&[
+    ("A", "autonomous"),
+    ("D", "differential"),
+    ("E", "estimated"),
+    ("F", "float RTK"),
+    ("M", "manual"),
+    ("N", "not valid"),
+    ("P", "precise"),
+    ("R", "RTK"),
+    ("S", "simulator"),
+]);
+const TRUE: Kind = Kind::Code(&[("T", "true")]);
+const MAGNETIC: Kind = Kind::Code(&[("M", "magnetic")]);
+const METERS: Kind = Kind::Code(&[("M", "meters")]);
+const RELATIVE: Kind = Kind::Code(&[("R", "relative"), ("T", "true")]);
+const SPEED_UNIT: Kind = Kind::Code(&[("K", "km/h"), ("M", "m/s"), ("N", "knots")]);
+
+const GGA: Reference = Reference {
+    description: "Fix data",
+    fields: &[
+        field("time", Kind::Time),
+        field("latitude", Kind::Latitude),
+        field("N/S", NS),
+        field("longitude", Kind::Longitude),
+        field("E/W", EW),
+        field(
+            "quality",
+            Kind::Code(&[
+                ("0", "invalid"),
+                ("1", "GPS"),
+                ("2", "DGPS"),
+                ("3", "PPS"),
+                ("4", "RTK fixed"),
+                ("5", "RTK float"),
+                ("6", "estimated"),
+                ("7", "manual"),
+                ("8", "simulation"),
+            ]),
+        ),
+        field("satellites", Kind::Number("")),
+        field("HDOP", Kind::Number("")),
+        field("altitude", Kind::Number("")),
+        field("altitude unit", METERS),
+        field("geoid separation", Kind::Number("")),
+        field("separation unit", METERS),
+        field("DGPS age", Kind::Number("s")),
+        field("DGPS station", Kind::Text),
+    ],
+    repeat: &[],
+};
+
+const RMC: Reference = Reference {
+    description: "Recommended minimum",
+    fields: &[
+        field("time", Kind::Time),
+        field("status", VALIDITY),
+        field("latitude", Kind::Latitude),
+        field("N/S", NS),
+        field("longitude", Kind::Longitude),
+        field("E/W", EW),
+        field("speed", Kind::Number("kn")),
+        field("course", Kind::Number("°")),
+        field("date", Kind::Date),
+        field("magnetic variation", Kind::Number("°")),
+        field("variation E/W", EW),
+        field("mode", MODE),
+        field("navigational status", Kind::Text),
+    ],
+    repeat: &[],
+};
+
+const GLL: Reference = Reference {
+    description: "Geographic position",
+    fields: &[
+        field("latitude", Kind::Latitude),
+        field("N/S", NS),
+        field("longitude", Kind::Longitude),
+        field("E/W", EW),
+        field("time", Kind::Time),
+        field("status", VALIDITY),
+        field("mode", MODE),
+    ],
+    repeat: &[],
+};
+
+const GNS: Reference = Reference {
+    description: "Multi-constellation fix data",
+    fields: &[
+        field("time", Kind::Time),
+        field("latitude", Kind::Latitude),
+        field("N/S", NS),
+        field("longitude", Kind::Longitude),
+        field("E/W", EW),
+        field("mode per constellation", Kind::Text),
+        field("satellites", Kind::Number("")),
+        field("HDOP", Kind::Number("")),
+        field("altitude", Kind::Number("m")),
+        field("geoid separation", Kind::Number("m")),
+        field("DGPS age", Kind::Number("s")),
+        field("DGPS station", Kind::Text),
+        field("navigational status", Kind::Text),
+    ],
+    repeat: &[],
+};
+
+const VTG: Reference = Reference {
+    description: "Course and speed over ground",
+    fields: &[
+        field("course", Kind::Number("°")),
+        field("reference", TRUE),
+        field("magnetic course", Kind::Number("°")),
+        field("reference", MAGNETIC),
+        field("speed", Kind::Number("kn")),
+        field("unit", SPEED_UNIT),
+        field("speed", Kind::Number("km/h")),
+        field("unit", SPEED_UNIT),
+        field("mode", MODE),
+    ],
+    repeat: &[],
+};
+
+const GSA: Reference = Reference {
+    description: "DOP and active satellites",
+    fields: &[
+        field(
+            "selection",
+            Kind::Code(&[("A", "automatic"), ("M", "manual")]),
+        ),
+        field(
+            "fix",
+            Kind::Code(&[("1", "no fix"), ("2", "2D"), ("3", "3D")]),
+        ),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("satellite", Kind::Text),
+        field("PDOP", Kind::Number("")),
+        field("HDOP", Kind::Number("")),
+        field("VDOP", Kind::Number("")),
+        field("system id", Kind::Text),
+    ],
+    repeat: &[],
+};
+
+const GSV: Reference = Reference {
+    description: "Satellites in view",
+    fields: &[
+        field("sentences", Kind::Number("")),
+        field("sentence", Kind::Number("")),
+        field("satellites in view", Kind::Number("")),
+    ],
+    repeat: &[
+        field("PRN", Kind::Text),
+        field("elevation", Kind::Number("°")),
+        field("azimuth", Kind::Number("°")),
+        field("SNR", Kind::Number("dB-Hz")),
+    ],
+};
+
+const ZDA: Reference = Reference {
+    description: "Time and date",
+    fields: &[
+        field("time", Kind::Time),
+        field("day", Kind::Number("")),
+        field("month", Kind::Number("")),
+        field("year", Kind::Number("")),
+        field("zone hours", Kind::Number("h")),
+        field("zone minutes", Kind::Number("min")),
+    ],
+    repeat: &[],
+};
+
+const HDT: Reference = Reference {
+    description: "True heading",
+    fields: &[
+        field("heading", Kind::Number("°")),
+        field("reference", TRUE),
+    ],
+    repeat: &[],
+};
+
+const HDG: Reference = Reference {
+    description: "Magnetic heading, deviation and variation",
+    fields: &[
+        field("heading", Kind::Number("°")),
+        field("deviation", Kind::Number("°")),
+        field("deviation E/W", EW),
+        field("variation", Kind::Number("°")),
+        field("variation E/W", EW),
+    ],
+    repeat: &[],
+};
+
+const DBT: Reference = Reference {
+    description: "Depth below transducer",
+    fields: &[
+        field("depth", Kind::Number("ft")),
+        field("unit", Kind::Code(&[("f", "feet")])),
+        field("depth", Kind::Number("m")),
+        field("unit", METERS),
+        field("depth", Kind::Number("fathoms")),
+        field("unit", Kind::Code(&[("F", "fathoms")])),
+    ],
+    repeat: &[],
+};
+
+const DPT: Reference = Reference {
+    description: "Depth",
+    fields: &[
+        field("depth below transducer", Kind::Number("m")),
+        field("transducer offset", Kind::Number("m")),
+        field("maximum range", Kind::Number("m")),
+    ],
+    repeat: &[],
+};
+
+const MWV: Reference = Reference {
+    description: "Wind speed and angle",
+    fields: &[
+        field("angle", Kind::Number("°")),
+        field("reference", RELATIVE),
+        field("speed", Kind::Number("")),
+        field("unit", SPEED_UNIT),
+        field("status", VALIDITY),
+    ],
+    repeat: &[],
+};
+
+const VHW: Reference = Reference {
+    description: "Water speed and heading",
+    fields: &[
+        field("heading", Kind::Number("°")),
+        field("reference", TRUE),
+        field("magnetic heading", Kind::Number("°")),
+        field("reference", MAGNETIC),
+        field("speed", Kind::Number("kn")),
+        field("unit", SPEED_UNIT),
+        field("speed", Kind::Number("km/h")),
+        field("unit", SPEED_UNIT),
+    ],
+    repeat: &[],
+};
+
+const XDR: Reference = Reference {
+    description: "Transducer measurements",
+    fields: &[],
+    repeat: &[
+        field(
+            "type",
+            Kind::Code(&[
+                ("A", "angle"),
+                ("C", "temperature"),
+                ("D", "displacement"),
+                ("H", "humidity"),
+                ("P", "pressure"),
+                ("U", "voltage"),
+                ("I", "current"),
+            ]),
+        ),
+        field("value", Kind::Number("")),
+        field("unit", Kind::Text),
+        field("transducer id", Kind::Text),
+    ],
+};
+
+const TXT: Reference = Reference {
+    description: "Text message",
+    fields: &[
+        field("sentences", Kind::Number("")),
+        field("sentence", Kind::Number("")),
+        field(
+            "type",
+            Kind::Code(&[
+                ("00", "error"),
+                ("01", "warning"),
+                ("02", "notice"),
+                ("07", "user"),
+            ]),
+        ),
+        field("text", Kind::Text),
+    ],
+    repeat: &[],
+};
+
+const VDM: Reference = Reference {
+    description: "AIS message",
+    fields: &[
+        field("fragments", Kind::Number("")),
+        field("fragment", Kind::Number("")),
+        field("message id", Kind::Text),
+        field("channel", Kind::Text),
+        field("payload", Kind::Text),
+        field("fill bits", Kind::Number("")),
+    ],
+    repeat: &[],
+};
+
+fn reference(kind: &str) -> Option<&'static Reference> {
+    Some(match kind {
+        "GGA" => &GGA,
+        "RMC" => &RMC,
+        "GLL" => &GLL,
+        "GNS" => &GNS,
+        "VTG" => &VTG,
+        "GSA" => &GSA,
+        "GSV" => &GSV,
+        "ZDA" => &ZDA,
+        "HDT" => &HDT,
+        "HDG" => &HDG,
+        "DBT" => &DBT,
+        "DPT" => &DPT,
+        "MWV" => &MWV,
+        "VHW" => &VHW,
+        "XDR" => &XDR,
+        "TXT" => &TXT,
+        "VDM" | "VDO" => &VDM,
+        _ => return None,
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct DecodedField {
+    pub name: &'static str,
+    pub raw: String,
+    /// Meaning of the raw value, when it differs from it.
+    pub value: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Decoded {
+    /// `GNGGA`
+    pub address: String,
+    /// What the sentence is, if it is in the reference.
+    pub description: Option<&'static str>,
+    pub fields: Vec<DecodedField>,
+}
+
+/// Labels the fields of `line`. Sentences missing from the reference get numbered fields.
+pub fn decode(line: &str) -> Option<Decoded> {
+    let sentence = Sentence::parse(line)?;
+    let reference = reference(sentence.kind);
+    let fields = sentence
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            let field = reference.and_then(|reference| {
+                reference.fields.get(i).or_else(|| {
+                    let i = i.checked_sub(reference.fields.len())?;
+                    reference.repeat.get(i % reference.repeat.len().max(1))
+                })
+            });
+            DecodedField {
+                name: field.map_or("", |field| field.name),
+                raw: raw.to_string(),
+                value: field.and_then(|field| value(field.kind, raw)),
+            }
+        })
+        .collect();
+    Some(Decoded {
+        address: format!("{}{}", sentence.talker, sentence.kind),
+        description: reference.map(|reference| reference.description),
+        fields,
+    })
+}
+
+fn value(kind: Kind, raw: &str) -> Option<String> {
+    if raw.is_empty() {
+        return None;
+    }
+    match kind {
+        Kind::Text => None,
+        Kind::Time => {
+            let (h, rest) = raw.split_at_checked(2)?;
+            let (m, s) = rest.split_at_checked(2)?;
+            Some(format!("{h}:{m}:{s} UTC"))
+        }
+        Kind::Date => {
+            let (d, rest) = raw.split_at_checked(2)?;
+            let (m, y) = rest.split_at_checked(2)?;
+            // As decoded: GPS time starts in 1980.
+            let century = if y.parse::<u8>().ok()? >= 80 { 19 } else { 20 };
+            Some(format!("{century}{y}-{m}-{d}"))
+        }
+        Kind::Latitude => degrees(raw, 2),
+        Kind::Longitude => degrees(raw, 3),
+        Kind::Number("") => None,
+        Kind::Number(unit) => Some(format!("{raw} {unit}")),
+        Kind::Code(codes) => codes
+            .iter()
+            .find(|(code, _)| *code == raw)
+            .map(|(_, meaning)| meaning.to_string()),
+    }
+}
+
+/// `ddmm.mmmm` with `digits` digits of degrees, as decimal degrees.
+fn degrees(raw: &str, digits: usize) -> Option<String> {
+    let (degrees, minutes) = raw.split_at_checked(digits)?;
+    let degrees = degrees.parse::<f64>().ok()? + minutes.parse::<f64>().ok()? / 60.0;
+    Some(format!("{degrees:.6}°"))
+}
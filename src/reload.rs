@@ -0,0 +1,101 @@
+//! Applying edits to the `--config` file without restarting, since the monitor often runs
+//! unattended for days. The file is reloaded when its modification time changes, with
+//! `R`, and on SIGHUP when that does not stop the monitor.
+//!
+//! Labels, the time zone, coordinate format, custom values, transducer names, the engine
+//! and battery panels and tide stations take effect at once, and the UI applies its map
+//! URL, map overlays, pinned sentences and palette. Options given on the command line
+//! still take precedence. Sources, recording and the other options read at start need a
+//! restart.
+//!
+//! A file that fails to load leaves the previous config in place and raises an alert
+//! until it loads.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use tokio::sync::{watch, Notify, RwLock};
+use tracing::{info, warn};
+
+#[cfg(unix)]
+use crate::shutdown;
+use crate::{config::Config, status::NmeaStatus, Args};
+
+/// How often the modification time of the file is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub struct Reload {
+    /// Notified to reload now.
+    pub request: Arc<Notify>,
+    /// The last config applied, for the UI to apply its part of.
+    pub config: watch::Receiver<Option<Arc<Config>>>,
+}
+
+/// Spawns the task watching `path`, which the engine was started with.
+pub fn spawn(args: Args, path: PathBuf, nmea: Arc<RwLock<NmeaStatus>>) -> Reload {
+    let request = Arc::new(Notify::new());
+    let (sender, config) = watch::channel(None);
+
+    #[cfg(unix)]
+    if shutdown::reload_on_hangup() {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangup = signal(SignalKind::hangup()).expect("Failed to handle SIGHUP.");
+        let request = Arc::clone(&request);
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                request.notify_one();
+            }
+        });
+    }
+
+    {
+        let request = Arc::clone(&request);
+        tokio::spawn(async move {
+            let mut last = modified(&path);
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        let now = modified(&path);
+                        if now == last {
+                            continue;
+                        }
+                        last = now;
+                    }
+                    _ = request.notified() => {}
+                }
+
+                let loaded = Config::load(&path).and_then(|mut config| {
+                    if let Some(name) = &args.profile {
+                        config.select_profile(name)?;
+                    }
+                    crate::init_globals(&args, &config)?;
+                    Ok(config)
+                });
+                let mut nmea = nmea.write().await;
+                match loaded {
+                    Ok(config) => {
+                        nmea.reconfigure(&args, &config);
+                        nmea.config_error = None;
+                        info!(path = %path.display(), "config reloaded");
+                        let _ = sender.send(Some(Arc::new(config)));
+                    }
+                    Err(e) => {
+                        warn!(path = %path.display(), "config not reloaded: {e:#}");
+                        nmea.config_error = Some(format!("{e:#}"));
+                    }
+                }
+            }
+        });
+    }
+
+    Reload { request, config }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
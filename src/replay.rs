@@ -0,0 +1,283 @@
+//! Paced playback of a log file with bookmarks to jump back to, with `--replay`.
+//!
+//! Lines are sent at the pace of the receiver times in RMC and ZDA sentences, or of the
+//! receive times of an extended log. Bookmarks are file offsets, kept next to the log in
+//! `<log>.bookmarks` as JSON.
+//!
+//! Times that jump, as after a receiver restart or a wrong GPS week, are discontinuities:
+//! playback resynchronizes to the new time and continues at the last regular pace
+//! instead of waiting out the jump or sending everything at once.
+
+use std::{
+    io::{ErrorKind, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncSeekExt as _, BufReader},
+    sync::{mpsc::Sender, RwLock},
+    time::sleep,
+};
+
+use crate::{
+    extended, input,
+    sentence::Sentence,
+    source::{Attribution, Received, SourceState},
+    status::NmeaStatus,
+};
+
+/// Longer gaps between recorded times are discontinuities.
+const MAX_GAP: f64 = 5.0;
+
+/// Times going back by up to this many seconds are played without a pause, as sentences
+/// of one epoch can arrive slightly out of order. Further back is a discontinuity.
+const JITTER: f64 = 1.0;
+
+/// Pace until the first regular gap is known.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often playback that reached the end checks for a jump back.
+const IDLE_POLL: Duration = Duration::from_millis(200);
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Bookmark {
+    /// Byte offset of the first line after the bookmark.
+    pub offset: u64,
+    /// Receiver time of day in seconds at the bookmark.
+    pub time: Option<f64>,
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Debug)]
+pub struct Replay {
+    /// Index of the source being played.
+    pub source: usize,
+    path: PathBuf,
+    /// Playback speed relative to the recorded pace.
+    pub speed: f64,
+    /// Byte offset of the next line.
+    pub offset: u64,
+    /// Size of the file.
+    pub length: u64,
+    /// Receiver time of day in seconds of the last timed line.
+    pub time: Option<f64>,
+    /// Number of times the recorded time jumped and playback resynchronized.
+    pub discontinuities: u64,
+    /// Offset to continue from, set by a jump until playback picks it up.
+    seek: Option<u64>,
+    /// Bookmarks in file order.
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Replay {
+    /// Plays `path` as the source at index `source`, loading its saved bookmarks.
+    pub fn new(source: usize, path: &Path, speed: f64) -> Result<Replay> {
+        let saved = bookmarks_path(path);
+        let bookmarks = match std::fs::read_to_string(&saved) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", saved.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", saved.display())),
+        };
+        let length = std::fs::metadata(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?
+            .len();
+        Ok(Replay {
+            source,
+            path: path.to_path_buf(),
+            speed,
+            offset: 0,
+            length,
+            time: None,
+            discontinuities: 0,
+            seek: None,
+            bookmarks,
+        })
+    }
+
+    /// Bookmarks the current playback position and saves the bookmarks.
+    #[cfg(feature = "tui")]
+    pub fn bookmark(&mut self) -> Result<Bookmark> {
+        let bookmark = Bookmark {
+            offset: self.offset,
+            time: self.time,
+        };
+        let index = self
+            .bookmarks
+            .partition_point(|b| b.offset < bookmark.offset);
+        if self.bookmarks.get(index) != Some(&bookmark) {
+            self.bookmarks.insert(index, bookmark.clone());
+        }
+        let saved = bookmarks_path(&self.path);
+        std::fs::write(&saved, serde_json::to_string_pretty(&self.bookmarks)?)
+            .with_context(|| format!("Failed to write {}", saved.display()))?;
+        Ok(bookmark)
+    }
+
+    /// Continues playback from `offset`, also after the end of the file was reached.
+    #[cfg(feature = "tui")]
+    pub fn jump(&mut self, offset: u64) {
+        self.seek = Some(offset);
+    }
+}
+
+fn bookmarks_path(log: &Path) -> PathBuf {
+    let mut path = log.as_os_str().to_owned();
+    path.push(".bookmarks");
+    path.into()
+}
+
+/// Receiver time of day in seconds of an RMC or ZDA sentence, also in an extended log.
+pub fn time_of_day(line: &str) -> Option<f64> {
+    let sentence = Sentence::parse(extended::strip(line))?;
+    if !matches!(sentence.kind, "RMC" | "ZDA") {
+        return None;
+    }
+    let time = sentence.fields.first()?;
+    let hours = time.get(..2)?.parse::<f64>().ok()?;
+    let minutes = time.get(2..4)?.parse::<f64>().ok()?;
+    let seconds = time.get(4..)?.parse::<f64>().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Time that playback is paced by.
+#[derive(Clone, Copy, Debug)]
+enum Pace {
+    /// Receive time of an extended log line, in seconds since the epoch.
+    Received(f64),
+    /// Receiver time of day in seconds.
+    TimeOfDay(f64),
+}
+
+impl Pace {
+    fn of(line: &str) -> Option<Pace> {
+        match extended::parse(line) {
+            Some((received, _, _)) => Some(Pace::Received(
+                received.duration_since(UNIX_EPOCH).ok()?.as_secs_f64(),
+            )),
+            None => time_of_day(line).map(Pace::TimeOfDay),
+        }
+    }
+
+    /// Seconds from `previous`, negative when the time went back, when both are the same
+    /// kind of time. Times of day take the shorter way around midnight.
+    fn since(self, previous: Pace) -> Option<f64> {
+        match (previous, self) {
+            (Pace::Received(previous), Pace::Received(now)) => Some(now - previous),
+            (Pace::TimeOfDay(previous), Pace::TimeOfDay(now)) => {
+                let half_day = SECONDS_PER_DAY / 2.0;
+                Some((now - previous + half_day).rem_euclid(SECONDS_PER_DAY) - half_day)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `hh:mm:ss` of a time of day in seconds.
+#[cfg(feature = "tui")]
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Sends the lines of `file` at their recorded pace, following the jumps requested
+/// through the status. Keeps waiting for a jump after the end of the file.
+pub async fn play(
+    index: usize,
+    file: File,
+    lines: Sender<Received>,
+    nmea: Arc<RwLock<NmeaStatus>>,
+) {
+    let mut reader = BufReader::new(file);
+    let mut buf = Vec::new();
+    let mut offset = 0;
+    let mut time = None;
+    let mut attribution = Attribution::default();
+    // Time of the previous timed line, which the next one is paced from.
+    let mut previous: Option<Pace> = None;
+    // Last regular gap, which discontinuities are played as.
+    let mut interval = DEFAULT_INTERVAL;
+    let mut discontinuities = 0;
+    let mut finished = false;
+    loop {
+        let (seek, speed) = {
+            let mut nmea = nmea.write().await;
+            let Some(replay) = &mut nmea.replay else {
+                return;
+            };
+            replay.offset = offset;
+            replay.time = time;
+            replay.discontinuities = discontinuities;
+            (replay.seek.take(), replay.speed)
+        };
+        if let Some(target) = seek {
+            if let Err(e) = reader.seek(SeekFrom::Start(target)).await {
+                let state = SourceState::Failed(e.to_string());
+                return attribution.set_state(&nmea, index, state).await;
+            }
+            offset = target;
+            previous = None;
+            if finished {
+                finished = false;
+                attribution
+                    .set_state(&nmea, index, SourceState::Connected)
+                    .await;
+            }
+        }
+        if finished {
+            sleep(IDLE_POLL).await;
+            continue;
+        }
+
+        buf.clear();
+        match input::read_line(&mut reader, &mut buf).await {
+            Ok(0) => {
+                finished = true;
+                attribution
+                    .set_state(&nmea, index, SourceState::Closed)
+                    .await;
+                continue;
+            }
+            Err(e) => {
+                let state = SourceState::Failed(e.to_string());
+                return attribution.set_state(&nmea, index, state).await;
+            }
+            Ok(read) => offset += read as u64,
+        }
+        let (line, dropped) = input::extract_text(&buf);
+        if dropped > 0 {
+            nmea.write().await.dropped_bytes += dropped as u64;
+        }
+        if let Some(now) = Pace::of(line) {
+            match previous.and_then(|previous| now.since(previous)) {
+                Some(gap) if !(-JITTER..=MAX_GAP).contains(&gap) => {
+                    discontinuities += 1;
+                    sleep(interval.div_f64(speed)).await;
+                }
+                Some(gap) if gap > 0.0 => {
+                    interval = Duration::from_secs_f64(gap);
+                    sleep(interval.div_f64(speed)).await;
+                }
+                _ => {}
+            }
+            previous = Some(now);
+        }
+        time = time_of_day(line).or(time);
+        let (source, line) = attribution.attribute(index, line, &nmea).await;
+        if !line.is_empty() && lines.send((source, line.to_string())).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Satellites in view, built from GSV sentences.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::{Duration, SystemTime},
+};
+
+use tokio::time::Instant;
+
+use crate::sentence::Sentence;
+
+/// Number of SNR samples kept per satellite.
+const SNR_HISTORY_LEN: usize = 300;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SatelliteId {
+    pub talker: String,
+    pub prn: u32,
+}
+
+impl SatelliteId {
+    #[cfg(feature = "tui")]
+    pub fn constellation(&self) -> &'static str {
+        match self.talker.as_str() {
+            "GP" => "GPS",
+            "GL" => "GLONASS",
+            "GA" => "Galileo",
+            "GB" | "BD" => "BeiDou",
+            "GQ" => "QZSS",
+            "GI" => "NavIC",
+            "GN" => "GNSS",
+            _ => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Satellite {
+    pub elevation: Option<f32>,
+    pub azimuth: Option<f32>,
+    pub snr: Option<u8>,
+    /// SNR samples, oldest first. Untracked samples are recorded as 0.
+    pub history: VecDeque<u64>,
+    /// When the satellite was seen rising above the horizon. `None` if it was already up
+    /// when first seen.
+    pub rise: Option<SystemTime>,
+    /// When the satellite was seen going below the horizon.
+    pub set: Option<SystemTime>,
+    /// Signal id of the GSV group that last reported this satellite.
+    signal: Option<String>,
+    last_seen: Option<Instant>,
+}
+
+impl Satellite {
+    pub fn is_up(&self) -> bool {
+        self.elevation.is_some_and(|e| e > 0.0)
+    }
+
+    fn update(
+        &mut self,
+        elevation: Option<f32>,
+        azimuth: Option<f32>,
+        snr: Option<u8>,
+        first_seen: bool,
+        now: SystemTime,
+    ) {
+        let was_up = self.is_up();
+        self.elevation = elevation;
+        self.azimuth = azimuth;
+        self.snr = snr;
+
+        match (was_up, self.is_up()) {
+            (false, true) if !first_seen => {
+                self.rise = Some(now);
+                self.set = None;
+            }
+            (true, false) => self.set = Some(now),
+            _ => {}
+        }
+
+        if self.history.len() == SNR_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(snr.unwrap_or_default().into());
+        self.last_seen = Some(Instant::now());
+    }
+
+    /// Marks the satellite as no longer reported by its talker.
+    fn lost(&mut self, now: SystemTime) {
+        if self.is_up() {
+            self.set = Some(now);
+        }
+        self.elevation = None;
+        self.azimuth = None;
+        self.snr = None;
+    }
+
+    fn is_stale(&self, expiry: Duration) -> bool {
+        self.last_seen.is_none_or(|t| t.elapsed() >= expiry)
+    }
+}
+
+/// One (prn, elevation, azimuth, snr) block of a GSV sentence.
+type GsvBlock = (u32, Option<f32>, Option<f32>, Option<u8>);
+
+/// A GSV group that has not received all of its messages yet.
+#[derive(Debug)]
+struct PendingGroup {
+    total: u16,
+    received: u16,
+    blocks: Vec<GsvBlock>,
+}
+
+#[derive(Debug)]
+pub struct Satellites {
+    satellites: BTreeMap<SatelliteId, Satellite>,
+    /// Incomplete GSV groups keyed by talker and signal id.
+    pending: HashMap<(String, Option<String>), PendingGroup>,
+    expiry: Duration,
+}
+
+impl Satellites {
+    pub fn new(expiry: Duration) -> Satellites {
+        Satellites {
+            satellites: BTreeMap::new(),
+            pending: HashMap::new(),
+            expiry,
+        }
+    }
+
+    /// Applies a single GSV sentence. Satellites are only updated once every message of
+    /// the group (`message x of y`) has arrived, so the view never shows half a group.
+    pub fn update(&mut self, gsv: &Sentence) {
+        // Fields 0..3 are the message count, message number and satellites in view,
+        // followed by up to four (prn, elevation, azimuth, snr) blocks and an optional
+        // signal id.
+        let (Some(Ok(total)), Some(Ok(number))) = (
+            gsv.fields.first().map(|f| f.parse::<u16>()),
+            gsv.fields.get(1).map(|f| f.parse::<u16>()),
+        ) else {
+            return;
+        };
+        let blocks = gsv.fields.get(3..).unwrap_or_default().chunks_exact(4);
+        let signal = blocks
+            .remainder()
+            .first()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        let key = (gsv.talker.to_string(), signal);
+
+        let blocks = blocks.filter_map(|block| {
+            Some((
+                block[0].parse().ok()?,
+                block[1].parse().ok(),
+                block[2].parse().ok(),
+                block[3].parse().ok(),
+            ))
+        });
+
+        if number == 1 {
+            self.pending.insert(
+                key.clone(),
+                PendingGroup {
+                    total,
+                    received: 0,
+                    blocks: Vec::new(),
+                },
+            );
+        }
+        let Some(group) = self.pending.get_mut(&key) else {
+            return;
+        };
+        if group.total != total || group.received + 1 != number {
+            // Out of sequence: drop the group and wait for the next message 1.
+            self.pending.remove(&key);
+            return;
+        }
+        group.received = number;
+        group.blocks.extend(blocks);
+
+        if number == total {
+            if let Some(group) = self.pending.remove(&key) {
+                self.commit(&key, group.blocks);
+            }
+        }
+    }
+
+    fn commit(&mut self, (talker, signal): &(String, Option<String>), blocks: Vec<GsvBlock>) {
+        let now = SystemTime::now();
+
+        for (prn, elevation, azimuth, snr) in &blocks {
+            let id = SatelliteId {
+                talker: talker.to_string(),
+                prn: *prn,
+            };
+            let first_seen = !self.satellites.contains_key(&id);
+            let sat = self.satellites.entry(id).or_default();
+            sat.update(*elevation, *azimuth, *snr, first_seen, now);
+            sat.signal.clone_from(signal);
+        }
+
+        for (id, sat) in self.satellites.iter_mut() {
+            if &id.talker == talker
+                && &sat.signal == signal
+                && !blocks.iter().any(|b| b.0 == id.prn)
+            {
+                sat.lost(now);
+            }
+        }
+
+        let expiry = self.expiry;
+        self.satellites.retain(|_, sat| !sat.is_stale(expiry));
+    }
+
+    /// Satellites seen within the expiry time.
+    pub fn iter(&self) -> impl Iterator<Item = (&SatelliteId, &Satellite)> {
+        self.satellites
+            .iter()
+            .filter(|(_, sat)| !sat.is_stale(self.expiry))
+    }
+}
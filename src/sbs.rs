@@ -0,0 +1,55 @@
+//! ADS-B aircraft from the SBS-1 (BaseStation) CSV feed that dump1090 serves on port
+//! 30003, read from `sbs://host:port` sources.
+//!
+//! `MSG,3,1,1,4CA2D6,1,2024/05/01,12:00:00.000,2024/05/01,12:00:00.000,,35000,,,53.1,-2.2,,,0,0,0,0`
+//!
+//! Each message carries only some of the fields, so aircraft are merged by ICAO address.
+
+/// Meters per foot.
+const FOOT: f64 = 0.3048;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SbsMessage {
+    /// ICAO address in hex.
+    pub icao: String,
+    pub callsign: Option<String>,
+    /// Barometric altitude in meters.
+    pub altitude: Option<f64>,
+    /// Ground speed in knots.
+    pub ground_speed: Option<f64>,
+    pub track: Option<f64>,
+    pub position: Option<(f64, f64)>,
+    /// Climb rate in m/s.
+    pub climb_rate: Option<f64>,
+    pub squawk: Option<String>,
+}
+
+/// Parses a `MSG` line. Other message types (`SEL`, `ID`, `AIR`, `STA`, `CLK`) carry no
+/// aircraft state and are ignored.
+pub fn parse(line: &str) -> Option<SbsMessage> {
+    let fields = line.trim_end().split(',').collect::<Vec<_>>();
+    if fields.first() != Some(&"MSG") {
+        return None;
+    }
+    let text = |index: usize| {
+        fields
+            .get(index)
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+    };
+    let number = |index: usize| fields.get(index).and_then(|f| f.trim().parse::<f64>().ok());
+    let icao = text(4).filter(|icao| icao.len() == 6)?;
+    Some(SbsMessage {
+        icao,
+        callsign: text(10),
+        altitude: number(11).map(|feet| feet * FOOT),
+        ground_speed: number(12),
+        track: number(13),
+        position: number(14)
+            .zip(number(15))
+            .filter(|(lat, lon)| lat.abs() <= 90.0 && lon.abs() <= 180.0),
+        climb_rate: number(16).map(|feet_per_minute| feet_per_minute * FOOT / 60.0),
+        squawk: text(17),
+    })
+}
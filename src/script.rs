@@ -0,0 +1,192 @@
+//! Rhai scripts given with `--script`, for decoding devices without explicit support.
+//!
+//! A script defines `on_sentence(line, sentence)`, called for every line applied to the
+//! status. `sentence` is a map with `talker`, `kind` and `fields`, or `()` for lines that
+//! are not NMEA 0183 sentences. `this` is a map kept between calls for the script's own
+//! state. Scripts can call:
+//!
+//! - `set(name, value)` to show a value in the status grid,
+//! - `alert(text)` to raise an alert for a few seconds.
+//!
+//! ```rhai
+//! fn on_sentence(line, sentence) {
+//!     if sentence?.kind == "PQTMEPE" {
+//!         set("EPE", sentence.fields[3] + " m");
+//!         if parse_float(sentence.fields[3]) > 5.0 { alert("EPE above 5 m"); }
+//!     }
+//! }
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::{sentence::Sentence, status::StatusValue};
+
+/// Name of the function called for each line.
+const ENTRY_POINT: &str = "on_sentence";
+
+/// How long an alert raised by a script lasts without being raised again.
+const ALERT_DURATION: Duration = Duration::from_secs(10);
+
+/// Bound on the work a script does per line, so that a runaway loop cannot stall input.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What scripts set, shared with the functions registered in the engine.
+#[derive(Default, Debug)]
+struct Output {
+    values: Vec<(String, StatusValue<String>)>,
+    alerts: Vec<(String, Instant)>,
+}
+
+struct Script {
+    path: PathBuf,
+    ast: AST,
+    this: Dynamic,
+}
+
+pub struct Scripts {
+    engine: Engine,
+    scripts: Vec<Script>,
+    output: Arc<Mutex<Output>>,
+    /// Latest error, with the script it came from.
+    pub error: Option<String>,
+}
+
+impl std::fmt::Debug for Scripts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scripts")
+            .field(
+                "scripts",
+                &self.scripts.iter().map(|s| &s.path).collect::<Vec<_>>(),
+            )
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Scripts {
+    /// Values set by scripts expire after `timeout` like received values.
+    pub fn new(timeout: Duration) -> Scripts {
+        let output = Arc::new(Mutex::new(Output::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        {
+            let output = Arc::clone(&output);
+            engine.register_fn("set", move |name: &str, value: Dynamic| {
+                let mut output = output.lock().unwrap();
+                let index = match output.values.iter().position(|(n, _)| n == name) {
+                    Some(index) => index,
+                    None => {
+                        output
+                            .values
+                            .push((name.to_string(), StatusValue::new(timeout)));
+                        output.values.len() - 1
+                    }
+                };
+                output.values[index].1.update(value.to_string());
+            });
+        }
+        {
+            let output = Arc::clone(&output);
+            engine.register_fn("alert", move |text: &str| {
+                let mut output = output.lock().unwrap();
+                output
+                    .alerts
+                    .retain(|(alert, at)| alert != text && at.elapsed() < ALERT_DURATION);
+                output.alerts.push((text.to_string(), Instant::now()));
+            });
+        }
+        Scripts {
+            engine,
+            scripts: Vec::new(),
+            output,
+            error: None,
+        }
+    }
+
+    /// Compiles `path` and runs its top level once.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        let error = |e| anyhow!("{}: {e}", path.display());
+        let ast = self
+            .engine
+            .compile_file(path.to_path_buf())
+            .map_err(error)?;
+        if !ast
+            .iter_functions()
+            .any(|function| function.name == ENTRY_POINT)
+        {
+            bail!("{} defines no {ENTRY_POINT} function", path.display());
+        }
+        self.engine.run_ast(&ast).map_err(error)?;
+        self.scripts.push(Script {
+            path: path.to_path_buf(),
+            ast,
+            this: Map::new().into(),
+        });
+        Ok(())
+    }
+
+    pub fn on_line(&mut self, line: &str) {
+        if self.scripts.is_empty() {
+            return;
+        }
+        let sentence = Sentence::parse(line).map_or(Dynamic::UNIT, |sentence| {
+            let mut map = Map::new();
+            map.insert("talker".into(), sentence.talker.into());
+            map.insert("kind".into(), sentence.kind.into());
+            let fields = sentence.fields.iter().map(|&field| field.into());
+            map.insert("fields".into(), fields.collect::<Array>().into());
+            map.into()
+        });
+
+        for script in &mut self.scripts {
+            let options = CallFnOptions::new()
+                .eval_ast(false)
+                .bind_this_ptr(&mut script.this);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                ENTRY_POINT,
+                (line.to_string(), sentence.clone()),
+            );
+            if let Err(e) = result {
+                let error = format!("{}: {e}", script.path.display());
+                if self.error.as_ref() != Some(&error) {
+                    warn!("script failed: {error}");
+                }
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// Values set by scripts, in the order they were first set.
+    #[cfg(feature = "tui")]
+    pub fn values(&self) -> Vec<(String, Option<String>)> {
+        let output = self.output.lock().unwrap();
+        output
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.get().cloned()))
+            .collect()
+    }
+
+    /// Alerts raised recently.
+    pub fn alerts(&self) -> Vec<String> {
+        let output = self.output.lock().unwrap();
+        output
+            .alerts
+            .iter()
+            .filter(|(_, at)| at.elapsed() < ALERT_DURATION)
+            .map(|(text, _)| text.clone())
+            .collect()
+    }
+}
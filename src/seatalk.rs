@@ -0,0 +1,61 @@
+//! Raymarine SeaTalk1 datagrams encapsulated as `$STALK` sentences by SeaTalk/NMEA
+//! bridges, e.g. `$STALK,00,02,00,0A,00*XX`.
+//!
+//! Only the `$STALK` encapsulation is supported. The raw bus sends 9-bit bytes with the
+//! 9th bit marking the command byte, which a line-oriented serial source cannot see, so a
+//! SeaTalk1 bus must be read through such a bridge.
+
+use crate::sentence::Sentence;
+
+/// One foot in meters.
+const FOOT: f64 = 0.3048;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Datagram {
+    /// Depth below transducer in meters.
+    Depth(f64),
+    /// Apparent wind angle in degrees.
+    WindAngle(f64),
+    /// Apparent wind speed in knots.
+    WindSpeed(f64),
+    /// Speed through water in knots.
+    WaterSpeed(f64),
+}
+
+pub fn parse(stalk: &Sentence) -> Option<Datagram> {
+    let bytes = stalk
+        .fields
+        .iter()
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let word = |at: usize| -> Option<f64> {
+        Some(f64::from(u16::from_le_bytes([
+            *bytes.get(at)?,
+            *bytes.get(at + 1)?,
+        ])))
+    };
+
+    match bytes.first()? {
+        // 00 02 YZ XX XX: depth in 1/10 ft, LSB first
+        0x00 => Some(Datagram::Depth(word(3)? / 10.0 * FOOT)),
+        // 10 01 XX YY: angle in 1/2 degrees, MSB first
+        0x10 => Some(Datagram::WindAngle(
+            f64::from(u16::from_be_bytes([*bytes.get(2)?, *bytes.get(3)?])) / 2.0,
+        )),
+        // 11 01 XX 0Y: XX & 0x7F knots + Y/10, m/s when XX & 0x80
+        0x11 => {
+            let whole = bytes.get(2)?;
+            let speed = f64::from(whole & 0x7F) + f64::from(bytes.get(3)? & 0x0F) / 10.0;
+            Some(Datagram::WindSpeed(if whole & 0x80 != 0 {
+                speed / crate::geo::KNOT
+            } else {
+                speed
+            }))
+        }
+        // 20 01 XX XX: speed in 1/10 knots
+        0x20 => Some(Datagram::WaterSpeed(word(2)? / 10.0)),
+        // 26 04 XX XX YY YY DE: speed in 1/100 knots
+        0x26 => Some(Datagram::WaterSpeed(word(2)? / 100.0)),
+        _ => None,
+    }
+}
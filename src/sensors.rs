@@ -0,0 +1,98 @@
+//! Generic transducer readings from XDR sentences.
+
+use std::collections::{BTreeMap, HashMap};
+
+use tokio::time::Instant;
+
+use crate::sentence::Sentence;
+
+#[derive(Clone, Debug)]
+pub struct Reading {
+    /// Transducer type letter (`C` temperature, `P` pressure, `U` voltage, ...).
+    pub kind: String,
+    pub value: f64,
+    pub unit: String,
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub updated_at: Instant,
+}
+
+impl Reading {
+    #[cfg(feature = "tui")]
+    pub fn kind_name(&self) -> &'static str {
+        match self.kind.as_str() {
+            "A" => "angle",
+            "C" => "temperature",
+            "D" => "displacement",
+            "F" => "frequency",
+            "G" => "generic",
+            "H" => "humidity",
+            "I" => "current",
+            "N" => "force",
+            "P" => "pressure",
+            "R" => "flow rate",
+            "S" => "switch",
+            "T" => "tachometer",
+            "U" => "voltage",
+            "V" => "volume",
+            _ => "unknown",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Sensors {
+    readings: BTreeMap<String, Reading>,
+    /// User-facing names keyed by transducer id.
+    names: HashMap<String, String>,
+}
+
+impl Sensors {
+    pub fn new(names: HashMap<String, String>) -> Sensors {
+        Sensors {
+            readings: BTreeMap::new(),
+            names,
+        }
+    }
+
+    /// Replaces the names from a reloaded config.
+    pub fn set_names(&mut self, names: HashMap<String, String>) {
+        self.names = names;
+    }
+
+    /// Applies an XDR sentence, which carries any number of
+    /// (type, value, unit, transducer id) quadruplets.
+    pub fn update(&mut self, xdr: &Sentence) {
+        for (index, quad) in xdr.fields.chunks_exact(4).enumerate() {
+            let Ok(value) = quad[1].parse() else {
+                continue;
+            };
+            let id = if quad[3].is_empty() {
+                format!("{}{index}", quad[0])
+            } else {
+                quad[3].to_string()
+            };
+            self.readings.insert(
+                id,
+                Reading {
+                    kind: quad[0].to_string(),
+                    value,
+                    unit: quad[2].to_string(),
+                    updated_at: Instant::now(),
+                },
+            );
+        }
+    }
+
+    /// Configured name of a transducer, falling back to its id.
+    pub fn name<'a>(&'a self, id: &'a str) -> &'a str {
+        self.names.get(id).map_or(id, String::as_str)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Reading> {
+        self.readings.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Reading)> {
+        self.readings.iter()
+    }
+}
@@ -0,0 +1,73 @@
+//! Raw NMEA 0183 sentence framing.
+//!
+//! The `nmea` crate only hands out decoded data for the sentences it knows, and hides
+//! details such as the talker id. Sentences that need those details are decoded from
+//! the raw comma-separated fields instead.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sentence<'a> {
+    /// Two-letter talker id (`GP`, `GN`, ...). Empty for proprietary and `STALK` sentences.
+    pub talker: &'a str,
+    /// Sentence formatter (`GSV`, `RMC`, ...), or the full address for proprietary sentences
+    /// (`PGRME`) and SeaTalk encapsulation (`STALK`).
+    pub kind: &'a str,
+    pub fields: Vec<&'a str>,
+}
+
+/// Why a line is not a sentence.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rejection {
+    /// No `$`/`!` start delimiter, or no valid address.
+    Framing,
+    /// The checksum is malformed or does not match.
+    Checksum,
+}
+
+impl<'a> Sentence<'a> {
+    /// Splits a `$`/`!` delimited sentence into its address and fields, rejecting it when a
+    /// checksum is present and does not match.
+    pub fn parse(line: &'a str) -> Option<Sentence<'a>> {
+        Self::check(line).ok()
+    }
+
+    /// Like [`Sentence::parse`], telling why the line was rejected.
+    pub fn check(line: &'a str) -> Result<Sentence<'a>, Rejection> {
+        let line = line.trim_end();
+        let body = line
+            .strip_prefix('$')
+            .or_else(|| line.strip_prefix('!'))
+            .ok_or(Rejection::Framing)?;
+        let body = match body.split_once('*') {
+            Some((body, checksum)) => {
+                match u8::from_str_radix(checksum, 16) {
+                    Ok(checksum) if checksum == self::checksum(body) => {}
+                    _ => return Err(Rejection::Checksum),
+                }
+                body
+            }
+            None => body,
+        };
+
+        let mut fields = body.split(',');
+        let address = fields.next().ok_or(Rejection::Framing)?;
+        if !address.is_ascii() || address.len() < 3 {
+            return Err(Rejection::Framing);
+        }
+        let (talker, kind) = if address.starts_with('P') || address == "STALK" {
+            ("", address)
+        } else {
+            address.split_at(2)
+        };
+
+        Ok(Sentence {
+            talker,
+            kind,
+            fields: fields.collect(),
+        })
+    }
+}
+
+/// XOR of every byte between the start delimiter and `*`.
+pub fn checksum(body: &str) -> u8 {
+    body.bytes().fold(0, |acc, b| acc ^ b)
+}
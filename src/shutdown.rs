@@ -0,0 +1,134 @@
+//! Orderly exit on SIGINT, SIGTERM and SIGHUP, and after a panic in any task, so that
+//! the terminal is restored, recordings are synced and exports are written before the
+//! process ends.
+//!
+//! A panicking task only ends itself, so the panic hook asks the main task to shut down
+//! instead of leaving the UI running without it.
+//!
+//! Headless with a config file, SIGHUP reloads the config instead. See [`crate::reload`].
+
+use std::{
+    fmt::Display,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+use tracing::error;
+
+use crate::{
+    geo,
+    history::{Series, Window},
+    status::NmeaStatus,
+};
+
+/// Why the program is exiting early.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reason {
+    Signal(&'static str, i32),
+    Panic,
+}
+
+impl Reason {
+    /// Exit code: 128 plus the signal number, as shells report it, or 101 like an
+    /// unhandled panic.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Signal(_, number) => 128 + number,
+            Self::Panic => 101,
+        }
+    }
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Signal(name, _) => write!(f, "stopped by {name}"),
+            Self::Panic => f.write_str("stopped after a panic"),
+        }
+    }
+}
+
+/// Whether SIGHUP reloads the config rather than stopping.
+static RELOAD_ON_HANGUP: AtomicBool = AtomicBool::new(false);
+
+fn panicked() -> &'static Notify {
+    static PANICKED: OnceLock<Notify> = OnceLock::new();
+    PANICKED.get_or_init(Notify::new)
+}
+
+/// Logs panics and has [`requested`] return for them. Install before the terminal is
+/// set up, so that its hook restores the terminal before this one runs.
+pub fn install(reload_on_hangup: bool) {
+    RELOAD_ON_HANGUP.store(reload_on_hangup, Ordering::Relaxed);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        error!("{info}");
+        previous(info);
+        panicked().notify_one();
+    }));
+}
+
+/// Whether SIGHUP reloads the config rather than stopping.
+pub fn reload_on_hangup() -> bool {
+    RELOAD_ON_HANGUP.load(Ordering::Relaxed)
+}
+
+/// Waits for a signal to stop or a panic.
+pub async fn requested() -> Reason {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate()).expect("Failed to handle SIGTERM.");
+        let mut hangup = signal(SignalKind::hangup()).expect("Failed to handle SIGHUP.");
+        let reload_on_hangup = reload_on_hangup();
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => return Reason::Signal("SIGINT", 2),
+                _ = terminate.recv() => return Reason::Signal("SIGTERM", 15),
+                _ = hangup.recv() => if !reload_on_hangup {
+                    return Reason::Signal("SIGHUP", 1);
+                },
+                _ = panicked().notified() => return Reason::Panic,
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => Reason::Signal("SIGINT", 2),
+        _ = panicked().notified() => Reason::Panic,
+    }
+}
+
+/// Waits like [`requested`], for modes that signals end normally. Returns a reason only
+/// for a panic.
+pub async fn stopped() -> Option<Reason> {
+    match requested().await {
+        Reason::Panic => Some(Reason::Panic),
+        Reason::Signal(..) => None,
+    }
+}
+
+/// One line about the session, printed when it ends early.
+pub fn summary(nmea: &NmeaStatus, recording: Option<&Path>) -> String {
+    let session = |series: &Series| series.summary(Window::Session);
+    let lines = nmea.sources.iter().map(|source| source.lines).sum::<u64>();
+    let mut summary = format!(
+        "ran {}, {lines} lines",
+        humantime::format_duration(Duration::from_secs(nmea.diagnostics.uptime().as_secs()))
+    );
+    if let Some(travelled) = session(&nmea.history.travelled) {
+        summary += &format!(", {:.2} NM", travelled.max / geo::NAUTICAL_MILE);
+    }
+    if let Some(sog) = session(&nmea.history.sog) {
+        summary += &format!(", max {:.1} kn", sog.max);
+    }
+    if let Some(path) = recording {
+        summary += &format!(", recorded to {}", path.display());
+    }
+    summary
+}
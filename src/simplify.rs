@@ -0,0 +1,106 @@
+//! Douglas–Peucker line simplification, for exports that do not need every fix.
+
+use crate::geo;
+
+/// Keeps the points of `points` (`(lat, lon)`) needed to stay within `tolerance` meters
+/// of the original line. The first and last points are always kept.
+pub fn simplify(points: &[(f64, f64)], tolerance: f64) -> Vec<(f64, f64)> {
+    if points.len() < 3 || tolerance <= 0.0 {
+        return points.to_vec();
+    }
+    let origin = points[0];
+    let xy = points
+        .iter()
+        .map(|&point| geo::local_xy(origin, point))
+        .collect::<Vec<_>>();
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    // Ranges still to split, instead of recursion that long tracks would overflow.
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let farthest = (first + 1..last)
+            .map(|i| (i, segment_distance(xy[i], xy[first], xy[last])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, distance)) = farthest {
+            if distance > tolerance {
+                keep[i] = true;
+                ranges.push((first, i));
+                ranges.push((i, last));
+            }
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(&point, keep)| keep.then_some(point))
+        .collect()
+}
+
+/// Distance from `p` to the segment from `a` to `b`.
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+    let (x, y) = (a.0 + t * dx, a.1 + t * dy);
+    ((p.0 - x).powi(2) + (p.1 - y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collinear_points_are_dropped() {
+        let line = (0..10)
+            .map(|i| (0.0, f64::from(i) / 1000.0))
+            .collect::<Vec<_>>();
+        assert_eq!(simplify(&line, 1.0), [line[0], line[9]]);
+    }
+
+    #[test]
+    fn zero_tolerance_keeps_everything() {
+        let line = (0..10)
+            .map(|i| (0.0, f64::from(i) / 1000.0))
+            .collect::<Vec<_>>();
+        assert_eq!(simplify(&line, 0.0), line);
+    }
+
+    #[test]
+    fn corner_is_kept() {
+        // About 111 m east then 111 m north.
+        let corner = [
+            (0.0, 0.0),
+            (0.0, 0.0005),
+            (0.0, 0.001),
+            (0.0005, 0.001),
+            (0.001, 0.001),
+        ];
+        assert_eq!(
+            simplify(&corner, 10.0),
+            [(0.0, 0.0), (0.0, 0.001), (0.001, 0.001)]
+        );
+    }
+
+    #[test]
+    fn first_and_last_points_are_kept() {
+        // A loop back to the start, where the ends are nearest each other.
+        let track = [
+            (0.0, 0.0),
+            (0.0, 0.001),
+            (0.001, 0.001),
+            (0.001, 0.0),
+            (0.0, 0.00001),
+        ];
+        let simplified = simplify(&track, 1000.0);
+        assert_eq!(simplified.first(), track.first());
+        assert_eq!(simplified.last(), track.last());
+        assert_eq!(simplify(&track[..2], 1000.0), track[..2]);
+    }
+}
@@ -0,0 +1,610 @@
+//! Input sources and the layer merging their lines into one stream.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use nmea::ParseResult;
+#[cfg(feature = "net")]
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncRead, AsyncWrite, BufReader},
+    sync::{mpsc::Sender, RwLock},
+    time::Instant,
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    extended,
+    gns::Gns,
+    input,
+    n2k::{self, N2kMessage},
+    replay,
+    sentence::Sentence,
+    status::{self, NmeaStatus},
+};
+
+/// A line of text received from the source at `source` index.
+pub type Received = (usize, String);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+    /// `tcp://host:port`
+    Tcp(String),
+    /// `udp://bind-address:port`
+    Udp(String),
+    /// `sbs://host:port`, an ADS-B receiver's BaseStation feed.
+    Sbs(String),
+    /// `unix:///path`, such as the socket of a daemon.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for Source {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(unix)]
+        if let Some(path) = s.strip_prefix("unix://") {
+            return Ok(Self::Unix(path.into()));
+        }
+        Ok(if s == "-" {
+            Self::Stdin
+        } else if let Some(address) = s.strip_prefix("tcp://") {
+            Self::Tcp(address.to_string())
+        } else if let Some(address) = s.strip_prefix("udp://") {
+            Self::Udp(address.to_string())
+        } else if let Some(address) = s.strip_prefix("sbs://") {
+            Self::Sbs(address.to_string())
+        } else {
+            Self::File(s.into())
+        })
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdin => f.write_str("stdin"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Tcp(address) => write!(f, "tcp://{address}"),
+            Self::Udp(address) => write!(f, "udp://{address}"),
+            Self::Sbs(address) => write!(f, "sbs://{address}"),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+impl Source {
+    /// Opens the source and spawns a task sending its lines to `lines`. Returns the write
+    /// half when `writable` is set and the source supports writing.
+    ///
+    /// Failures are recorded in the source's status as well as returned.
+    pub async fn spawn(
+        &self,
+        index: usize,
+        writable: bool,
+        lines: Sender<Received>,
+        nmea: Arc<RwLock<NmeaStatus>>,
+    ) -> Result<Option<Box<dyn AsyncWrite + Unpin + Send>>> {
+        let result = self.open(index, writable, lines, Arc::clone(&nmea)).await;
+        if let Err(e) = &result {
+            set_state(&nmea, index, SourceState::Failed(e.to_string())).await;
+        }
+        result
+    }
+
+    async fn open(
+        &self,
+        index: usize,
+        writable: bool,
+        lines: Sender<Received>,
+        nmea: Arc<RwLock<NmeaStatus>>,
+    ) -> Result<Option<Box<dyn AsyncWrite + Unpin + Send>>> {
+        let (reader, writer): (
+            Box<dyn AsyncRead + Unpin + Send>,
+            Option<Box<dyn AsyncWrite + Unpin + Send>>,
+        ) = match self {
+            Self::Stdin => (Box::new(tokio::io::stdin()), None),
+            Self::File(path) if writable => {
+                let file = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(device_path(path))
+                    .await?;
+                let (read, write) = tokio::io::split(file);
+                (Box::new(read), Some(Box::new(write)))
+            }
+            Self::File(path) => {
+                let file = File::open(device_path(path)).await?;
+                let replayed = nmea
+                    .read()
+                    .await
+                    .replay
+                    .as_ref()
+                    .is_some_and(|replay| replay.source == index);
+                if replayed {
+                    set_state(&nmea, index, SourceState::Connected).await;
+                    tokio::spawn(replay::play(index, file, lines, nmea));
+                    return Ok(None);
+                }
+                (Box::new(file), None)
+            }
+            #[cfg(feature = "net")]
+            Self::Tcp(address) => {
+                let stream = TcpStream::connect(address).await?;
+                let (read, write) = stream.into_split();
+                (Box::new(read), writable.then(|| Box::new(write) as _))
+            }
+            // Nothing is sent to an ADS-B receiver, so it is never the writer.
+            #[cfg(feature = "net")]
+            Self::Sbs(address) => (Box::new(TcpStream::connect(address).await?), None),
+            #[cfg(unix)]
+            Self::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                let (read, write) = stream.into_split();
+                (Box::new(read), writable.then(|| Box::new(write) as _))
+            }
+            #[cfg(feature = "net")]
+            Self::Udp(address) => {
+                let socket = UdpSocket::bind(address).await?;
+                set_state(&nmea, index, SourceState::Connected).await;
+                tokio::spawn(read_datagrams(index, socket, lines, nmea));
+                return Ok(None);
+            }
+            #[cfg(not(feature = "net"))]
+            Self::Tcp(_) | Self::Udp(_) | Self::Sbs(_) => {
+                anyhow::bail!("{self}: built without network sources")
+            }
+        };
+
+        set_state(&nmea, index, SourceState::Connected).await;
+        tokio::spawn(read_lines(index, reader, lines, nmea));
+        Ok(writer)
+    }
+}
+
+/// Path to open for a file source. Windows opens serial ports by their plain names only
+/// up to `COM9`, and `\\.\COM10` style device paths for all of them. The port keeps the
+/// settings made with `mode`, such as `mode COM3 BAUD=4800`.
+#[cfg(windows)]
+fn device_path(path: &Path) -> Cow<'_, Path> {
+    match port_device(&path.to_string_lossy()) {
+        Some(device) => Cow::Owned(device),
+        None => Cow::Borrowed(path),
+    }
+}
+
+#[cfg(not(windows))]
+fn device_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// The Windows device path of a `COMn` port name, case-insensitively.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn port_device(name: &str) -> Option<PathBuf> {
+    let number = name.get(3..)?;
+    let is_port = name[..3].eq_ignore_ascii_case("COM")
+        && !number.is_empty()
+        && number.bytes().all(|b| b.is_ascii_digit());
+    is_port.then(|| PathBuf::from(format!(r"\\.\{name}")))
+}
+
+async fn read_lines(
+    index: usize,
+    reader: Box<dyn AsyncRead + Unpin + Send>,
+    lines: Sender<Received>,
+    nmea: Arc<RwLock<NmeaStatus>>,
+) {
+    let mut reader = BufReader::with_capacity(128, reader);
+    let mut buf = Vec::new();
+    let mut attribution = Attribution::default();
+    let state = loop {
+        buf.clear();
+        match input::read_line(&mut reader, &mut buf).await {
+            Ok(0) => break SourceState::Closed,
+            Err(e) => break SourceState::Failed(e.to_string()),
+            Ok(_) => {}
+        }
+        let (line, dropped) = input::extract_text(&buf);
+        if dropped > 0 {
+            nmea.write().await.dropped_bytes += dropped as u64;
+        }
+        let (source, line) = attribution.attribute(index, line, &nmea).await;
+        if !line.is_empty() && lines.send((source, line.to_string())).await.is_err() {
+            return;
+        }
+    };
+    attribution.set_state(&nmea, index, state).await;
+}
+
+/// Gives the lines of an extended log a source per recorded source index, added to the
+/// status when it first appears. Other lines stay with the source they were read from.
+#[derive(Default, Debug)]
+pub struct Attribution {
+    /// Status index by recorded index.
+    indices: HashMap<usize, usize>,
+}
+
+impl Attribution {
+    pub async fn attribute<'a>(
+        &mut self,
+        index: usize,
+        line: &'a str,
+        nmea: &RwLock<NmeaStatus>,
+    ) -> (usize, &'a str) {
+        let Some((_, recorded, line)) = extended::parse(line) else {
+            return (index, line);
+        };
+        if let Some(&source) = self.indices.get(&recorded) {
+            return (source, line);
+        }
+        let mut nmea = nmea.write().await;
+        let name = nmea
+            .sources
+            .get(index)
+            .map_or_else(String::new, |source| source.name.clone());
+        let mut status = SourceStatus::new(format!("{name} #{recorded}"));
+        status.state = SourceState::Connected;
+        nmea.sources.push(status);
+        let source = nmea.sources.len() - 1;
+        self.indices.insert(recorded, source);
+        (source, line)
+    }
+
+    /// Sets the state of the source at `index` and of the sources recorded in it.
+    pub async fn set_state(&self, nmea: &RwLock<NmeaStatus>, index: usize, state: SourceState) {
+        for &source in self.indices.values() {
+            set_state(nmea, source, state.clone()).await;
+        }
+        set_state(nmea, index, state).await;
+    }
+}
+
+/// UDP broadcasters put one or more complete lines in each datagram.
+#[cfg(feature = "net")]
+async fn read_datagrams(
+    index: usize,
+    socket: UdpSocket,
+    lines: Sender<Received>,
+    nmea: Arc<RwLock<NmeaStatus>>,
+) {
+    let mut buf = vec![0; 65536];
+    let error = loop {
+        let len = match socket.recv(&mut buf).await {
+            Ok(len) => len,
+            Err(e) => break e,
+        };
+        for raw in buf[..len].split(|&b| b == b'\n') {
+            let (line, dropped) = input::extract_text(raw);
+            if dropped > 0 {
+                nmea.write().await.dropped_bytes += dropped as u64;
+            }
+            if !line.is_empty() && lines.send((index, line.to_string())).await.is_err() {
+                return;
+            }
+        }
+    };
+    set_state(&nmea, index, SourceState::Failed(error.to_string())).await;
+}
+
+pub async fn set_state(nmea: &RwLock<NmeaStatus>, index: usize, state: SourceState) {
+    if let Some(source) = nmea.write().await.sources.get_mut(index) {
+        match &state {
+            SourceState::Failed(error) => {
+                source.errors += 1;
+                warn!(source = source.name, error, "source failed");
+            }
+            state => info!(source = source.name, %state, "source state changed"),
+        }
+        source.state = state;
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SourceState {
+    Connecting,
+    Connected,
+    /// End of file or connection closed by the peer.
+    Closed,
+    Failed(String),
+}
+
+impl Display for SourceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connecting => f.write_str("connecting"),
+            Self::Connected => f.write_str("connected"),
+            Self::Closed => f.write_str("closed"),
+            Self::Failed(error) => write!(f, "failed: {error}"),
+        }
+    }
+}
+
+/// Window over which the sentence rate is measured.
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug)]
+pub struct SourceStatus {
+    pub name: String,
+    pub state: SourceState,
+    /// Lines received in total.
+    pub lines: u64,
+    /// Arrival times of lines within the rate window.
+    arrivals: VecDeque<Instant>,
+    /// Sentences with bad framing or checksum, and read errors.
+    pub errors: u64,
+    /// When the source last reported a position.
+    pub position_at: Option<Instant>,
+    /// Latest latitude and longitude reported by this source.
+    pub last_position: Option<(f64, f64)>,
+    /// Latest positions by talker, `n2k` for NMEA 2000, newest last, so that talkers
+    /// disagreeing about the position show up.
+    pub talkers: BTreeMap<String, VecDeque<TalkerFix>>,
+}
+
+/// Fixes kept by talker, enough to find one of the same second from another talker
+/// that is a little behind.
+const TALKER_FIXES: usize = 4;
+
+/// How close the arrivals of two fixes without a time must be to count as the same fix.
+const SAME_FIX: Duration = Duration::from_millis(250);
+
+/// A position reported by one talker.
+#[derive(Clone, Copy, Debug)]
+pub struct TalkerFix {
+    pub position: (f64, f64),
+    /// UTC time of day of the fix, from GGA and GNS.
+    pub time: Option<(i8, i8, f64)>,
+    pub at: Instant,
+}
+
+impl TalkerFix {
+    /// Whether `self` and `other` are the same fix: they have the same UTC time or, where
+    /// one has none, arrived together.
+    fn same_fix(&self, other: &TalkerFix) -> bool {
+        match (self.time, other.time) {
+            (Some(time), Some(other)) => time == other,
+            _ => self.at.max(other.at) - self.at.min(other.at) < SAME_FIX,
+        }
+    }
+}
+
+/// The positions two talkers reported for the newest fix they both have.
+pub fn same_fix(
+    a: &VecDeque<TalkerFix>,
+    b: &VecDeque<TalkerFix>,
+) -> Option<((f64, f64), (f64, f64))> {
+    a.iter().rev().find_map(|fix| {
+        let other = b.iter().rev().find(|other| fix.same_fix(other))?;
+        Some((fix.position, other.position))
+    })
+}
+
+impl SourceStatus {
+    pub fn new(name: String) -> SourceStatus {
+        SourceStatus {
+            name,
+            state: SourceState::Connecting,
+            lines: 0,
+            arrivals: VecDeque::new(),
+            errors: 0,
+            position_at: None,
+            last_position: None,
+            talkers: BTreeMap::new(),
+        }
+    }
+
+    /// Records a line from this source. Returns whether it carries position.
+    pub fn update(&mut self, line: &str) -> bool {
+        let now = Instant::now();
+        self.lines += 1;
+        self.arrivals.push_back(now);
+        while self
+            .arrivals
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > RATE_WINDOW)
+        {
+            self.arrivals.pop_front();
+        }
+
+        if line.starts_with(['$', '!']) && Sentence::parse(line).is_none() {
+            self.errors += 1;
+            debug!(source = self.name, line, "malformed sentence");
+            return false;
+        }
+
+        let Some(position) = position(line) else {
+            return false;
+        };
+        // A source that keeps talking without a fix is not alive for failover.
+        if let Some(position) = position {
+            self.position_at = Some(now);
+            self.last_position = Some(position);
+            let sentence = Sentence::parse(line);
+            let talker = sentence.as_ref().map_or("n2k", |sentence| sentence.talker);
+            let fixes = self.talkers.entry(talker.to_string()).or_default();
+            if fixes.len() == TALKER_FIXES {
+                fixes.pop_front();
+            }
+            fixes.push_back(TalkerFix {
+                position,
+                time: sentence.and_then(|sentence| status::time_of_day(sentence.fields.first()?)),
+                at: now,
+            });
+        }
+        true
+    }
+
+    /// When the last line arrived.
+    pub fn last_line_at(&self) -> Option<Instant> {
+        self.arrivals.back().copied()
+    }
+
+    /// Lines per second over the last few seconds.
+    pub fn rate(&self) -> f64 {
+        let recent = self
+            .arrivals
+            .iter()
+            .filter(|at| at.elapsed() <= RATE_WINDOW)
+            .count();
+        recent as f64 / RATE_WINDOW.as_secs_f64()
+    }
+}
+
+/// For lines that drive the position fields, which only the active source may update,
+/// returns the latitude and longitude if the line has them.
+pub fn position(line: &str) -> Option<Option<(f64, f64)>> {
+    if let Some(sentence) = Sentence::parse(line) {
+        if sentence.kind == "GNS" {
+            let gns = Gns::parse(&sentence)?;
+            return Some(gns.lat.zip(gns.lon));
+        }
+        return match nmea::parse_str(line) {
+            Ok(ParseResult::GGA(gga)) => Some(gga.latitude.zip(gga.longitude)),
+            _ if matches!(sentence.kind, "GGA" | "RMC" | "VTG") => Some(None),
+            _ => None,
+        };
+    }
+    match n2k::parse(line)? {
+        N2kMessage::Position { lat, lon } => Some(Some((lat, lon))),
+        N2kMessage::CogSog { .. } => Some(None),
+        _ => None,
+    }
+}
+
+/// Drops sentences that another source delivered within a short window, for when one
+/// receiver is reachable through several sources.
+#[derive(Debug)]
+pub struct Dedup {
+    window: Duration,
+    /// Source and arrival time of recent lines.
+    seen: HashMap<String, (usize, Instant)>,
+    order: VecDeque<String>,
+}
+
+impl Dedup {
+    pub fn new(window: Duration) -> Dedup {
+        Dedup {
+            window,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Records `line` and returns whether it duplicates a line from another source.
+    pub fn is_duplicate(&mut self, source: usize, line: &str) -> bool {
+        while let Some(oldest) = self.order.front() {
+            if self.seen[oldest].1.elapsed() < self.window {
+                break;
+            }
+            self.seen.remove(oldest);
+            self.order.pop_front();
+        }
+
+        match self.seen.get(line) {
+            Some(&(seen_from, _)) => seen_from != source,
+            None => {
+                self.seen.insert(line.to_string(), (source, Instant::now()));
+                self.order.push_back(line.to_string());
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn com_ports_get_device_paths() {
+        assert_eq!(port_device("COM1"), Some(PathBuf::from(r"\\.\COM1")));
+        assert_eq!(port_device("COM10"), Some(PathBuf::from(r"\\.\COM10")));
+        assert_eq!(port_device("com3"), Some(PathBuf::from(r"\\.\com3")));
+    }
+
+    #[test]
+    fn other_names_pass_through() {
+        for name in [
+            "COM",
+            "COMX",
+            "COM1.log",
+            "/dev/ttyUSB0",
+            "log.nmea",
+            "CO",
+            "",
+        ] {
+            assert_eq!(port_device(name), None, "{name}");
+        }
+    }
+
+    fn fixes(fixes: &[((f64, f64), Option<f64>, u64)]) -> VecDeque<TalkerFix> {
+        let start = Instant::now();
+        fixes
+            .iter()
+            .map(|&(position, second, millis)| TalkerFix {
+                position,
+                time: second.map(|second| (12, 0, second)),
+                at: start + Duration::from_millis(millis),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn talkers_are_compared_at_the_same_time() {
+        // GP is a second ahead of GL, and moving.
+        let gp = fixes(&[((0.0, 0.0), Some(1.0), 0), ((0.0, 0.001), Some(2.0), 1000)]);
+        let gl = fixes(&[((0.0, 0.0), Some(1.0), 900)]);
+        assert_eq!(same_fix(&gp, &gl), Some(((0.0, 0.0), (0.0, 0.0))));
+        let gl = fixes(&[((0.0, 0.0), Some(3.0), 900)]);
+        assert_eq!(same_fix(&gp, &gl), None);
+    }
+
+    #[test]
+    fn fixes_without_time_are_compared_by_arrival() {
+        let gp = fixes(&[((0.0, 0.0), Some(1.0), 0), ((0.0, 0.001), Some(2.0), 1000)]);
+        let n2k = fixes(&[((0.0, 0.002), None, 1100)]);
+        assert_eq!(same_fix(&gp, &n2k), Some(((0.0, 0.001), (0.0, 0.002))));
+        let n2k = fixes(&[((0.0, 0.002), None, 1500)]);
+        assert_eq!(same_fix(&gp, &n2k), None);
+    }
+
+    /// A reader that fails like an unplugged serial adapter.
+    struct Unplugged;
+
+    impl AsyncRead for Unplugged {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+            _: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Err(std::io::Error::other("device unplugged")))
+        }
+    }
+
+    #[tokio::test]
+    async fn read_errors_fail_the_source() {
+        let args = <crate::Args as clap::Parser>::parse_from(["nmea-monitor"]);
+        let nmea = Arc::new(RwLock::new(NmeaStatus::new(&args, &Default::default())));
+        let (lines, mut received) = tokio::sync::mpsc::channel(1);
+        read_lines(0, Box::new(Unplugged), lines, nmea.clone()).await;
+
+        assert!(received.recv().await.is_none());
+        let nmea = nmea.read().await;
+        let source = &nmea.sources[0];
+        assert_eq!(source.state, SourceState::Failed("device unplugged".into()));
+        assert_eq!(source.errors, 1);
+    }
+}
@@ -0,0 +1,169 @@
+//! `stats`: a summary of a log without the TUI, from the same history the statistics
+//! screen keeps.
+//!
+//! The history is sampled once per second of receiver time, taken from RMC and ZDA, so
+//! that the log is summarized as it would have been live. The status times everything
+//! with the tokio clock, which is paused here and moved on by the receiver time instead,
+//! so that values time out and the boat counts as stationary as they did live.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::BufReader,
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+
+use crate::{
+    config::Config,
+    extended, geo,
+    history::{Series, Window},
+    input, raw_log, replay,
+    status::NmeaStatus,
+    Args,
+};
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// What is taken from the status once per receiver second.
+#[derive(Default)]
+struct Samples {
+    fix_types: BTreeMap<&'static str, u64>,
+    /// South-west and north-east corners.
+    bounds: Option<((f64, f64), (f64, f64))>,
+}
+
+impl Samples {
+    fn sample(&mut self, nmea: &mut NmeaStatus) {
+        nmea.tick();
+        if let Some(&fix_type) = nmea.fix_type.get() {
+            *self.fix_types.entry(fix_type).or_default() += 1;
+        }
+        if let Some(position) = nmea.position().filter(|position| !position.estimated) {
+            let (lat, lon) = (position.lat, position.lon);
+            self.bounds = Some(match self.bounds {
+                Some(((south, west), (north, east))) => (
+                    (south.min(lat), west.min(lon)),
+                    (north.max(lat), east.max(lon)),
+                ),
+                None => ((lat, lon), (lat, lon)),
+            });
+        }
+    }
+}
+
+pub fn run(args: &Args, config: &Config, log: &Path) -> Result<()> {
+    // The clock can only be paused on a runtime of its own, off the thread of the main one.
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_time()
+                    .start_paused(true)
+                    .build()?
+                    .block_on(summarize(args, config, log))
+            })
+            .join()
+            .expect("stats thread panicked")
+    })
+}
+
+async fn summarize(args: &Args, config: &Config, log: &Path) -> Result<()> {
+    let file = File::open(log).with_context(|| format!("Failed to open {}", log.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut nmea = NmeaStatus::new(args, config);
+
+    let mut counts = HashMap::<String, u64>::new();
+    let mut samples = Samples::default();
+    let mut time: Option<f64> = None;
+    let mut duration = 0.0;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if input::read_line_blocking(&mut reader, &mut buf)? == 0 {
+            break;
+        }
+        let (line, dropped) = input::extract_text(&buf);
+        let line = extended::strip(line);
+        nmea.dropped_bytes += dropped as u64;
+
+        // A new receiver second: the last one is sampled as the live tick would, after
+        // all its lines, then the clock moves on.
+        if let Some(now) = replay::time_of_day(line).filter(|&now| time != Some(now)) {
+            if let Some(previous) = time {
+                samples.sample(&mut nmea);
+                let gap = (now - previous).rem_euclid(SECONDS_PER_DAY);
+                duration += gap;
+                tokio::time::advance(Duration::from_secs_f64(gap)).await;
+            }
+            time = Some(now);
+        }
+
+        if !line.is_empty() {
+            let address = raw_log::address(line).unwrap_or_else(|| "(other)".to_string());
+            *counts.entry(address).or_default() += 1;
+            if nmea.receive(0, line) {
+                nmea.handle_line(line);
+            }
+        }
+    }
+    if time.is_some() {
+        samples.sample(&mut nmea);
+    }
+    let Samples { fix_types, bounds } = samples;
+
+    let history = &nmea.history;
+    let session = |series: &Series| series.summary(Window::Session);
+    println!(
+        "duration      {}",
+        humantime::format_duration(Duration::from_secs(duration as u64))
+    );
+    println!(
+        "distance      {}",
+        session(&history.travelled).map_or_else(
+            || "-".to_string(),
+            |travelled| format!("{:.2} NM", travelled.max / geo::NAUTICAL_MILE)
+        )
+    );
+    println!(
+        "sog           {}",
+        session(&history.sog).map_or_else(
+            || "-".to_string(),
+            |sog| format!("max {:.1} kn, mean {:.1} kn", sog.max, sog.mean())
+        )
+    );
+    println!(
+        "bounding box  {}",
+        bounds.map_or_else(
+            || "-".to_string(),
+            |((south, west), (north, east))| format!(
+                "{} to {}",
+                nmea.coordinates.position(south, west),
+                nmea.coordinates.position(north, east)
+            )
+        )
+    );
+
+    let samples = fix_types.values().sum::<u64>().max(1);
+    let mut fix_types = fix_types.into_iter().collect::<Vec<_>>();
+    fix_types.sort_by_key(|&(_, count)| Reverse(count));
+    println!("fix types");
+    for (fix_type, count) in fix_types {
+        println!(
+            "{:>10} s {fix_type} ({:.0}%)",
+            count,
+            count as f64 / samples as f64 * 100.0
+        );
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    println!("sentences");
+    for (address, count) in counts {
+        println!("{count:>10} {address}");
+    }
+    Ok(())
+}
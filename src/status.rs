@@ -0,0 +1,1108 @@
+use std::time::{Duration, SystemTime};
+
+use nmea::{sentences::FixType, ParseResult};
+#[cfg(feature = "tui")]
+use ratatui::text::Text;
+use tokio::time::Instant;
+use tracing::debug;
+
+#[cfg(feature = "ais")]
+use crate::ais::{AisTargets, OwnShip};
+#[cfg(feature = "net")]
+use crate::aprs::AprsStatus;
+#[cfg(feature = "extract")]
+use crate::extract::Extracts;
+#[cfg(feature = "script")]
+use crate::script::Scripts;
+use crate::{
+    accuracy::Accuracy,
+    alarm::Alarms,
+    almanac::Almanac,
+    autopilot::Autopilot,
+    barometer::Barometer,
+    battery::Batteries,
+    clock,
+    config::Config,
+    datum::Datum,
+    depth::Keel,
+    device::DeviceMessages,
+    diagnostics::Diagnostics,
+    dsc::DscCalls,
+    filter::{Smoothing, VelocityFilter, VerticalSpeed},
+    format::{Coordinates, VerticalSpeedUnit},
+    geo,
+    gns::Gns,
+    gps_time::{self, TimeCheck},
+    history::{History, Sample},
+    integrity::Integrity,
+    kalman::Kalman,
+    maidenhead,
+    mob::Mob,
+    motion::Motion,
+    n2k::{self, N2kMessage},
+    outlier::OutlierFilter,
+    plugin::Plugins,
+    propulsion::Propulsion,
+    raw_log::RawLog,
+    replay::Replay,
+    satellite::Satellites,
+    sbs,
+    seatalk::{self, Datagram},
+    sensors::Sensors,
+    sentence::Sentence,
+    source::{self, SourceStatus},
+    tide::TideStations,
+    track::Track,
+    traffic::{AlarmLevel, Traffic},
+    trip::Trips,
+    waypoint::Waypoints,
+    wind, Args,
+};
+
+/// How long crossing into another grid square stays in the alerts.
+const GRID_CHANGE_ALERT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug)]
+struct GridChange {
+    from: String,
+    to: String,
+    at: Instant,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Position {
+    pub lat: f64,
+    pub lon: f64,
+    /// Dead-reckoned rather than received.
+    pub estimated: bool,
+}
+
+#[derive(Debug)]
+pub struct NmeaStatus {
+    pub lat: StatusValue<f64>,
+    pub lon: StatusValue<f64>,
+    pub alt: StatusValue<f64>,
+    /// Climb rate in m/s.
+    pub vertical_speed: StatusValue<f64>,
+    vertical_speed_filter: VerticalSpeed,
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub vertical_speed_unit: VerticalSpeedUnit,
+    /// True heading in degrees.
+    pub hdg: StatusValue<f64>,
+    /// UTC date and time reported by the receiver in RMC or ZDA.
+    pub utc: StatusValue<SystemTime>,
+    time_check: TimeCheck,
+    /// Commands to the autopilot, from APB and HSC.
+    pub autopilot: Autopilot,
+    /// Magnetic variation in degrees, east positive, from RMC or HDG.
+    pub variation: StatusValue<f64>,
+    /// Speed over ground, smoothed with `--smoothing`.
+    pub sog: StatusValue<f64>,
+    /// Course over ground, smoothed with `--smoothing`.
+    pub cog: StatusValue<f64>,
+    pub sog_raw: StatusValue<f64>,
+    pub cog_raw: StatusValue<f64>,
+    velocity_filter: VelocityFilter,
+    /// Position and velocity estimate, with `--kalman`.
+    pub kalman: Option<Kalman>,
+    /// Number of character pairs of the displayed grid locator.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub locator_pairs: usize,
+    /// Four character grid square of the last fix.
+    grid_square: Option<String>,
+    grid_change: Option<GridChange>,
+    pub tides: Option<TideStations>,
+    /// Whether there was a valid fix at the last tick, to capture when it is lost.
+    had_fix: bool,
+    /// A trip started since the last tick.
+    trip_started: bool,
+    /// Paced playback of a file source, with `--replay`.
+    pub replay: Option<Replay>,
+    /// Why the last rewrite of the `--geojson` file failed.
+    pub geojson_error: Option<String>,
+    /// Why the last reload of the `--config` file failed.
+    pub config_error: Option<String>,
+    /// Bells sounded for alerts.
+    pub alarms: Alarms,
+    /// Man overboard mark, from `M` until cancelled with `X`.
+    pub mob: Option<Mob>,
+    /// How long to dead-reckon after the fix is lost, with `--dead-reckoning`.
+    dead_reckoning: Option<Duration>,
+    /// Last valid fix and when it came. Unlike `lat` and `lon` it is kept through
+    /// sentences reporting no fix, for dead reckoning.
+    last_fix: Option<((f64, f64), Instant)>,
+    /// Last valid speed and course over ground, kept like `last_fix`.
+    last_velocity: Option<(f64, f64)>,
+    pub fix_type: StatusValue<&'static str>,
+    /// Fix mode of each constellation, from GNS.
+    pub constellation_modes: StatusValue<Vec<(&'static str, &'static str)>>,
+    pub hdop: StatusValue<f64>,
+    /// Satellites used in the fix.
+    pub satellites_used: StatusValue<u32>,
+    pub history: History,
+    /// Depth below transducer in meters.
+    pub depth: StatusValue<f64>,
+    pub keel: Keel,
+    /// Apparent wind angle relative to the bow in degrees.
+    pub wind_angle: StatusValue<f64>,
+    /// Apparent wind speed in knots.
+    pub wind_speed: StatusValue<f64>,
+    /// Speed through water in knots.
+    pub stw: StatusValue<f64>,
+    pub accuracy: Accuracy,
+    /// RAIM results from GBS.
+    pub integrity: Integrity,
+    pub almanac: Almanac,
+    /// Last datum reported by DTM. Kept for good since receivers send it rarely.
+    pub datum: Option<Datum>,
+    /// Bytes of binary data or noise skipped by the readers.
+    pub dropped_bytes: u64,
+    pub diagnostics: Diagnostics,
+    /// Lines dropped undecoded by `--drop-policy` because decoding fell behind, copied from
+    /// the queue by the decoder.
+    pub queue_drops: u64,
+    /// Lines waiting to be decoded, out of `--queue-capacity`.
+    pub queued: (usize, usize),
+    /// Sentences dropped because another source delivered them first.
+    pub duplicates: u64,
+    /// Fixes rejected as implausible jumps.
+    pub outliers: OutlierFilter,
+    /// Inputs in priority order.
+    pub sources: Vec<SourceStatus>,
+    failover: Duration,
+    /// Meters apart two talkers may report the position before an alert.
+    divergence_limit: f64,
+    pub satellites: Satellites,
+    #[cfg(feature = "ais")]
+    pub ais: AisTargets,
+    /// Aircraft reported by a FLARM or an ADS-B receiver.
+    pub traffic: Traffic,
+    pub dsc: DscCalls,
+    pub device: DeviceMessages,
+    pub raw_log: RawLog,
+    /// Custom values from the config file.
+    #[cfg(feature = "extract")]
+    pub extracts: Extracts,
+    /// Scripts given with `--script`.
+    #[cfg(feature = "script")]
+    pub scripts: Scripts,
+    /// Plugins enabled with `--plugin`.
+    pub plugins: Plugins,
+    pub sensors: Sensors,
+    pub propulsion: Propulsion,
+    pub batteries: Batteries,
+    /// Pressure from the first XDR pressure transducer.
+    pub barometer: Barometer,
+    /// Track of fixes, paused while stationary.
+    pub track: Track,
+    pub motion: Motion,
+    trips: Trips,
+    pub waypoints: Waypoints,
+    pub coordinates: Coordinates,
+    /// Beacons sent to APRS-IS, with `--aprs-call`.
+    #[cfg(feature = "net")]
+    pub aprs: Option<AprsStatus>,
+}
+
+impl NmeaStatus {
+    pub fn new(args: &Args, config: &Config) -> NmeaStatus {
+        let timeout = args.timeout.into();
+        NmeaStatus {
+            lat: StatusValue::new(timeout),
+            lon: StatusValue::new(timeout),
+            alt: StatusValue::new(timeout),
+            vertical_speed: StatusValue::new(timeout),
+            vertical_speed_filter: VerticalSpeed::new(args.smoothing_samples),
+            vertical_speed_unit: args.vertical_speed_unit,
+            hdg: StatusValue::new(timeout),
+            autopilot: Autopilot::new(timeout),
+            variation: StatusValue::new(timeout),
+            utc: StatusValue::new(timeout),
+            time_check: TimeCheck::new(args.gps_time),
+            sog: StatusValue::new(timeout),
+            cog: StatusValue::new(timeout),
+            sog_raw: StatusValue::new(timeout),
+            cog_raw: StatusValue::new(timeout),
+            velocity_filter: VelocityFilter::new(args.smoothing, args.smoothing_samples),
+            kalman: args
+                .kalman
+                .then(|| Kalman::new(args.kalman_position_noise, args.kalman_acceleration)),
+            fix_type: StatusValue::new(timeout),
+            constellation_modes: StatusValue::new(timeout),
+            hdop: StatusValue::new(timeout),
+            satellites_used: StatusValue::new(timeout),
+            history: History::new(args.speed_bin),
+            depth: StatusValue::new(timeout),
+            keel: Keel::new(config.depth.clone(), args.shallow_alarm),
+            wind_angle: StatusValue::new(timeout),
+            wind_speed: StatusValue::new(timeout),
+            stw: StatusValue::new(timeout),
+            accuracy: Accuracy::new(timeout),
+            integrity: Integrity::new(timeout, args.integrity_limit),
+            almanac: Almanac::default(),
+            datum: None,
+            dropped_bytes: 0,
+            diagnostics: Diagnostics::default(),
+            queue_drops: 0,
+            queued: (0, args.queue_capacity as usize),
+            duplicates: 0,
+            outliers: OutlierFilter::new(args.outlier_distance),
+            sources: args
+                .sources()
+                .iter()
+                .map(|source| SourceStatus::new(source.to_string()))
+                .collect(),
+            failover: args.failover.into(),
+            divergence_limit: args.divergence_limit,
+            satellites: Satellites::new(args.satellite_expiry.into()),
+            #[cfg(feature = "ais")]
+            ais: AisTargets::new(
+                args.cpa_alarm * geo::NAUTICAL_MILE,
+                args.tcpa_alarm.into(),
+                args.ais_expiry.into(),
+            ),
+            traffic: Traffic::new(args.traffic_expiry.into()),
+            dsc: DscCalls::new(args.dsc_expiry.into()),
+            device: DeviceMessages::default(),
+            raw_log: RawLog::default(),
+            #[cfg(feature = "extract")]
+            extracts: Extracts::new(&config.extract, timeout),
+            #[cfg(feature = "script")]
+            scripts: Scripts::new(timeout),
+            plugins: Plugins::new(timeout),
+            sensors: Sensors::new(config.transducers.clone()),
+            propulsion: Propulsion::new(config.engine.clone(), timeout),
+            batteries: Batteries::new(config.battery.clone()),
+            barometer: Barometer::new(timeout, args.pressure_drop_alarm),
+            track: Track::default(),
+            motion: Motion::new(args.stationary_speed, args.stationary_after.into()),
+            trips: Trips::new(args.split_daily, args.split_gap.map(Into::into)),
+            waypoints: Waypoints::default(),
+            coordinates: Coordinates::new(args, config),
+            #[cfg(feature = "net")]
+            aprs: args.aprs_call.as_ref().map(|_| AprsStatus::default()),
+            dead_reckoning: args.dead_reckoning.map(Into::into),
+            last_fix: None,
+            last_velocity: None,
+            tides: config.tides.clone(),
+            had_fix: false,
+            trip_started: false,
+            replay: None,
+            geojson_error: None,
+            config_error: None,
+            alarms: Alarms::new(config.alarms.clone()),
+            mob: None,
+            locator_pairs: usize::from(args.locator_length / 2),
+            grid_square: None,
+            grid_change: None,
+        }
+    }
+
+    /// Applies the parts of a reloaded config that the status uses, keeping what was
+    /// received.
+    pub fn reconfigure(&mut self, args: &Args, config: &Config) {
+        #[cfg(feature = "extract")]
+        self.extracts
+            .set_configs(&config.extract, args.timeout.into());
+        self.sensors.set_names(config.transducers.clone());
+        self.propulsion.set_config(config.engine.clone());
+        self.batteries.set_config(config.battery.clone());
+        self.coordinates = Coordinates::new(args, config);
+        self.tides = config.tides.clone();
+        self.alarms.set_patterns(config.alarms.clone());
+        self.keel.set_config(config.depth.clone());
+    }
+
+    /// Current position, dead-reckoned from the last fix and velocity while the fix is
+    /// lost if enabled.
+    pub fn position(&self) -> Option<Position> {
+        if let (Some(&lat), Some(&lon)) = (self.lat.get(), self.lon.get()) {
+            return Some(Position {
+                lat,
+                lon,
+                estimated: false,
+            });
+        }
+
+        let max = self.dead_reckoning?;
+        let (fix, at) = self.last_fix?;
+        let age = at.elapsed();
+        if age > max {
+            return None;
+        }
+        let (lat, lon) = match self.kalman.as_ref().and_then(Kalman::estimate) {
+            Some(estimate) => (estimate.lat, estimate.lon),
+            None => {
+                let (sog, cog) = self.last_velocity?;
+                let offset = geo::displacement(cog, sog * geo::KNOT * age.as_secs_f64());
+                geo::from_local_xy(fix, offset)
+            }
+        };
+        Some(Position {
+            lat,
+            lon,
+            estimated: true,
+        })
+    }
+
+    /// The highest-priority source that reported a position recently.
+    pub fn active_source(&self) -> Option<usize> {
+        self.sources.iter().position(|source| {
+            source
+                .position_at
+                .is_some_and(|at| at.elapsed() < self.failover)
+        })
+    }
+
+    /// The two talkers with the furthest apart positions for the same fix, with the
+    /// distance in meters and the bearing from the first to the second. Talkers are
+    /// prefixed with their source when there are several.
+    pub fn divergence(&self) -> Option<(String, String, f64, f64)> {
+        let several = self.sources.len() > 1;
+        let talkers = self
+            .sources
+            .iter()
+            .flat_map(|source| {
+                source.talkers.iter().map(move |(talker, fixes)| {
+                    let label = if several {
+                        format!("{} {talker}", source.name)
+                    } else {
+                        talker.clone()
+                    };
+                    (label, fixes)
+                })
+            })
+            .filter(|(_, fixes)| {
+                fixes
+                    .back()
+                    .is_some_and(|fix| fix.at.elapsed() < self.failover)
+            })
+            .collect::<Vec<_>>();
+        let mut furthest: Option<(String, String, f64, f64)> = None;
+        for (i, (a, a_fixes)) in talkers.iter().enumerate() {
+            for (b, b_fixes) in &talkers[i + 1..] {
+                let Some((from, to)) = source::same_fix(a_fixes, b_fixes) else {
+                    continue;
+                };
+                let (bearing, distance) = geo::course_and_length(geo::local_xy(from, to));
+                if furthest.as_ref().is_none_or(|f| distance > f.2) {
+                    furthest = Some((a.clone(), b.clone(), distance, bearing));
+                }
+            }
+        }
+        furthest
+    }
+
+    /// Records a line in the statistics of `source` and returns whether it should be
+    /// applied. Position from backup sources is ignored while a higher-priority source
+    /// is alive.
+    pub fn receive(&mut self, source: usize, line: &str) -> bool {
+        let carries_position = self
+            .sources
+            .get_mut(source)
+            .is_some_and(|status| status.update(line));
+        !carries_position || self.active_source() == Some(source)
+    }
+
+    /// Decodes one line of input and applies it.
+    pub fn handle_line(&mut self, line: &str) {
+        self.raw_log.push(line);
+        #[cfg(feature = "extract")]
+        self.extracts.update(line);
+        #[cfg(feature = "script")]
+        self.scripts.on_line(line);
+        self.plugins.on_line(line);
+        if let Some(sentence) = Sentence::parse(line) {
+            match sentence.kind {
+                "GSV" => return self.satellites.update(&sentence),
+                "GNS" => {
+                    if let Some(gns) = Gns::parse(&sentence) {
+                        let time = sentence.fields.first().and_then(|time| time_of_day(time));
+                        self.apply_gns(gns, time);
+                    }
+                    return;
+                }
+                #[cfg(feature = "ais")]
+                "VDM" => return self.ais.update(&sentence),
+                "PFLAA" | "PFLAU" => return self.traffic.update(&sentence),
+                "DSC" | "DSE" => return self.dsc.update(&sentence),
+                "XDR" => {
+                    self.barometer.update(&sentence);
+                    return self.sensors.update(&sentence);
+                }
+                "HDT" => return self.hdg.update(number(sentence.fields.first())),
+                "HDG" => return self.update_magnetic_heading(&sentence),
+                "APB" | "HSC" => return self.autopilot.update(&sentence),
+                "RPM" => return self.propulsion.update(&sentence),
+                "DPT" => {
+                    let depth = self.keel.update_dpt(&sentence);
+                    return self.depth.update(depth);
+                }
+                // Depth in feet, meters and fathoms, each followed by its unit.
+                "DBT" => return self.depth.update(number(sentence.fields.get(2))),
+                // Angle, reference, speed, unit, status. Only apparent wind is used; true
+                // wind is computed from it.
+                "MWV" => {
+                    let fields = &sentence.fields;
+                    if fields.get(1) != Some(&"R") || fields.get(4) == Some(&"V") {
+                        return;
+                    }
+                    let speed = number(fields.get(2))
+                        .zip(fields.get(3))
+                        .and_then(|(speed, unit)| wind::knots(speed, unit));
+                    if let (Some(angle), Some(speed)) = (number(fields.first()), speed) {
+                        self.wind_angle.update(angle);
+                        self.wind_speed.update(speed);
+                    }
+                    return;
+                }
+                // True heading, magnetic heading, then speed in knots and km/h.
+                "VHW" => {
+                    let knots = number(sentence.fields.get(4))
+                        .or_else(|| number(sentence.fields.get(6)).map(|kmh| kmh / 1.852));
+                    return self.stw.update(knots);
+                }
+                "RMC" => {
+                    if let Some(variation) = signed(&sentence.fields, 9) {
+                        self.variation.update(variation);
+                    }
+                    let date = sentence.fields.get(8).and_then(|date| ddmmyy(date));
+                    self.update_utc(date, &sentence);
+                }
+                "ZDA" => {
+                    let date = zda_date(&sentence.fields);
+                    return self.update_utc(date, &sentence);
+                }
+                "PGRME" | "PGRMZ" | "PUBX" | "GST" => return self.accuracy.update(&sentence),
+                "GBS" => return self.integrity.update(&sentence),
+                "ALM" => return self.almanac.update(&sentence),
+                "DTM" => {
+                    if let Some(datum) = Datum::parse(&sentence) {
+                        self.datum = Some(datum);
+                    }
+                    return;
+                }
+                kind if kind.starts_with("PMTK") => return self.device.update(line, &sentence),
+                "STALK" => {
+                    if let Some(datagram) = seatalk::parse(&sentence) {
+                        self.apply_seatalk(datagram);
+                    }
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(message) = sbs::parse(line) {
+            let own = self.own_position();
+            return self.traffic.update_sbs(message, own);
+        }
+
+        if let Some(message) = n2k::parse(line) {
+            return self.apply_n2k(message);
+        }
+
+        match nmea::parse_str(line) {
+            Ok(ParseResult::GGA(gga)) => {
+                self.hdop.update(gga.hdop.map(From::from));
+                self.satellites_used.update(gga.fix_satellites);
+                let alt = gga.altitude.map(From::from);
+                let time = Sentence::parse(line)
+                    .and_then(|sentence| time_of_day(sentence.fields.first()?));
+                if !self.update_position(gga.latitude, gga.longitude, alt, time) {
+                    return;
+                }
+                self.fix_type.update(gga.fix_type.map(|t| match t {
+                    FixType::Invalid => "Invalid",
+                    FixType::Gps => "Gps",
+                    FixType::DGps => "DGps",
+                    FixType::Pps => "Pps",
+                    FixType::Rtk => "Rtk",
+                    FixType::FloatRtk => "FloatRtk",
+                    FixType::Estimated => "Estimated",
+                    FixType::Manual => "Manual",
+                    FixType::Simulation => "Simulation",
+                }));
+            }
+            Ok(ParseResult::RMC(rmc)) => self.update_velocity(
+                rmc.speed_over_ground.map(From::from),
+                rmc.true_course.map(From::from),
+            ),
+            Ok(ParseResult::VTG(vtg)) => self.update_velocity(
+                vtg.speed_over_ground.map(From::from),
+                vtg.true_course.map(From::from),
+            ),
+            Err(nmea::Error::ParsingError(e)) => debug!(line, error = %e, "failed to parse"),
+            _ => {}
+        }
+    }
+
+    /// Applies a fix from GGA or GNS made at `time`. Returns false when it was rejected as
+    /// an outlier.
+    fn update_position(
+        &mut self,
+        lat: Option<f64>,
+        lon: Option<f64>,
+        alt: Option<f64>,
+        time: Option<(i8, i8, f64)>,
+    ) -> bool {
+        if let (Some(lat), Some(lon)) = (lat, lon) {
+            if !self.outliers.accept((lat, lon), time) {
+                return false;
+            }
+        }
+        self.lat.update(lat);
+        self.lon.update(lon);
+        self.alt.update(alt);
+        if let Some(alt) = alt {
+            let rate = self.vertical_speed_filter.update(alt);
+            self.vertical_speed.update(rate);
+        }
+        if let (Some(lat), Some(lon)) = (lat, lon) {
+            self.update_fix(lat, lon);
+        }
+        true
+    }
+
+    fn apply_gns(&mut self, gns: Gns, time: Option<(i8, i8, f64)>) {
+        self.hdop.update(gns.hdop);
+        self.satellites_used.update(gns.satellites);
+        if !self.update_position(gns.lat, gns.lon, gns.alt, time) {
+            return;
+        }
+        self.fix_type.update(gns.fix_type());
+        self.constellation_modes.update(gns.modes);
+    }
+
+    /// Sets the receiver time from the time in the first field of `sentence` on `date`.
+    fn update_utc(&mut self, date: Option<(i16, i8, i8)>, sentence: &Sentence) {
+        let time = sentence.fields.first();
+        if let Some(utc) = date
+            .zip(time)
+            .and_then(|(date, time)| receiver_time(date, time))
+        {
+            self.utc.update(self.time_check.apply(utc));
+        }
+    }
+
+    /// HDG gives the magnetic heading with deviation and, optionally, variation. The true
+    /// heading needs the variation, from the same sentence or from RMC.
+    fn update_magnetic_heading(&mut self, sentence: &Sentence) {
+        if let Some(variation) = signed(&sentence.fields, 3) {
+            self.variation.update(variation);
+        }
+        let Some(magnetic) = number(sentence.fields.first()) else {
+            return;
+        };
+        let deviation = signed(&sentence.fields, 1).unwrap_or(0.0);
+        if let Some(&variation) = self.variation.get() {
+            self.hdg
+                .update((magnetic + deviation + variation).rem_euclid(360.0));
+        }
+    }
+
+    /// Replaces the speed and course smoothing, starting afresh.
+    pub fn set_smoothing(&mut self, smoothing: Smoothing, samples: usize) {
+        self.velocity_filter = VelocityFilter::new(smoothing, samples);
+    }
+
+    fn update_fix(&mut self, lat: f64, lon: f64) {
+        self.last_fix = Some(((lat, lon), Instant::now()));
+        let time = self.utc.get().copied().unwrap_or_else(SystemTime::now);
+        if self.trips.on_fix(time) {
+            self.track.split();
+            self.trip_started = true;
+        }
+        if !self.motion.stationary() {
+            self.track.push(lat, lon);
+        }
+
+        let square = maidenhead::locator(lat, lon, 2);
+        if let Some(from) = self.grid_square.replace(square.clone()) {
+            if from != square {
+                self.grid_change = Some(GridChange {
+                    from,
+                    to: square,
+                    at: Instant::now(),
+                });
+            }
+        }
+
+        if let Some(kalman) = &mut self.kalman {
+            kalman.update_position(lat, lon);
+        }
+    }
+
+    fn update_velocity(&mut self, sog: Option<f64>, cog: Option<f64>) {
+        self.motion.update(sog);
+        self.sog_raw.update(sog);
+        self.cog_raw.update(cog);
+        match (sog, cog) {
+            (Some(sog), Some(cog)) => {
+                if let Some(kalman) = &mut self.kalman {
+                    kalman.update_velocity(sog, cog);
+                }
+                let (sog, cog) = self.velocity_filter.update(sog, cog);
+                self.last_velocity = Some((sog, cog));
+                self.sog.update(sog);
+                self.cog.update(cog);
+            }
+            _ => {
+                self.velocity_filter.reset();
+                self.sog.update(sog);
+                self.cog.update(cog);
+            }
+        }
+    }
+
+    /// Applies a decoded NMEA 2000 message to the same fields the 0183 sentences drive.
+    pub fn apply_n2k(&mut self, message: N2kMessage) {
+        match message {
+            N2kMessage::Position { lat, lon } if self.outliers.accept((lat, lon), None) => {
+                self.lat.update(lat);
+                self.lon.update(lon);
+                self.update_fix(lat, lon);
+            }
+            N2kMessage::Position { .. } => {}
+            N2kMessage::CogSog { cog, sog } => self.update_velocity(sog, cog),
+            N2kMessage::Depth { depth } => self.depth.update(depth),
+            N2kMessage::Wind {
+                angle,
+                speed,
+                apparent: true,
+            } => {
+                self.wind_angle.update(angle);
+                self.wind_speed.update(speed);
+            }
+            N2kMessage::Wind { .. } => {}
+        }
+    }
+
+    pub fn apply_seatalk(&mut self, datagram: Datagram) {
+        match datagram {
+            Datagram::Depth(depth) => self.depth.update(depth),
+            Datagram::WindAngle(angle) => self.wind_angle.update(angle),
+            Datagram::WindSpeed(speed) => self.wind_speed.update(speed),
+            Datagram::WaterSpeed(speed) => self.stw.update(speed),
+        }
+    }
+
+    #[cfg(feature = "ais")]
+    pub fn own_ship(&self) -> Option<OwnShip> {
+        Some(OwnShip {
+            position: (*self.lat.get()?, *self.lon.get()?),
+            sog: *self.sog.get()?,
+            cog: *self.cog.get()?,
+        })
+    }
+
+    /// Conditions that need the user's attention right now.
+    pub fn alerts(&self) -> Vec<Alert> {
+        let mob = self.mob.as_ref().map(|mob| Alert {
+            id: "mob".to_string(),
+            text: match self.position() {
+                Some(position) => {
+                    let (bearing, distance) = mob.back_from((position.lat, position.lon));
+                    format!(
+                        "MAN OVERBOARD {:.2} NM at {bearing:.0}°",
+                        distance / geo::NAUTICAL_MILE
+                    )
+                }
+                None => "MAN OVERBOARD".to_string(),
+            },
+        });
+
+        let dsc = self
+            .dsc
+            .iter()
+            .filter(|call| call.is_alert())
+            .map(|call| Alert {
+                id: format!("dsc {}", call.mmsi),
+                text: format!(
+                    "DSC {} from {}{} at {}",
+                    call.category.as_str(),
+                    call.mmsi,
+                    call.nature.map(|n| format!(" ({n})")).unwrap_or_default(),
+                    call.position.map_or_else(
+                        || "unknown position".to_string(),
+                        |(lat, lon)| self.coordinates.position(lat, lon)
+                    ),
+                ),
+            });
+
+        #[cfg(feature = "ais")]
+        let cpa = self
+            .ais
+            .by_cpa(self.own_ship().as_ref())
+            .into_iter()
+            .filter_map(|(target, cpa)| {
+                let cpa = cpa.filter(|cpa| self.ais.is_dangerous(cpa))?;
+                Some(Alert {
+                    id: format!("cpa {}", target.mmsi),
+                    text: format!(
+                        "CPA {} {:.2} NM in {:.0} min",
+                        target.mmsi,
+                        cpa.distance / geo::NAUTICAL_MILE,
+                        cpa.time / 60.0,
+                    ),
+                })
+            });
+
+        #[cfg(not(feature = "ais"))]
+        let cpa = std::iter::empty();
+
+        let shallow = self
+            .depth
+            .get()
+            .and_then(|&depth| self.keel.shallow(depth))
+            .map(|below| Alert {
+                id: "depth".to_string(),
+                text: format!("shallow water: {below:.1} m below the keel"),
+            });
+
+        let traffic = self
+            .traffic
+            .by_distance()
+            .into_iter()
+            .filter(|aircraft| aircraft.alarm != AlarmLevel::None)
+            .map(|aircraft| Alert {
+                id: format!("traffic {}", aircraft.id),
+                text: format!(
+                    "traffic {} alarm: {} at {}",
+                    aircraft.alarm.as_str(),
+                    aircraft.id,
+                    aircraft.distance().map_or_else(
+                        || "unknown distance".to_string(),
+                        |distance| format!("{distance:.0} m")
+                    ),
+                ),
+            });
+
+        let pressure = self
+            .barometer
+            .tendency()
+            .filter(|_| self.barometer.is_dropping())
+            .map(|(change, span)| Alert {
+                id: "pressure drop".to_string(),
+                text: format!(
+                    "pressure fell {:.1} hPa in {:.1} h",
+                    -change,
+                    span.as_secs_f64() / 3600.0
+                ),
+            });
+
+        let datum = self
+            .datum
+            .as_ref()
+            .and_then(Datum::warning)
+            .map(|text| Alert {
+                id: "datum".to_string(),
+                text,
+            });
+
+        let divergence = self
+            .divergence()
+            .filter(|&(_, _, distance, _)| distance > self.divergence_limit)
+            .map(|(a, b, distance, bearing)| Alert {
+                id: format!("divergence {a} {b}"),
+                text: format!("{a} and {b} positions differ by {distance:.0} m at {bearing:.0}°"),
+            });
+
+        let geojson = self.geojson_error.as_ref().map(|error| Alert {
+            id: "geojson".to_string(),
+            text: format!("GeoJSON not written: {error}"),
+        });
+
+        let config = self.config_error.as_ref().map(|error| Alert {
+            id: "config".to_string(),
+            text: format!("config not reloaded: {error}"),
+        });
+
+        let integrity = self.integrity.problem().map(|text| Alert {
+            id: "integrity".to_string(),
+            text,
+        });
+
+        let battery = self
+            .batteries
+            .iter(&self.sensors)
+            .into_iter()
+            .filter(|battery| battery.is_low())
+            .map(|battery| Alert {
+                id: format!("battery {}", battery.id),
+                text: format!("battery {} low: {:.2} V", battery.name, battery.voltage),
+            });
+
+        let grid = self
+            .grid_change
+            .as_ref()
+            .filter(|change| change.at.elapsed() < GRID_CHANGE_ALERT)
+            .map(|change| Alert {
+                id: format!("grid {}", change.to),
+                text: format!("grid square changed: {} → {}", change.from, change.to),
+            });
+
+        #[cfg(feature = "script")]
+        let script = self.scripts.alerts().into_iter().map(|text| Alert {
+            id: format!("script {text}"),
+            text,
+        });
+
+        #[cfg(not(feature = "script"))]
+        let script = std::iter::empty();
+
+        let rollover = self
+            .utc
+            .get()
+            .zip(self.time_check.rollovers)
+            .map(|(&utc, count)| Alert {
+                id: "rollover".to_string(),
+                text: format!(
+                    "receiver date {} is {} weeks behind: GPS week number rollover",
+                    &clock::timestamp(utc)[..10],
+                    count * gps_time::ROLLOVER_WEEKS
+                ),
+            });
+
+        mob.into_iter()
+            .chain(dsc)
+            .chain(cpa)
+            .chain(shallow)
+            .chain(traffic)
+            .chain(pressure)
+            .chain(battery)
+            .chain(integrity)
+            .chain(datum)
+            .chain(divergence)
+            .chain(geojson)
+            .chain(config)
+            .chain(grid)
+            .chain(script)
+            .chain(rollover)
+            .collect()
+    }
+
+    /// True wind angle relative to the bow, speed in knots and, with a heading, the
+    /// direction it blows from. Boat speed is taken through the water when known.
+    #[cfg(feature = "tui")]
+    pub fn true_wind(&self) -> Option<(f64, f64, Option<f64>)> {
+        let boat_speed = self.stw.get().or(self.sog.get())?;
+        let (angle, speed) = wind::true_wind(
+            *self.wind_angle.get()?,
+            *self.wind_speed.get()?,
+            *boat_speed,
+        );
+        let direction = self
+            .hdg
+            .get()
+            .map(|heading| (heading + angle).rem_euclid(360.0));
+        Some((angle, speed, direction))
+    }
+
+    /// Set (direction the water flows towards) and drift in knots of the current, from
+    /// the difference between the velocity over ground and through the water.
+    #[cfg(feature = "tui")]
+    pub fn current(&self) -> Option<(f64, f64)> {
+        let (ground_x, ground_y) = geo::displacement(*self.cog.get()?, *self.sog.get()?);
+        let (water_x, water_y) = geo::displacement(*self.hdg.get()?, *self.stw.get()?);
+        Some(geo::course_and_length((
+            ground_x - water_x,
+            ground_y - water_y,
+        )))
+    }
+
+    /// Receiver time of the fix in a GGA or GNS `line`, from its time of day on the date
+    /// last received. RMC and ZDA usually come after the fix in an epoch, so `utc` is
+    /// still the time of the fix before.
+    #[cfg(feature = "export")]
+    pub fn fix_time(&self, line: &str) -> Option<SystemTime> {
+        let sentence = Sentence::parse(line).filter(|s| matches!(s.kind, "GGA" | "GNS"))?;
+        let (&utc, _) = self.utc.last()?;
+        clock::on_date_of(utc, time_of_day(sentence.fields.first()?)?)
+    }
+
+    /// Position and altitude that traffic is placed relative to.
+    fn own_position(&self) -> Option<((f64, f64), Option<f64>)> {
+        Some((
+            (*self.lat.get()?, *self.lon.get()?),
+            self.alt.get().copied(),
+        ))
+    }
+
+    /// Periodic work, once a second. Returns what the recording has to act on.
+    pub fn tick(&mut self) -> Tick {
+        if let Some(own) = self.own_position() {
+            self.traffic.locate(own);
+        }
+        self.history.sample(Sample {
+            sog: self.sog_raw.get().copied(),
+            alt: self.alt.get().copied(),
+            hdop: self.hdop.get().copied(),
+            satellites: self.satellites_used.get().map(|&n| f64::from(n)),
+            depth: self.depth.get().copied(),
+            stw: self.stw.get().copied(),
+            wind_speed: self.wind_speed.get().copied(),
+            sigma_lat: self.accuracy.sigma_lat.get().copied(),
+            sigma_lon: self.accuracy.sigma_lon.get().copied(),
+            sigma_alt: self.accuracy.sigma_alt.get().copied(),
+            position: self
+                .lat
+                .get()
+                .copied()
+                .zip(self.lon.get().copied())
+                .filter(|_| !self.motion.stationary()),
+            lines: self.sources.iter().map(|source| source.lines).sum(),
+        });
+        self.batteries.sample(&self.sensors);
+        let alerts = self.alerts();
+        self.alarms.update(&alerts);
+        Tick {
+            conditions: self.capture_conditions(alerts),
+            split: std::mem::take(&mut self.trip_started),
+        }
+    }
+
+    /// The alerts, and the fix being lost, which trigger a capture with `--capture`.
+    fn capture_conditions(&mut self, alerts: Vec<Alert>) -> Vec<String> {
+        let mut conditions = alerts.into_iter().map(|alert| alert.id).collect::<Vec<_>>();
+        let fix = self.lat.get().is_some() && self.lon.get().is_some();
+        if self.had_fix && !fix {
+            conditions.push("fix lost".to_string());
+        }
+        self.had_fix = fix;
+        conditions
+    }
+}
+
+/// What [`NmeaStatus::tick`] asks of the recording, which the status does not hold.
+#[derive(Debug)]
+pub struct Tick {
+    /// Conditions present now; a capture is triggered for each new one.
+    pub conditions: Vec<String>,
+    /// A trip started, so the recording goes on in a new file.
+    pub split: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Alert {
+    /// Stays the same while the condition lasts, unlike the text.
+    pub id: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusValue<T> {
+    inner: Option<T>,
+    updated_at: Instant,
+    timeout: Duration,
+}
+
+impl<T> Default for StatusValue<T> {
+    fn default() -> Self {
+        StatusValue {
+            inner: None,
+            updated_at: Instant::now(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl<T> StatusValue<T> {
+    pub fn new(timeout: Duration) -> StatusValue<T> {
+        StatusValue {
+            timeout,
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&mut self, next: impl Into<Option<T>>) {
+        self.inner = next.into();
+        self.updated_at = Instant::now();
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        self.inner
+            .as_ref()
+            .filter(|_| self.updated_at.elapsed() < self.timeout)
+    }
+
+    /// The last value received, however old, and its age.
+    pub fn last(&self) -> Option<(&T, Duration)> {
+        Some((self.inner.as_ref()?, self.updated_at.elapsed()))
+    }
+}
+
+#[cfg(feature = "tui")]
+impl<T> From<StatusValue<T>> for Text<'_>
+where
+    T: ToString,
+{
+    fn from(value: StatusValue<T>) -> Self {
+        match value.get() {
+            Some(v) => Text::from(v.to_string()),
+            None => Text::from("value"),
+        }
+    }
+}
+
+fn number(field: Option<&&str>) -> Option<f64> {
+    field?.parse().ok()
+}
+
+/// An angle at `fields[index]` with an `E`/`W` direction after it, east positive.
+fn signed(fields: &[&str], index: usize) -> Option<f64> {
+    let value = number(fields.get(index))?;
+    match *fields.get(index + 1)? {
+        "E" => Some(value),
+        "W" => Some(-value),
+        _ => None,
+    }
+}
+
+/// Two decimal digits at `index`.
+fn digits(field: &str, index: usize) -> Option<i8> {
+    field.get(index..index + 2)?.parse().ok()
+}
+
+/// An RMC `ddmmyy` date field as `(year, month, day)`.
+///
+/// Years from 80 on are taken as 19yy, as GPS time starts in 1980, so that a receiver
+/// hit by a week-number rollover still reports a date [`gps_time`] can flag.
+fn ddmmyy(date: &str) -> Option<(i16, i8, i8)> {
+    let (day, month, year) = (digits(date, 0)?, digits(date, 2)?, digits(date, 4)?);
+    let century = if year >= 80 { 1900 } else { 2000 };
+    Some((century + i16::from(year), month, day))
+}
+
+/// The ZDA day, month and year fields as `(year, month, day)`.
+fn zda_date(fields: &[&str]) -> Option<(i16, i8, i8)> {
+    let field = |i: usize| fields.get(i)?.parse().ok();
+    Some((fields.get(3)?.parse().ok()?, field(2)?, field(1)?))
+}
+
+/// A `hhmmss.ss` time field on a UTC date.
+fn receiver_time(date: (i16, i8, i8), time: &str) -> Option<SystemTime> {
+    clock::from_utc(date, time_of_day(time)?)
+}
+
+/// A `hhmmss.ss` time field as `(hour, minute, second)`.
+pub fn time_of_day(time: &str) -> Option<(i8, i8, f64)> {
+    let second = time.get(4..)?.parse().ok()?;
+    Some((digits(time, 0)?, digits(time, 2)?, second))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ddmmyy_dates() {
+        assert_eq!(ddmmyy("010924"), Some((2024, 9, 1)));
+        assert_eq!(ddmmyy("311279"), Some((2079, 12, 31)));
+        // GPS time starts in 1980, so these are a week number rollover, not the future.
+        assert_eq!(ddmmyy("060180"), Some((1980, 1, 6)));
+        assert_eq!(ddmmyy("170199"), Some((1999, 1, 17)));
+        assert_eq!(ddmmyy("0109"), None);
+        assert_eq!(ddmmyy(""), None);
+    }
+}
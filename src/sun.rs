@@ -0,0 +1,79 @@
+//! Sunrise, sunset and civil twilight from the sunrise equation, accurate to a minute or
+//! so, which is all a monitor needs.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Julian date of the Unix epoch.
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+/// Julian date of the J2000.0 epoch.
+const J2000: f64 = 2_451_545.0;
+
+/// Sun altitude at sunrise and sunset, allowing for refraction and the solar disc.
+const SUNRISE_ALTITUDE: f64 = -0.833;
+/// Sun altitude at the start and end of civil twilight.
+const CIVIL_ALTITUDE: f64 = -6.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Crossing {
+    /// The times the sun passes the altitude on its way up and down.
+    Times(SystemTime, SystemTime),
+    /// The sun stays above the altitude all day.
+    AlwaysAbove,
+    /// The sun stays below the altitude all day.
+    AlwaysBelow,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SunTimes {
+    /// Sunrise and sunset.
+    pub day: Crossing,
+    /// Civil dawn and dusk.
+    pub civil: Crossing,
+}
+
+/// Sun times for the local day containing `time` at the given position.
+pub fn sun_times(lat: f64, lon: f64, time: SystemTime) -> SunTimes {
+    let unix = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    let jd = unix / 86_400.0 + UNIX_EPOCH_JD;
+    // Days since J2000 of the local solar noon nearest to `time`.
+    let n = (jd - J2000 + lon / 360.0).round();
+
+    let mean_noon = n - lon / 360.0;
+    let mean_anomaly = (357.5291 + 0.985_600_28 * mean_noon).rem_euclid(360.0);
+    let m = mean_anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic_lon = (mean_anomaly + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic_lon).sin();
+    let declination = (ecliptic_lon.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+    let crossing = |altitude: f64| {
+        let lat = lat.to_radians();
+        let cos_hour_angle = (altitude.to_radians().sin() - lat.sin() * declination.sin())
+            / (lat.cos() * declination.cos());
+        if cos_hour_angle > 1.0 {
+            Crossing::AlwaysBelow
+        } else if cos_hour_angle < -1.0 {
+            Crossing::AlwaysAbove
+        } else {
+            let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+            Crossing::Times(
+                from_julian(transit - half_day),
+                from_julian(transit + half_day),
+            )
+        }
+    };
+
+    SunTimes {
+        day: crossing(SUNRISE_ALTITUDE),
+        civil: crossing(CIVIL_ALTITUDE),
+    }
+}
+
+fn from_julian(jd: f64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs_f64(((jd - UNIX_EPOCH_JD) * 86_400.0).max(0.0))
+}
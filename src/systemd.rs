@@ -0,0 +1,93 @@
+//! systemd service notifications (`Type=notify`, `WatchdogSec=`) for daemon mode.
+//!
+//! The watchdog is only fed while input keeps arriving, so that systemd restarts a daemon
+//! whose readers have hung.
+
+use std::{env, os::unix::net::UnixDatagram, sync::Arc, time::Duration};
+
+use tokio::sync::RwLock;
+
+use crate::status::NmeaStatus;
+
+/// Sends a notification such as `READY=1`. Does nothing when not run by systemd.
+pub fn notify(state: &str) {
+    let Some(path) = env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    let Ok(socket) = UnixDatagram::unbound() else {
+        return;
+    };
+    let path = path.to_string_lossy();
+    #[cfg(target_os = "linux")]
+    if let Some(name) = path.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt as _;
+        if let Ok(address) = std::os::unix::net::SocketAddr::from_abstract_name(name) {
+            let _ = socket.send_to_addr(state.as_bytes(), &address);
+        }
+        return;
+    }
+    let _ = socket.send_to(state.as_bytes(), path.as_ref());
+}
+
+/// Reports readiness, then keeps the status text current and feeds the watchdog. Started
+/// once the daemon socket is bound, so that `READY=1` means clients can attach.
+pub async fn run(nmea: Arc<RwLock<NmeaStatus>>) {
+    notify("READY=1");
+
+    let watchdog = env::var("WATCHDOG_USEC")
+        .ok()
+        .and_then(|usec| usec.parse().ok())
+        .map(Duration::from_micros);
+    let mut interval =
+        tokio::time::interval(watchdog.map_or(Duration::from_secs(5), |watchdog| watchdog / 2));
+    loop {
+        interval.tick().await;
+        let nmea = nmea.read().await;
+        notify(&format!("STATUS={}", status_text(&nmea)));
+
+        let Some(watchdog) = watchdog else {
+            continue;
+        };
+        let alive = nmea
+            .sources
+            .iter()
+            .filter_map(|source| source.last_line_at())
+            .any(|at| at.elapsed() < watchdog);
+        if alive {
+            notify("WATCHDOG=1");
+        }
+    }
+}
+
+/// One part per source, e.g. `tcp://gps:10110 connected 1.0/s (active)`, then the
+/// monitor's own usage and queues.
+fn status_text(nmea: &NmeaStatus) -> String {
+    let active = nmea.active_source();
+    let diagnostics = &nmea.diagnostics;
+    let usage = format!(
+        "{}, queued {}/{} ({} dropped)",
+        diagnostics.usage(),
+        nmea.queued.0,
+        nmea.queued.1,
+        nmea.queue_drops
+    );
+    nmea.sources
+        .iter()
+        .enumerate()
+        .map(|(index, source)| {
+            format!(
+                "{} {} {:.1}/s{}",
+                source.name,
+                source.state,
+                source.rate(),
+                if active == Some(index) {
+                    " (active)"
+                } else {
+                    ""
+                }
+            )
+        })
+        .chain([usage])
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,113 @@
+//! Colors of status values by range from the `[thresholds]` of the config file, such as
+//! HDOP green below 2, yellow below 5 and red above, or depth red below 3 m.
+//!
+//! The value is the first number shown in the cell, so thresholds apply to custom values
+//! and script values as well as built-in ones, in the units shown.
+
+#[cfg(feature = "tui")]
+use ratatui::style::Color;
+use serde::Deserialize;
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Threshold {
+    /// Applies to values below this; without it, to any value not matched before.
+    pub below: Option<f64>,
+    pub color: ThresholdColor,
+}
+
+#[derive(Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdColor {
+    Green,
+    Yellow,
+    Red,
+    Blue,
+    Cyan,
+    Magenta,
+    Gray,
+}
+
+#[cfg(feature = "tui")]
+impl From<ThresholdColor> for Color {
+    fn from(color: ThresholdColor) -> Self {
+        match color {
+            ThresholdColor::Green => Color::Green,
+            ThresholdColor::Yellow => Color::Yellow,
+            ThresholdColor::Red => Color::LightRed,
+            ThresholdColor::Blue => Color::LightBlue,
+            ThresholdColor::Cyan => Color::Cyan,
+            ThresholdColor::Magenta => Color::Magenta,
+            ThresholdColor::Gray => Color::DarkGray,
+        }
+    }
+}
+
+/// The color of the first threshold `text` is within, checked in order.
+#[cfg(feature = "tui")]
+pub fn color(thresholds: &[Threshold], text: &str) -> Option<ThresholdColor> {
+    let value = leading_number(text)?;
+    thresholds
+        .iter()
+        .find(|threshold| threshold.below.is_none_or(|below| value < below))
+        .map(|threshold| threshold.color)
+}
+
+/// The first number in `text`, with its sign.
+#[cfg(feature = "tui")]
+fn leading_number(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let start = match text[..start].chars().next_back() {
+        Some('-' | '+') => start - 1,
+        _ => start,
+    };
+    let end = text[start + 1..]
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .map_or(text.len(), |end| start + 1 + end);
+    text[start..end].parse().ok()
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    fn hdop() -> Vec<Threshold> {
+        let threshold = |below, color| Threshold { below, color };
+        vec![
+            threshold(Some(2.0), ThresholdColor::Green),
+            threshold(Some(5.0), ThresholdColor::Yellow),
+            threshold(None, ThresholdColor::Red),
+        ]
+    }
+
+    #[test]
+    fn first_threshold_the_value_is_below() {
+        assert_eq!(color(&hdop(), "0.9"), Some(ThresholdColor::Green));
+        assert_eq!(color(&hdop(), "2.0"), Some(ThresholdColor::Yellow));
+        assert_eq!(color(&hdop(), "12.5"), Some(ThresholdColor::Red));
+        let depth = [Threshold {
+            below: Some(3.0),
+            color: ThresholdColor::Red,
+        }];
+        assert_eq!(color(&depth, "2.4 m"), Some(ThresholdColor::Red));
+        assert_eq!(color(&depth, "3.1 m"), None);
+    }
+
+    #[test]
+    fn placeholder_has_no_color() {
+        assert_eq!(color(&hdop(), "value"), None);
+        assert_eq!(color(&hdop(), "-"), None);
+    }
+
+    #[test]
+    fn leading_numbers() {
+        assert_eq!(leading_number("12.5 m"), Some(12.5));
+        assert_eq!(leading_number("-3.25°C"), Some(-3.25));
+        assert_eq!(leading_number("+4 hPa"), Some(4.0));
+        assert_eq!(leading_number("depth 7"), Some(7.0));
+        assert_eq!(leading_number("12.5 kn, 3.0 m"), Some(12.5));
+        assert_eq!(leading_number("value"), None);
+        assert_eq!(leading_number(""), None);
+    }
+}
@@ -0,0 +1,265 @@
+//! Tide prediction from harmonic constants of stations in an offline database.
+//!
+//! The database is a TOML file of stations:
+//!
+//! ```toml
+//! [[station]]
+//! name = "Tokyo"
+//! lat = 35.65
+//! lon = 139.77
+//! # Mean water level above chart datum in meters.
+//! datum = 1.13
+//! # Amplitude in meters and Greenwich phase lag (UTC) in degrees.
+//! constituents = [
+//!     { name = "M2", amplitude = 0.50, phase = 155.0 },
+//!     { name = "K1", amplitude = 0.25, phase = 180.0 },
+//! ]
+//! ```
+
+use std::path::Path;
+#[cfg(feature = "tui")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context as _, Result};
+use serde::Deserialize;
+
+#[cfg(feature = "tui")]
+use crate::geo;
+
+/// How far ahead to look for the next high and low water.
+#[cfg(feature = "tui")]
+const SEARCH_HORIZON: Duration = Duration::from_secs(26 * 3600);
+/// Step of the high/low water search.
+#[cfg(feature = "tui")]
+const SEARCH_STEP: Duration = Duration::from_secs(6 * 60);
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TideStations {
+    #[serde(rename = "station")]
+    stations: Vec<Station>,
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Station {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub datum: f64,
+    constituents: Vec<Constituent>,
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct Constituent {
+    name: String,
+    amplitude: f64,
+    phase: f64,
+}
+
+#[cfg(feature = "tui")]
+#[derive(Clone, Copy, Debug)]
+pub struct Prediction {
+    /// Height above chart datum in meters.
+    pub height: f64,
+    /// Rate of change in meters per hour.
+    pub rate: f64,
+    /// Next high and low water, each with its height.
+    pub next_high: Option<(SystemTime, f64)>,
+    pub next_low: Option<(SystemTime, f64)>,
+}
+
+impl TideStations {
+    pub fn load(path: &Path) -> Result<TideStations> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let stations: TideStations =
+            toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))?;
+        for station in &stations.stations {
+            for constituent in &station.constituents {
+                if doodson(&constituent.name).is_none() {
+                    bail!(
+                        "Unknown tidal constituent {} at {}",
+                        constituent.name,
+                        station.name
+                    );
+                }
+            }
+        }
+        Ok(stations)
+    }
+
+    /// The station closest to a position and its distance in meters.
+    #[cfg(feature = "tui")]
+    pub fn nearest(&self, lat: f64, lon: f64) -> Option<(&Station, f64)> {
+        self.stations
+            .iter()
+            .map(|station| {
+                (
+                    station,
+                    geo::distance((lat, lon), (station.lat, station.lon)),
+                )
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+impl Station {
+    /// Predicted height above chart datum at `time`.
+    #[cfg(feature = "tui")]
+    pub fn height(&self, time: SystemTime) -> f64 {
+        let args = Astronomy::at(time);
+        self.datum
+            + self
+                .constituents
+                .iter()
+                .filter_map(|c| {
+                    let (numbers, offset) = doodson(&c.name)?;
+                    let (f, u) = args.nodal(&c.name);
+                    let v = args.equilibrium(numbers) + offset;
+                    Some(f * c.amplitude * (v + u - c.phase).to_radians().cos())
+                })
+                .sum::<f64>()
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn predict(&self, time: SystemTime) -> Prediction {
+        let height = self.height(time);
+        let rate = (self.height(time + Duration::from_secs(300)) - height) * 12.0;
+
+        let mut next_high = None;
+        let mut next_low = None;
+        let mut previous = (time, height);
+        let mut rising = rate > 0.0;
+        let mut at = time + SEARCH_STEP;
+        while at < time + SEARCH_HORIZON && (next_high.is_none() || next_low.is_none()) {
+            let h = self.height(at);
+            if rising && h < previous.1 && next_high.is_none() {
+                next_high = Some(previous);
+            } else if !rising && h > previous.1 && next_low.is_none() {
+                next_low = Some(previous);
+            }
+            rising = h > previous.1;
+            previous = (at, h);
+            at += SEARCH_STEP;
+        }
+
+        Prediction {
+            height,
+            rate,
+            next_high,
+            next_low,
+        }
+    }
+}
+
+/// Mean longitudes (degrees) of the Doodson arguments at a time.
+#[cfg(feature = "tui")]
+struct Astronomy {
+    /// Mean lunar time.
+    tau: f64,
+    /// Moon.
+    s: f64,
+    /// Sun.
+    h: f64,
+    /// Lunar perigee.
+    p: f64,
+    /// Lunar ascending node.
+    n: f64,
+    /// Solar perigee.
+    p1: f64,
+}
+
+#[cfg(feature = "tui")]
+impl Astronomy {
+    fn at(time: SystemTime) -> Astronomy {
+        let unix = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        // Julian centuries since J2000.0.
+        let t = (unix / 86_400.0 - 10_957.5) / 36_525.0;
+        let hours = (unix / 3600.0).rem_euclid(24.0);
+        let s = 218.3164 + 481_267.881_2 * t;
+        let h = 280.4661 + 36_000.769_8 * t;
+        Astronomy {
+            tau: 15.0 * hours + 180.0 + h - s,
+            s,
+            h,
+            p: 83.3535 + 4069.0137 * t,
+            n: 125.0445 - 1934.1363 * t,
+            p1: 282.9384 + 1.7195 * t,
+        }
+    }
+
+    fn equilibrium(&self, [a, b, c, d, e, f]: [i8; 6]) -> f64 {
+        // Doodson numbers multiply the negated node longitude.
+        [self.tau, self.s, self.h, self.p, -self.n, self.p1]
+            .iter()
+            .zip([a, b, c, d, e, f])
+            .map(|(arg, k)| arg * f64::from(k))
+            .sum()
+    }
+
+    /// Nodal amplitude factor and phase correction (degrees).
+    fn nodal(&self, name: &str) -> (f64, f64) {
+        let n = self.n.to_radians();
+        let sin = |k: f64| (k * n).sin();
+        let cos = |k: f64| (k * n).cos();
+        let m2 = (
+            1.0004 - 0.0373 * cos(1.0) + 0.0002 * cos(2.0),
+            -2.14 * sin(1.0),
+        );
+        let k1 = (
+            1.0060 + 0.1150 * cos(1.0) - 0.0088 * cos(2.0) + 0.0006 * cos(3.0),
+            -8.86 * sin(1.0) + 0.68 * sin(2.0) - 0.07 * sin(3.0),
+        );
+        let o1 = (
+            1.0089 + 0.1871 * cos(1.0) - 0.0147 * cos(2.0) + 0.0014 * cos(3.0),
+            10.80 * sin(1.0) - 1.34 * sin(2.0) + 0.19 * sin(3.0),
+        );
+        let k2 = (
+            1.0241 + 0.2863 * cos(1.0) + 0.0083 * cos(2.0) - 0.0015 * cos(3.0),
+            -17.74 * sin(1.0) + 0.68 * sin(2.0) - 0.04 * sin(3.0),
+        );
+        match name {
+            "M2" | "N2" | "2N2" | "NU2" | "MU2" | "L2" | "MS4" => m2,
+            "M4" | "MN4" => (m2.0 * m2.0, 2.0 * m2.1),
+            "M6" => (m2.0.powi(3), 3.0 * m2.1),
+            "K1" | "J1" => k1,
+            "O1" | "Q1" => o1,
+            "K2" => k2,
+            _ => (1.0, 0.0),
+        }
+    }
+}
+
+/// Doodson numbers and phase offset (degrees) of the supported constituents.
+fn doodson(name: &str) -> Option<([i8; 6], f64)> {
+    Some(match name {
+        "M2" => ([2, 0, 0, 0, 0, 0], 0.0),
+        "S2" => ([2, 2, -2, 0, 0, 0], 0.0),
+        "N2" => ([2, -1, 0, 1, 0, 0], 0.0),
+        "K2" => ([2, 2, 0, 0, 0, 0], 0.0),
+        "2N2" => ([2, -2, 0, 2, 0, 0], 0.0),
+        "NU2" => ([2, -1, 2, -1, 0, 0], 0.0),
+        "MU2" => ([2, -2, 2, 0, 0, 0], 0.0),
+        "L2" => ([2, 1, 0, -1, 0, 0], 180.0),
+        "K1" => ([1, 1, 0, 0, 0, 0], 90.0),
+        "O1" => ([1, -1, 0, 0, 0, 0], -90.0),
+        "P1" => ([1, 1, -2, 0, 0, 0], -90.0),
+        "Q1" => ([1, -2, 0, 1, 0, 0], -90.0),
+        "J1" => ([1, 2, 0, -1, 0, 0], 90.0),
+        "M4" => ([4, 0, 0, 0, 0, 0], 0.0),
+        "MS4" => ([4, 2, -2, 0, 0, 0], 0.0),
+        "MN4" => ([4, -1, 0, 1, 0, 0], 0.0),
+        "M6" => ([6, 0, 0, 0, 0, 0], 0.0),
+        "SA" | "Sa" => ([0, 0, 1, 0, 0, 0], 0.0),
+        "SSA" | "Ssa" => ([0, 0, 2, 0, 0, 0], 0.0),
+        _ => return None,
+    })
+}
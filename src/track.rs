@@ -0,0 +1,47 @@
+//! Own-ship position history.
+
+use std::collections::VecDeque;
+
+/// Number of positions kept for the track plot.
+const TRACK_LEN: usize = 3600;
+
+#[derive(Default, Debug)]
+pub struct Track {
+    /// Points with whether each starts a new segment.
+    points: VecDeque<((f64, f64), bool)>,
+    /// The next point starts a new segment.
+    split: bool,
+}
+
+impl Track {
+    pub fn push(&mut self, lat: f64, lon: f64) {
+        if self.points.len() == TRACK_LEN {
+            self.points.pop_front();
+        }
+        self.points
+            .push_back(((lat, lon), std::mem::take(&mut self.split)));
+    }
+
+    /// Starts a new segment with the next point.
+    pub fn split(&mut self) {
+        self.split = !self.points.is_empty();
+    }
+
+    /// `(lat, lon)` points, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &(f64, f64)> {
+        self.points.iter().map(|(point, _)| point)
+    }
+
+    /// Points split into segments, oldest first.
+    #[cfg(feature = "export")]
+    pub fn segments(&self) -> Vec<Vec<(f64, f64)>> {
+        let mut segments = Vec::<Vec<_>>::new();
+        for &(point, starts) in &self.points {
+            match segments.last_mut() {
+                Some(segment) if !starts => segment.push(point),
+                _ => segments.push(vec![point]),
+            }
+        }
+        segments
+    }
+}
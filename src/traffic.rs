@@ -0,0 +1,265 @@
+//! Air traffic reported by a FLARM in `$PFLAA` and `$PFLAU` sentences, and by ADS-B
+//! receivers in SBS messages.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::{geo, sbs::SbsMessage, sentence::Sentence};
+
+/// Collision warning level, by time to impact.
+#[derive(Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum AlarmLevel {
+    #[default]
+    None,
+    /// 13 to 18 seconds to impact.
+    Low,
+    /// 9 to 12 seconds.
+    Important,
+    /// Less than 9 seconds.
+    Urgent,
+}
+
+impl AlarmLevel {
+    fn parse(field: Option<&&str>) -> AlarmLevel {
+        match field.copied() {
+            Some("1") => Self::Low,
+            Some("2") => Self::Important,
+            Some("3") => Self::Urgent,
+            _ => Self::None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "-",
+            Self::Low => "low",
+            Self::Important => "important",
+            Self::Urgent => "urgent",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Aircraft {
+    /// FLARM or ICAO address in hex.
+    pub id: String,
+    pub callsign: Option<String>,
+    pub squawk: Option<String>,
+    /// Latitude and longitude, from ADS-B. FLARM only reports the relative position.
+    pub position: Option<(f64, f64)>,
+    /// Barometric altitude in meters, from ADS-B.
+    pub altitude: Option<f64>,
+    /// Meters east and north of own position.
+    pub relative: Option<(f64, f64)>,
+    /// Meters above own altitude.
+    pub relative_altitude: Option<f64>,
+    pub track: Option<f64>,
+    /// Ground speed in knots.
+    pub ground_speed: Option<f64>,
+    /// Climb rate in m/s.
+    pub climb_rate: Option<f64>,
+    pub kind: Option<&'static str>,
+    pub alarm: AlarmLevel,
+    pub updated_at: Instant,
+}
+
+impl Aircraft {
+    fn new(id: String) -> Aircraft {
+        Aircraft {
+            id,
+            callsign: None,
+            squawk: None,
+            position: None,
+            altitude: None,
+            relative: None,
+            relative_altitude: None,
+            track: None,
+            ground_speed: None,
+            climb_rate: None,
+            kind: None,
+            alarm: AlarmLevel::None,
+            updated_at: Instant::now(),
+        }
+    }
+
+    /// Horizontal distance in meters.
+    pub fn distance(&self) -> Option<f64> {
+        self.relative.map(|(x, y)| x.hypot(y))
+    }
+
+    /// True bearing from own position in degrees.
+    #[cfg(feature = "tui")]
+    pub fn bearing(&self) -> Option<f64> {
+        self.relative
+            .map(|(x, y)| x.atan2(y).to_degrees().rem_euclid(360.0))
+    }
+}
+
+/// The FLARM's own state and most important threat, from `$PFLAU`.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct FlarmStatus {
+    /// Devices received.
+    pub received: u32,
+    pub transmitting: bool,
+    pub gps: bool,
+    pub alarm: AlarmLevel,
+    /// Bearing of the most important threat relative to own track.
+    pub relative_bearing: Option<f64>,
+    /// Distance to the most important threat in meters.
+    pub distance: Option<f64>,
+    pub updated_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct Traffic {
+    aircraft: BTreeMap<String, Aircraft>,
+    flarm: Option<FlarmStatus>,
+    /// Aircraft silent for longer than this are dropped.
+    expiry: Duration,
+}
+
+impl Traffic {
+    pub fn new(expiry: Duration) -> Traffic {
+        Traffic {
+            aircraft: BTreeMap::new(),
+            flarm: None,
+            expiry,
+        }
+    }
+
+    pub fn update(&mut self, sentence: &Sentence) {
+        match sentence.kind {
+            "PFLAA" => self.update_target(&sentence.fields),
+            "PFLAU" => self.update_status(&sentence.fields),
+            _ => {}
+        }
+    }
+
+    /// AlarmLevel, RelativeNorth, RelativeEast, RelativeVertical, IDType, ID, Track,
+    /// TurnRate, GroundSpeed, ClimbRate, AcftType.
+    fn update_target(&mut self, fields: &[&str]) {
+        let number = |index: usize| fields.get(index).and_then(|f| f.parse::<f64>().ok());
+        let Some(id) = fields.get(5).filter(|id| !id.is_empty()) else {
+            return;
+        };
+        let aircraft = self
+            .aircraft
+            .entry(id.to_string())
+            .or_insert_with(|| Aircraft::new(id.to_string()));
+        aircraft.alarm = AlarmLevel::parse(fields.first());
+        // Bearingless targets leave north and east empty.
+        aircraft.relative = number(2).zip(number(1));
+        aircraft.relative_altitude = number(3);
+        aircraft.track = number(6);
+        aircraft.ground_speed = number(8).map(|speed| speed / geo::KNOT);
+        aircraft.climb_rate = number(9);
+        aircraft.kind = fields.get(10).and_then(|kind| aircraft_type(kind));
+        aircraft.updated_at = Instant::now();
+
+        let expiry = self.expiry;
+        self.aircraft
+            .retain(|_, aircraft| aircraft.updated_at.elapsed() < expiry);
+    }
+
+    /// Merges an ADS-B message into the aircraft with the same address, placing it
+    /// relative to `own` position and altitude.
+    pub fn update_sbs(&mut self, message: SbsMessage, own: Option<((f64, f64), Option<f64>)>) {
+        let aircraft = self
+            .aircraft
+            .entry(message.icao.clone())
+            .or_insert_with(|| Aircraft::new(message.icao));
+        aircraft.callsign = message.callsign.or(aircraft.callsign.take());
+        aircraft.squawk = message.squawk.or(aircraft.squawk.take());
+        aircraft.position = message.position.or(aircraft.position);
+        aircraft.altitude = message.altitude.or(aircraft.altitude);
+        aircraft.ground_speed = message.ground_speed.or(aircraft.ground_speed);
+        aircraft.track = message.track.or(aircraft.track);
+        aircraft.climb_rate = message.climb_rate.or(aircraft.climb_rate);
+        aircraft.updated_at = Instant::now();
+        if let Some(own) = own {
+            locate(aircraft, own);
+        }
+
+        let expiry = self.expiry;
+        self.aircraft
+            .retain(|_, aircraft| aircraft.updated_at.elapsed() < expiry);
+    }
+
+    /// Places ADS-B aircraft relative to `own` position and altitude again, as own
+    /// position changes between their messages.
+    pub fn locate(&mut self, own: ((f64, f64), Option<f64>)) {
+        for aircraft in self.aircraft.values_mut() {
+            locate(aircraft, own);
+        }
+    }
+
+    /// RX, TX, GPS, Power, AlarmLevel, RelativeBearing, AlarmType, RelativeVertical,
+    /// RelativeDistance, ID.
+    fn update_status(&mut self, fields: &[&str]) {
+        let number = |index: usize| fields.get(index).and_then(|f| f.parse::<f64>().ok());
+        self.flarm = Some(FlarmStatus {
+            received: fields.first().and_then(|f| f.parse().ok()).unwrap_or(0),
+            transmitting: fields.get(1) == Some(&"1"),
+            gps: matches!(fields.get(2), Some(&"1" | &"2")),
+            alarm: AlarmLevel::parse(fields.get(4)),
+            relative_bearing: number(5),
+            distance: number(8),
+            updated_at: Instant::now(),
+        });
+    }
+
+    /// Status of the FLARM, unless it went silent for the expiry time.
+    #[cfg(feature = "tui")]
+    pub fn flarm(&self) -> Option<&FlarmStatus> {
+        self.flarm
+            .as_ref()
+            .filter(|flarm| flarm.updated_at.elapsed() < self.expiry)
+    }
+
+    /// Aircraft heard from within the expiry time, closest first. Aircraft without a
+    /// position come last.
+    pub fn by_distance(&self) -> Vec<&Aircraft> {
+        let mut aircraft = self
+            .aircraft
+            .values()
+            .filter(|aircraft| aircraft.updated_at.elapsed() < self.expiry)
+            .collect::<Vec<_>>();
+        aircraft.sort_by(|a, b| match (a.distance(), b.distance()) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        aircraft
+    }
+}
+
+fn locate(aircraft: &mut Aircraft, (position, altitude): ((f64, f64), Option<f64>)) {
+    let Some(absolute) = aircraft.position else {
+        return;
+    };
+    aircraft.relative = Some(geo::local_xy(position, absolute));
+    aircraft.relative_altitude = aircraft.altitude.zip(altitude).map(|(a, own)| a - own);
+}
+
+/// Aircraft type from the hex digit in `$PFLAA`.
+fn aircraft_type(kind: &str) -> Option<&'static str> {
+    Some(match kind {
+        "1" => "glider",
+        "2" => "tow plane",
+        "3" => "helicopter",
+        "4" => "skydiver",
+        "5" => "drop plane",
+        "6" => "hang glider",
+        "7" => "paraglider",
+        "8" => "powered",
+        "9" => "jet",
+        "B" => "balloon",
+        "C" => "airship",
+        "D" => "drone",
+        "F" => "obstacle",
+        _ => return None,
+    })
+}
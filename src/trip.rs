@@ -0,0 +1,36 @@
+//! Splitting of the track and the recording into trips: on a new day in the zone times
+//! are shown in, with `--split-daily`, or when no fix came for `--split-gap`.
+
+use std::time::{Duration, SystemTime};
+
+use crate::clock;
+
+#[derive(Debug)]
+pub struct Trips {
+    daily: bool,
+    gap: Option<Duration>,
+    /// Time and date of the last fix.
+    last: Option<(SystemTime, String)>,
+}
+
+impl Trips {
+    pub fn new(daily: bool, gap: Option<Duration>) -> Trips {
+        Trips {
+            daily,
+            gap,
+            last: None,
+        }
+    }
+
+    /// Records a fix at `time`, the receiver's time when known so that logs split as they
+    /// would have live. Returns whether it starts a new trip.
+    pub fn on_fix(&mut self, time: SystemTime) -> bool {
+        let date = clock::date(time);
+        let new = self.last.as_ref().is_some_and(|(last, last_date)| {
+            let gap = time.duration_since(*last).unwrap_or_default();
+            (self.daily && *last_date != date) || self.gap.is_some_and(|max| gap > max)
+        });
+        self.last = Some((time, date));
+        new
+    }
+}
@@ -0,0 +1,106 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize as _},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::{map, or_dash, target_title};
+use crate::{geo, i18n::tr, status::NmeaStatus};
+
+pub fn draw(
+    frame: &mut Frame,
+    area: Rect,
+    nmea: &NmeaStatus,
+    state: &mut TableState,
+    course_up: bool,
+    view: &mut map::MapView,
+    rings: Option<f64>,
+) {
+    let [left, canvas] =
+        Layout::horizontal([Constraint::Length(82), Constraint::Min(0)]).areas(area);
+    let [table, detail] = Layout::vertical([Constraint::Min(0), Constraint::Length(4)]).areas(left);
+
+    let own = nmea.own_ship();
+    let targets = nmea.ais.by_cpa(own.as_ref());
+    let rows = targets.iter().map(|(target, cpa)| {
+        let row = Row::new([
+            target.mmsi.to_string(),
+            or_dash(target.name.as_deref()),
+            or_dash(target.lat.map(|v| nmea.coordinates.lat(v))),
+            or_dash(target.lon.map(|v| nmea.coordinates.lon(v))),
+            or_dash(target.sog),
+            or_dash(target.cog),
+            or_dash(cpa.map(|cpa| format!("{:.2}", cpa.distance / geo::NAUTICAL_MILE))),
+            or_dash(cpa.map(|cpa| format!("{:.1}", cpa.time / 60.0))),
+        ]);
+        if cpa.is_some_and(|cpa| nmea.ais.is_dangerous(&cpa)) {
+            row.fg(Color::LightRed)
+        } else {
+            row
+        }
+    });
+    let widths = [
+        Constraint::Length(9),  // mmsi
+        Constraint::Length(15), // name
+        Constraint::Length(16), // lat
+        Constraint::Length(16), // lon
+        Constraint::Length(6),  // sog
+        Constraint::Length(6),  // cog
+        Constraint::Length(6),  // cpa
+        Constraint::Length(6),  // tcpa
+    ];
+    let table_widget = Table::new(rows, widths)
+        .header(
+            Row::new([
+                "mmsi", "name", "lat", "lon", "sog", "cog", "cpa nm", "tcpa m",
+            ])
+            .bold(),
+        )
+        .highlight_style(Style::new().reversed())
+        .block(Block::new().title(Line::raw(tr("ais targets (↑/↓ to select)"))));
+    frame.render_stateful_widget(table_widget, table, state);
+
+    let selected = state
+        .selected()
+        .and_then(|i| targets.get(i.min(targets.len().saturating_sub(1))))
+        .map(|(target, _)| target);
+    if let Some(target) = selected {
+        let lines = [
+            format!(
+                "{}  call sign {}  type {}",
+                or_dash(target.name.as_deref()),
+                or_dash(target.call_sign.as_deref()),
+                or_dash(target.ship_type),
+            ),
+            format!(
+                "{}  destination {}",
+                target
+                    .dimensions
+                    .map_or_else(|| "-".to_string(), |(l, b)| format!("{l} m x {b} m")),
+                or_dash(target.destination.as_deref()),
+            ),
+        ];
+        let mut block = Block::new().title(target.mmsi.to_string());
+        if let (Some(own), Some(position)) = (&own, target.position()) {
+            let (bearing, distance) = geo::course_and_length(geo::local_xy(own.position, position));
+            block = block.title(target_title(
+                nmea,
+                &target.mmsi.to_string(),
+                bearing,
+                format!("{:.2} NM", distance / geo::NAUTICAL_MILE),
+            ));
+        }
+        frame.render_widget(Paragraph::new(lines.join("\n")).block(block), detail);
+    }
+    map::draw(
+        frame,
+        canvas,
+        nmea,
+        selected.map(|target| target.mmsi),
+        course_up,
+        view,
+        rings,
+    );
+}
@@ -0,0 +1,38 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Stylize as _},
+    text::Line,
+    widgets::{Block, Row, Table},
+    Frame,
+};
+
+use super::{clock, or_dash};
+use crate::{i18n::tr, status::NmeaStatus};
+
+/// Alarms in the banner and past ones, newest first, with when they were raised, how long
+/// they lasted and when they were acknowledged.
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus) {
+    let rows = nmea.alarms.iter().map(|alarm| {
+        let duration = std::time::Duration::from_secs(alarm.duration().as_secs());
+        let row = Row::new([
+            clock(alarm.raised),
+            humantime::format_duration(duration).to_string(),
+            or_dash(alarm.acknowledged.map(clock)),
+            alarm.text.clone(),
+        ]);
+        match alarm.cleared {
+            Some(_) => row,
+            None => row.fg(Color::LightRed),
+        }
+    });
+    let widths = [
+        Constraint::Length(9),  // raised
+        Constraint::Length(12), // duration
+        Constraint::Length(9),  // acknowledged
+        Constraint::Min(0),     // alert
+    ];
+    let table = Table::new(rows, widths)
+        .header(Row::new(["raised", "lasted", "ack", "alert"].map(tr)).bold())
+        .block(Block::new().title(Line::raw(tr("alarms (A to acknowledge)"))));
+    frame.render_widget(table, area);
+}
@@ -0,0 +1,115 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize as _,
+    text::{Line, Text},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::status::NmeaStatus;
+
+/// Value shown filling the screen, cycled with `b`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BigValue {
+    Sog,
+    Stw,
+    Depth,
+    Cog,
+}
+
+impl BigValue {
+    /// The value after this one, or `None` to leave the big display.
+    pub fn next(self) -> Option<BigValue> {
+        match self {
+            Self::Sog => Some(Self::Stw),
+            Self::Stw => Some(Self::Depth),
+            Self::Depth => Some(Self::Cog),
+            Self::Cog => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Sog => "sog (kn)",
+            Self::Stw => "stw (kn)",
+            Self::Depth => "depth (m)",
+            Self::Cog => "cog (°)",
+        }
+    }
+
+    fn text(self, nmea: &NmeaStatus) -> String {
+        let value = match self {
+            Self::Sog => nmea.sog.get(),
+            Self::Stw => nmea.stw.get(),
+            Self::Depth => nmea.depth.get(),
+            Self::Cog => nmea.cog.get(),
+        };
+        match (self, value) {
+            (_, None) => "--".to_string(),
+            (Self::Cog, Some(v)) => format!("{v:03.0}"),
+            (_, Some(v)) => format!("{v:.1}"),
+        }
+    }
+}
+
+/// Height of a glyph in pixels.
+const GLYPH_HEIGHT: usize = 5;
+
+/// 3×5 pixel glyphs, `#` set.
+fn glyph(c: char) -> [&'static str; GLYPH_HEIGHT] {
+    match c {
+        '0' => ["###", "# #", "# #", "# #", "###"],
+        '1' => [" # ", "## ", " # ", " # ", "###"],
+        '2' => ["###", "  #", "###", "#  ", "###"],
+        '3' => ["###", "  #", "###", "  #", "###"],
+        '4' => ["# #", "# #", "###", "  #", "  #"],
+        '5' => ["###", "#  ", "###", "  #", "###"],
+        '6' => ["###", "#  ", "###", "# #", "###"],
+        '7' => ["###", "  #", "  #", "  #", "  #"],
+        '8' => ["###", "# #", "###", "# #", "###"],
+        '9' => ["###", "# #", "###", "  #", "###"],
+        '.' => ["   ", "   ", "   ", "   ", " # "],
+        '-' => ["   ", "   ", "###", "   ", "   "],
+        _ => ["   ", "   ", "   ", "   ", "   "],
+    }
+}
+
+/// Draws `value` in block digits as large as fits in `area`, with its label below.
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, value: BigValue) {
+    let text = value.text(nmea);
+    let glyphs = text.chars().map(glyph).collect::<Vec<_>>();
+    // One pixel column between glyphs.
+    let pixels_wide = glyphs.len() * 4 - 1;
+
+    let [digits, label] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+    // Terminal cells are about twice as tall as wide, so pixels are two cells wide.
+    let scale = (usize::from(digits.height) / GLYPH_HEIGHT)
+        .min(usize::from(digits.width) / (pixels_wide * 2))
+        .max(1);
+
+    let mut lines = Vec::new();
+    for row in 0..GLYPH_HEIGHT {
+        let mut line = String::new();
+        for (i, glyph) in glyphs.iter().enumerate() {
+            if i > 0 {
+                line.push_str(&" ".repeat(scale * 2));
+            }
+            for pixel in glyph[row].chars() {
+                let cell = if pixel == '#' { '█' } else { ' ' };
+                line.extend(std::iter::repeat_n(cell, scale * 2));
+            }
+        }
+        lines.extend(std::iter::repeat_n(Line::from(line), scale));
+    }
+
+    let [digits] = Layout::vertical([Constraint::Length((GLYPH_HEIGHT * scale) as u16)])
+        .flex(Flex::Center)
+        .areas(digits);
+    frame.render_widget(Paragraph::new(Text::from(lines)).centered(), digits);
+    frame.render_widget(
+        Paragraph::new(format!("{} (b for next)", value.label()))
+            .centered()
+            .dark_gray(),
+        label,
+    );
+}
@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize as _},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{Axis, Block, Chart, Dataset, GraphType},
+    Frame,
+};
+
+use crate::{
+    history::Series,
+    i18n::{self, tr},
+    status::NmeaStatus,
+};
+
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, span: Duration, by_distance: bool) {
+    let history = &nmea.history;
+    let has_errors = [&history.sigma_lat, &history.sigma_lon, &history.sigma_alt]
+        .iter()
+        .any(|series| series.recent(span).next().is_some());
+    let [quality, errors, altitude] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Fill(if has_errors { 1 } else { 0 }),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+    draw_quality(frame, quality, nmea, span);
+    if has_errors {
+        draw_errors(frame, errors, nmea, span);
+    }
+    draw_altitude(frame, altitude, nmea, span, by_distance);
+}
+
+/// GST error estimates, one line each for latitude, longitude and altitude.
+fn draw_errors(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, span: Duration) {
+    let history = &nmea.history;
+    let series = [
+        ("σ lat", &history.sigma_lat, Color::Cyan),
+        ("σ lon", &history.sigma_lon, Color::Yellow),
+        ("σ alt", &history.sigma_alt, Color::Green),
+    ]
+    .map(|(name, series, color)| (name, points(series, span), color));
+    let top = series
+        .iter()
+        .flat_map(|(_, points, _)| points)
+        .map(|&(_, y)| y)
+        .fold(1.0, f64::max)
+        .ceil();
+
+    let datasets = series
+        .iter()
+        .map(|(name, points, color)| {
+            Dataset::default()
+                .name(*name)
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::new().fg(*color))
+                .data(points)
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        Chart::new(datasets)
+            .block(Block::new().title(Line::raw(tr("error estimates (m)"))))
+            .x_axis(time_axis(span))
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, top])
+                    .labels(["0".to_string(), format!("{top:.0}")]),
+            ),
+        area,
+    );
+}
+
+/// HDOP and satellites used on one time axis; dips in fix quality show as HDOP rising
+/// while the satellite count drops.
+fn draw_quality(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, span: Duration) {
+    let hdop = points(&nmea.history.hdop, span);
+    let satellites = points(&nmea.history.satellites, span);
+    let top = hdop
+        .iter()
+        .chain(&satellites)
+        .map(|&(_, y)| y)
+        .fold(1.0, f64::max)
+        .ceil();
+
+    let datasets = vec![
+        Dataset::default()
+            .name(Line::raw(tr("satellites used")))
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().fg(Color::Cyan))
+            .data(&satellites),
+        Dataset::default()
+            .name("HDOP")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().fg(Color::Yellow))
+            .data(&hdop),
+    ];
+    frame.render_widget(
+        Chart::new(datasets)
+            .block(Block::new().title(Line::raw(tr("fix quality"))))
+            .x_axis(time_axis(span))
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, top])
+                    .labels(["0".to_string(), format!("{top:.0}")]),
+            ),
+        area,
+    );
+}
+
+/// Altitude against time, or against the distance travelled in the same span.
+fn draw_altitude(
+    frame: &mut Frame,
+    area: Rect,
+    nmea: &NmeaStatus,
+    span: Duration,
+    by_distance: bool,
+) {
+    let history = &nmea.history;
+    let (data, x_axis) = if by_distance {
+        let data = history
+            .travelled
+            .join(&history.alt, span)
+            .map(|(travelled, alt)| (travelled / 1000.0, alt))
+            .collect::<Vec<_>>();
+        let start = data.first().map_or(0.0, |&(x, _)| x);
+        let end = data.last().map_or(1.0, |&(x, _)| x).max(start + 0.1);
+        let axis = Axis::default()
+            .title(Span::from("km").dark_gray())
+            .bounds([start, end])
+            .labels([format!("{start:.1}"), format!("{end:.1}")]);
+        (data, axis)
+    } else {
+        (points(&history.alt, span), time_axis(span))
+    };
+
+    let min = data.iter().map(|&(_, y)| y).reduce(f64::min);
+    let max = data.iter().map(|&(_, y)| y).reduce(f64::max);
+    let title = match min.zip(max) {
+        Some((min, max)) => i18n::format(
+            "altitude, min {} m, max {} m",
+            &[&format!("{min:.1}"), &format!("{max:.1}")],
+        ),
+        None => tr("altitude").into_owned(),
+    };
+    let toggle = if by_distance {
+        "d for time"
+    } else {
+        "d for distance"
+    };
+    let title = format!("{title} ({})", tr(toggle));
+    // Padded so that the extremes do not sit on the frame.
+    let (low, high) = match min.zip(max) {
+        Some((min, max)) => {
+            let margin = ((max - min) * 0.1).max(1.0);
+            (min - margin, max + margin)
+        }
+        None => (0.0, 1.0),
+    };
+
+    let datasets = vec![Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::new().fg(Color::Green))
+        .data(&data)];
+    frame.render_widget(
+        Chart::new(datasets)
+            .block(Block::new().title(title))
+            .x_axis(x_axis)
+            .y_axis(
+                Axis::default()
+                    .bounds([low, high])
+                    .labels([format!("{low:.0}"), format!("{high:.0}")]),
+            ),
+        area,
+    );
+}
+
+/// Samples within `span` as (minutes ago, negative, value).
+fn points(series: &Series, span: Duration) -> Vec<(f64, f64)> {
+    series
+        .recent(span)
+        .map(|(age, value)| (-age.as_secs_f64() / 60.0, value))
+        .collect()
+}
+
+fn time_axis(span: Duration) -> Axis<'static> {
+    let minutes = span.as_secs_f64() / 60.0;
+    Axis::default()
+        .title(Span::from("min").dark_gray())
+        .bounds([-minutes, 0.0])
+        .labels([
+            format!("-{minutes:.0}"),
+            format!("-{:.0}", minutes / 2.0),
+            "0".to_string(),
+        ])
+}
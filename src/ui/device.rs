@@ -0,0 +1,62 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize as _},
+    text::Line,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use super::clock;
+use crate::{i18n::tr, status::NmeaStatus};
+
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, writable: bool) {
+    let [sent, received] =
+        Layout::vertical([Constraint::Percentage(40), Constraint::Min(0)]).areas(area);
+
+    let title = if writable {
+        "sent commands (: to send)"
+    } else {
+        "sent commands (start with --writable to send)"
+    };
+    let commands = nmea
+        .device
+        .sent_commands()
+        .map(|command| {
+            let line = Line::from(format!(
+                "{} {}  {}",
+                clock(command.sent_at),
+                command.raw,
+                command
+                    .reply
+                    .as_ref()
+                    .map_or("waiting for reply", |reply| reply.text.as_str()),
+            ));
+            match &command.reply {
+                Some(reply) if reply.ok => line.fg(Color::Green),
+                Some(_) => line.fg(Color::LightRed),
+                None => line,
+            }
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        Paragraph::new(commands).block(Block::new().title(Line::raw(tr(title)))),
+        sent,
+    );
+
+    let messages = nmea
+        .device
+        .received()
+        .map(|message| {
+            Line::from(format!(
+                "{} {}  {}",
+                clock(message.received_at),
+                message.raw,
+                message.description.as_deref().unwrap_or_default(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        Paragraph::new(messages).block(Block::new().title(Line::raw(tr("device messages")))),
+        received,
+    );
+}
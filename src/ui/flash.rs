@@ -0,0 +1,51 @@
+//! Highlighting of status values for a moment after they change, so that it is obvious at
+//! a glance which are updating, such as heading against COG.
+
+use std::{collections::HashMap, time::Duration};
+
+use ratatui::{style::Stylize as _, text::Text};
+use tokio::time::Instant;
+
+#[derive(Default, Debug)]
+pub struct Flash {
+    duration: Duration,
+    /// Text last shown in each cell by id, and when it last changed.
+    shown: HashMap<String, (String, Option<Instant>)>,
+}
+
+impl Flash {
+    /// Highlights changed values for `duration`, or never if it is zero.
+    pub fn new(duration: Duration) -> Flash {
+        Flash {
+            duration,
+            shown: HashMap::new(),
+        }
+    }
+
+    /// `value` of the cell `id`, reversed if it changed within the duration. Called on
+    /// every draw, which drives the highlight.
+    pub fn apply(&mut self, id: &str, value: Text<'static>) -> Text<'static> {
+        if self.duration.is_zero() {
+            return value;
+        }
+        let text = value.to_string();
+        let changed = match self.shown.get_mut(id) {
+            Some((shown, changed)) => {
+                if *shown != text {
+                    *shown = text;
+                    *changed = Some(Instant::now());
+                }
+                *changed
+            }
+            None => {
+                self.shown.insert(id.to_string(), (text, None));
+                None
+            }
+        };
+        if changed.is_some_and(|at| at.elapsed() < self.duration) {
+            value.reversed()
+        } else {
+            value
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::ops::Range;
+
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style, Stylize as _},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Row, Table},
+    Frame,
+};
+use regex::Regex;
+
+use super::clock;
+use crate::{
+    i18n::{self, tr},
+    raw_log::{self, RawLog},
+    reference::{self, Decoded},
+    status::NmeaStatus,
+};
+
+/// Prefix of search patterns that are regular expressions.
+const REGEX_PREFIX: &str = "re:";
+
+#[derive(Debug)]
+pub enum Search {
+    Plain(String),
+    Regex(Regex),
+}
+
+impl Search {
+    /// `re:` followed by a regular expression, or a plain substring.
+    pub fn new(pattern: &str) -> Result<Search, regex::Error> {
+        Ok(match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => Self::Regex(Regex::new(regex)?),
+            None => Self::Plain(pattern.to_string()),
+        })
+    }
+
+    fn matches(&self, text: &str) -> Vec<Range<usize>> {
+        match self {
+            Self::Plain(pattern) if pattern.is_empty() => Vec::new(),
+            Self::Plain(pattern) => text
+                .match_indices(pattern.as_str())
+                .map(|(start, found)| start..start + found.len())
+                .collect(),
+            Self::Regex(regex) => regex
+                .find_iter(text)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+        }
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        !self.matches(text).is_empty()
+    }
+}
+
+/// Scroll position, search and pinned sentences of the raw log screen.
+#[derive(Default, Debug)]
+pub struct LogView {
+    /// Number of the selected line; the view follows new lines when there is none.
+    pub selected: Option<u64>,
+    pub search: Option<Search>,
+    /// Sentence addresses whose latest line is shown above the log.
+    pub pinned: Vec<String>,
+    /// Fields of the selected line, shown in a popup.
+    pub decoding: Option<Decoded>,
+}
+
+impl LogView {
+    pub fn new(pinned: Vec<String>) -> LogView {
+        LogView {
+            pinned,
+            ..Default::default()
+        }
+    }
+
+    /// Decodes the selected line for the popup. Returns whether it could be.
+    pub fn decode(&mut self, log: &RawLog) -> bool {
+        self.decoding = self
+            .selected
+            .and_then(|number| log.iter().nth(log.index_of(number)?))
+            .and_then(|line| reference::decode(&line.text));
+        self.decoding.is_some()
+    }
+
+    /// Pins the address of the selected line, or unpins it if it is pinned. Returns the
+    /// address and whether it is now pinned.
+    pub fn toggle_pin(&mut self, log: &RawLog) -> Option<(String, bool)> {
+        let index = log.index_of(self.selected?)?;
+        let address = raw_log::address(&log.iter().nth(index)?.text)?;
+        match self.pinned.iter().position(|pinned| *pinned == address) {
+            Some(position) => {
+                self.pinned.remove(position);
+                Some((address, false))
+            }
+            None => {
+                self.pinned.push(address.clone());
+                Some((address, true))
+            }
+        }
+    }
+
+    /// Selects the previous line, starting from the newest when following.
+    pub fn select_previous(&mut self, log: &RawLog) {
+        let index = match self.selected.and_then(|number| log.index_of(number)) {
+            Some(index) => index.saturating_sub(1),
+            None => log.iter().len().saturating_sub(1),
+        };
+        self.selected = log.iter().nth(index).map(|line| line.number);
+    }
+
+    /// Selects the next line, or follows new lines again past the newest.
+    pub fn select_next(&mut self, log: &RawLog) {
+        self.selected = self
+            .selected
+            .and_then(|number| log.index_of(number))
+            .and_then(|index| log.iter().nth(index + 1))
+            .map(|line| line.number);
+    }
+
+    /// Selects the closest match older than the selection, or the newest match when
+    /// following. Returns whether there was one.
+    pub fn find_previous(&mut self, log: &RawLog) -> bool {
+        let Some(search) = &self.search else {
+            return false;
+        };
+        let before = self
+            .selected
+            .and_then(|number| log.index_of(number))
+            .unwrap_or(log.iter().len());
+        let found = log
+            .iter()
+            .take(before)
+            .rev()
+            .find(|line| search.is_match(&line.text));
+        self.select_found(found.map(|line| line.number))
+    }
+
+    /// Selects the closest match newer than the selection.
+    pub fn find_next(&mut self, log: &RawLog) -> bool {
+        let Some(search) = &self.search else {
+            return false;
+        };
+        let Some(after) = self.selected.and_then(|number| log.index_of(number)) else {
+            return false;
+        };
+        let found = log
+            .iter()
+            .skip(after + 1)
+            .find(|line| search.is_match(&line.text));
+        self.select_found(found.map(|line| line.number))
+    }
+
+    fn select_found(&mut self, found: Option<u64>) -> bool {
+        if found.is_some() {
+            self.selected = found;
+        }
+        found.is_some()
+    }
+}
+
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, view: &LogView) {
+    let log = &nmea.raw_log;
+    let pinned_height = if view.pinned.is_empty() {
+        0
+    } else {
+        view.pinned.len() as u16 + 1
+    };
+    let [pinned, area] =
+        Layout::vertical([Constraint::Length(pinned_height), Constraint::Min(0)]).areas(area);
+
+    let width = view
+        .pinned
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or_default();
+    let pinned_lines = view
+        .pinned
+        .iter()
+        .map(|address| match log.latest(address) {
+            Some(line) => {
+                let mut spans = vec![Span::raw(format!(
+                    "{address:width$} {} ",
+                    clock(line.received_at)
+                ))];
+                spans.extend(diff(
+                    &line.text,
+                    log.previous(address).map(|line| line.text.as_str()),
+                ));
+                Line::from(spans)
+            }
+            None => Line::from(format!("{address:width$} -")).dark_gray(),
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        Paragraph::new(pinned_lines).block(Block::new().title(Line::raw(tr(
+            "pinned (p to pin or unpin, changed fields highlighted)",
+        )))),
+        pinned,
+    );
+
+    let height = area.height.saturating_sub(1) as usize;
+    let len = log.iter().len();
+    let selected = view.selected.and_then(|number| log.index_of(number));
+    // Keep the selection in the middle, or show the newest lines.
+    let end = match selected {
+        Some(index) => (index + height / 2 + 1).max(height).min(len),
+        None => len,
+    };
+    let start = end.saturating_sub(height);
+
+    let lines = log
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(end - start)
+        .map(|(index, line)| {
+            let mut spans = vec![Span::raw(format!("{} ", clock(line.received_at)))];
+            spans.extend(highlight(&line.text, view.search.as_ref()));
+            let line = Line::from(spans);
+            if selected == Some(index) {
+                line.reversed()
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let title = match (&view.search, selected) {
+        (Some(_), _) => "raw log (/ search, n/N older/newer match, ↑↓ scroll, p pin, Enter decode)",
+        (None, Some(_)) => {
+            "raw log (/ search, ↑↓ scroll, ↓ past the end to follow, p pin, Enter decode)"
+        }
+        (None, None) => "raw log (/ search, ↑ to scroll back)",
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::new().title(Line::raw(tr(title)))),
+        area,
+    );
+
+    if let Some(decoded) = &view.decoding {
+        draw_decoded(frame, area, decoded);
+    }
+}
+
+fn draw_decoded(frame: &mut Frame, area: Rect, decoded: &Decoded) {
+    let [popup] = Layout::vertical([Constraint::Length(decoded.fields.len() as u16 + 3)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::horizontal([Constraint::Length(72)])
+        .flex(Flex::Center)
+        .areas(popup);
+    let rows = decoded.fields.iter().enumerate().map(|(i, field)| {
+        Row::new([
+            (i + 1).to_string(),
+            field.name.to_string(),
+            field.raw.clone(),
+            field.value.clone().unwrap_or_default(),
+        ])
+    });
+    let title = match decoded.description {
+        Some(description) => format!(
+            "{} {description} ({})",
+            decoded.address,
+            tr("Enter to close")
+        ),
+        None => i18n::format("{} not in reference (Enter to close)", &[&decoded.address]),
+    };
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Length(24),
+                Constraint::Length(16),
+                Constraint::Min(0),
+            ],
+        )
+        .header(Row::new(["#", "field", "raw", "value"].map(tr)).bold())
+        .block(Block::bordered().title(title)),
+        popup,
+    );
+}
+
+/// Splits `text` into spans with the matches of `search` highlighted.
+fn highlight<'a>(text: &'a str, search: Option<&Search>) -> Vec<Span<'a>> {
+    let Some(search) = search else {
+        return vec![Span::raw(text)];
+    };
+    let mut spans = Vec::new();
+    let mut at = 0;
+    for range in search.matches(text) {
+        spans.push(Span::raw(&text[at..range.start]));
+        spans.push(Span::styled(
+            &text[range.clone()],
+            Style::new().fg(Color::Black).bg(Color::Yellow),
+        ));
+        at = range.end;
+    }
+    spans.push(Span::raw(&text[at..]));
+    spans
+}
+
+/// Splits `text` into spans with the comma-separated fields that differ from `previous`
+/// highlighted. The checksum is left out of the comparison.
+fn diff<'a>(text: &'a str, previous: Option<&str>) -> Vec<Span<'a>> {
+    let Some(previous) = previous else {
+        return vec![Span::raw(text)];
+    };
+    let (body, checksum) = split_checksum(text);
+    let mut previous = split_checksum(previous).0.split(',');
+    let mut spans = Vec::new();
+    for (i, field) in body.split(',').enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(","));
+        }
+        if previous.next() == Some(field) {
+            spans.push(Span::raw(field));
+        } else {
+            spans.push(Span::raw(field).fg(Color::Yellow).bold());
+        }
+    }
+    spans.push(Span::raw(checksum));
+    spans
+}
+
+/// `$GPGGA,...*47` → (`$GPGGA,...`, `*47`).
+fn split_checksum(text: &str) -> (&str, &str) {
+    text.find('*').map_or((text, ""), |at| text.split_at(at))
+}
@@ -0,0 +1,382 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Stylize as _},
+    symbols::Marker,
+    text::Span,
+    widgets::{
+        canvas::{Canvas, Circle, Context, Line, Points},
+        Block,
+    },
+    Frame,
+};
+
+use crate::{
+    accuracy::Ellipse,
+    geo,
+    i18n::{self, tr},
+    overlay::Overlay,
+    status::NmeaStatus,
+};
+
+/// Smallest distance from own ship to the edge of the map.
+const MIN_RANGE: f64 = 500.0;
+
+/// Length of the course/speed vectors drawn for moving targets, in seconds of travel.
+#[cfg(feature = "ais")]
+const VECTOR_SECONDS: f64 = 360.0;
+
+/// Closest and farthest zoom, in meters from the center to the nearer edge.
+const MIN_ZOOM: f64 = 20.0;
+const MAX_ZOOM: f64 = 500_000.0;
+
+/// Most range rings drawn, when the interval is small for the zoom.
+const MAX_RINGS: u32 = 12;
+
+/// Segments the error ellipse is drawn with.
+const ELLIPSE_SEGMENTS: usize = 48;
+
+/// A target's MMSI, position and course/speed vector in map coordinates.
+type Target = (u32, (f64, f64), Option<(f64, f64)>);
+
+/// Zoom and pan of the map, changed with `+`/`-` and the arrow keys (with shift on the
+/// AIS screen).
+#[derive(Default, Debug)]
+pub struct MapView {
+    /// Meters from the center to the nearer edge, fitting the track and targets when not
+    /// set.
+    range: Option<f64>,
+    /// Position at the center once panned. The map follows own position when not set.
+    center: Option<(f64, f64)>,
+    /// Range, center and course up of the last draw, which zooming and panning start from.
+    shown: Option<(f64, (f64, f64), Option<f64>)>,
+    /// Reference lines from the config, drawn under the track.
+    overlay: Overlay,
+}
+
+impl MapView {
+    pub fn zoom_in(&mut self) {
+        self.zoom(0.5);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom(2.0);
+    }
+
+    fn zoom(&mut self, factor: f64) {
+        if let Some((range, _, _)) = self.shown {
+            self.range = Some((range * factor).clamp(MIN_ZOOM, MAX_ZOOM));
+        }
+    }
+
+    /// Zooms to fit the track and targets again.
+    pub fn fit(&mut self) {
+        self.range = None;
+    }
+
+    /// Moves the center a quarter of the range in a direction on screen, `(1, 0)` being
+    /// right and `(0, 1)` up. Stops following own position.
+    pub fn pan(&mut self, direction: (f64, f64)) {
+        let Some((range, center, up)) = self.shown else {
+            return;
+        };
+        let (x, y) = up.map_or(direction, |cog| geo::rotate(direction, -cog));
+        self.center = Some(geo::from_local_xy(
+            center,
+            (x * range / 4.0, y * range / 4.0),
+        ));
+    }
+
+    pub fn set_overlay(&mut self, overlay: Overlay) {
+        self.overlay = overlay;
+    }
+
+    /// Centers on own position and follows it again.
+    pub fn follow(&mut self) {
+        self.center = None;
+    }
+}
+
+/// Draws own track, the estimated position error and AIS targets over the overlay of
+/// `view`, north up or, when `course_up` is set and COG is known, rotated so that own
+/// course points up. The map is centered on own position unless panned with `view`.
+pub fn draw(
+    frame: &mut Frame,
+    area: Rect,
+    nmea: &NmeaStatus,
+    selected: Option<u32>,
+    course_up: bool,
+    view: &mut MapView,
+    rings: Option<f64>,
+) {
+    let up = nmea.cog.get().copied().filter(|_| course_up);
+    let mut title = match up {
+        Some(cog) => i18n::format("map (course up {}°)", &[&format!("{cog:.0}")]),
+        None => tr("map (north up)").into_owned(),
+    };
+    if let Some(range) = view.range {
+        title += &format!(" {} {}", tr("range"), scale(range));
+    }
+    if view.center.is_some() {
+        title += &format!(" · {}", tr("F to follow"));
+    }
+    let block = Block::bordered().title(title);
+    let (Some(&lat), Some(&lon)) = (nmea.lat.get(), nmea.lon.get()) else {
+        frame.render_widget(block, area);
+        return;
+    };
+    let origin = view.center.unwrap_or((lat, lon));
+    let orient = |xy| up.map_or(xy, |cog| geo::rotate(xy, cog));
+    let (own_x, own_y) = orient(geo::local_xy(origin, (lat, lon)));
+
+    let track = nmea
+        .track
+        .iter()
+        .map(|&point| orient(geo::local_xy(origin, point)))
+        .collect::<Vec<_>>();
+    let ellipse = nmea.accuracy.error_ellipse().map(|ellipse| {
+        outline(ellipse)
+            .map(|xy| {
+                let (x, y) = orient(xy);
+                (own_x + x, own_y + y)
+            })
+            .collect::<Vec<_>>()
+    });
+    #[cfg(feature = "ais")]
+    let targets =
+        nmea.ais
+            .iter()
+            .filter_map(|target| {
+                let (x, y) = orient(geo::local_xy(origin, target.position()?));
+                let vector = target.sog.zip(target.cog).map(|(sog, cog)| {
+                    orient(geo::displacement(cog, sog * geo::KNOT * VECTOR_SECONDS))
+                });
+                Some((target.mmsi, (x, y), vector))
+            })
+            .collect::<Vec<Target>>();
+    #[cfg(not(feature = "ais"))]
+    let targets = Vec::<Target>::new();
+
+    let range = view.range.unwrap_or_else(|| {
+        track
+            .iter()
+            .chain(targets.iter().map(|(_, position, _)| position))
+            .map(|(x, y)| x.abs().max(y.abs()))
+            .fold(MIN_RANGE, f64::max)
+            * 1.2
+    });
+    view.shown = Some((range, origin, up));
+    let (x_bounds, y_bounds) = bounds(block.inner(area), range);
+
+    let canvas = Canvas::default()
+        .block(block)
+        .marker(Marker::Braille)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            for line in &view.overlay.lines {
+                let mut points = line
+                    .iter()
+                    .map(|&point| orient(geo::local_xy(origin, point)));
+                let Some(mut from) = points.next() else {
+                    continue;
+                };
+                if line.len() == 1 {
+                    ctx.draw(&Points {
+                        coords: &[from],
+                        color: Color::DarkGray,
+                    });
+                }
+                for to in points {
+                    if let Some(((x1, y1), (x2, y2))) = clip(from, to, x_bounds, y_bounds) {
+                        ctx.draw(&Line::new(x1, y1, x2, y2, Color::DarkGray));
+                    }
+                    from = to;
+                }
+            }
+            ctx.draw(&Points {
+                coords: &track,
+                color: Color::Cyan,
+            });
+            for &(mmsi, (x, y), vector) in &targets {
+                let color = if Some(mmsi) == selected {
+                    Color::LightRed
+                } else {
+                    Color::Yellow
+                };
+                if let Some((dx, dy)) = vector {
+                    ctx.draw(&Line::new(x, y, x + dx, y + dy, color));
+                }
+                ctx.draw(&Points {
+                    coords: &[(x, y)],
+                    color,
+                });
+                if Some(mmsi) == selected {
+                    ctx.print(x, y, Span::from(mmsi.to_string()).fg(color));
+                }
+            }
+            if let Some(ellipse) = &ellipse {
+                for (&(x1, y1), &(x2, y2)) in ellipse.iter().zip(ellipse.iter().cycle().skip(1)) {
+                    ctx.draw(&Line::new(x1, y1, x2, y2, Color::Green));
+                }
+            }
+            draw_rings(ctx, (own_x, own_y), x_bounds, y_bounds, rings);
+            ctx.draw(&Points {
+                coords: &[(own_x, own_y)],
+                color: Color::White,
+            });
+            draw_scale_bar(ctx, x_bounds, y_bounds);
+            if up.is_some() {
+                let (x, y) = orient((0.0, range * 0.9));
+                ctx.print(x, y, Span::from("N").fg(Color::DarkGray));
+            }
+        });
+    frame.render_widget(canvas, area);
+}
+
+/// Range in meters below a nautical mile, else in nautical miles.
+fn scale(range: f64) -> String {
+    if range < geo::NAUTICAL_MILE {
+        format!("{range:.0} m")
+    } else {
+        format!("{:.1} NM", range / geo::NAUTICAL_MILE)
+    }
+}
+
+/// A ring or scale bar distance, in nautical miles when it is whole tenths of one and
+/// in meters otherwise, so that all rings use the unit of their interval.
+fn round_scale(meters: f64) -> String {
+    let tenths = meters / geo::NAUTICAL_MILE * 10.0;
+    if (tenths - tenths.round()).abs() < 1e-6 {
+        format!("{:.1} NM", tenths / 10.0)
+    } else {
+        format!("{meters:.0} m")
+    }
+}
+
+/// The largest of 1, 2 or 5 times a power of ten not above `meters`, counted in meters
+/// below a nautical mile and in nautical miles above.
+fn round_distance(meters: f64) -> f64 {
+    let unit = if meters < geo::NAUTICAL_MILE {
+        1.0
+    } else {
+        geo::NAUTICAL_MILE
+    };
+    let value = meters / unit;
+    let magnitude = 10_f64.powf(value.log10().floor());
+    let step = [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|&step| step <= value)
+        .unwrap_or(magnitude);
+    step * unit
+}
+
+/// Range rings around `center` every `interval` meters, or at a round interval fitting a
+/// few on the canvas, each labelled with its distance.
+pub fn draw_rings(
+    ctx: &mut Context,
+    (x, y): (f64, f64),
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+    interval: Option<f64>,
+) {
+    let half = (x_bounds[1] - x_bounds[0]).min(y_bounds[1] - y_bounds[0]) / 2.0;
+    let interval = interval
+        .filter(|&interval| interval * f64::from(MAX_RINGS) >= half)
+        .unwrap_or_else(|| round_distance(half / 2.0));
+    let farthest = [x_bounds[0], x_bounds[1]]
+        .into_iter()
+        .flat_map(|corner_x| y_bounds.map(|corner_y| (corner_x - x).hypot(corner_y - y)))
+        .fold(0.0, f64::max);
+    for ring in 1..=MAX_RINGS * 2 {
+        let radius = interval * f64::from(ring);
+        if radius > farthest {
+            break;
+        }
+        ctx.draw(&Circle {
+            x,
+            y,
+            radius,
+            color: Color::DarkGray,
+        });
+        if y + radius < y_bounds[1] {
+            ctx.print(
+                x,
+                y + radius,
+                Span::from(round_scale(radius)).fg(Color::DarkGray),
+            );
+        }
+    }
+}
+
+/// Scale bar of a round distance near a quarter of the width, in the bottom left corner.
+pub fn draw_scale_bar(ctx: &mut Context, [x_min, x_max]: [f64; 2], [y_min, y_max]: [f64; 2]) {
+    let length = round_distance((x_max - x_min) / 4.0);
+    let (x, y) = (
+        x_min + (x_max - x_min) * 0.03,
+        y_min + (y_max - y_min) * 0.03,
+    );
+    ctx.draw(&Line::new(x, y, x + length, y, Color::White));
+    ctx.print(
+        x + length + (x_max - x_min) * 0.02,
+        y,
+        Span::from(round_scale(length)).fg(Color::White),
+    );
+}
+
+/// Part of the segment from `a` to `b` inside the bounds, if any, so that lines running
+/// off the map are drawn up to its edge.
+fn clip(
+    (x1, y1): (f64, f64),
+    (x2, y2): (f64, f64),
+    [x_min, x_max]: [f64; 2],
+    [y_min, y_max]: [f64; 2],
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let (mut start, mut end) = (0.0_f64, 1.0_f64);
+    // Liang-Barsky: narrow the part of the segment within each edge in turn.
+    for (p, q) in [
+        (-dx, x1 - x_min),
+        (dx, x_max - x1),
+        (-dy, y1 - y_min),
+        (dy, y_max - y1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            start = start.max(q / p);
+        } else {
+            end = end.min(q / p);
+        }
+    }
+    (start <= end).then_some((
+        (x1 + start * dx, y1 + start * dy),
+        (x1 + end * dx, y1 + end * dy),
+    ))
+}
+
+/// Points around an error ellipse centered on own position, in map coordinates.
+fn outline(ellipse: Ellipse) -> impl Iterator<Item = (f64, f64)> {
+    (0..ELLIPSE_SEGMENTS).map(move |i| {
+        let angle = std::f64::consts::TAU * i as f64 / ELLIPSE_SEGMENTS as f64;
+        let (major_x, major_y) =
+            geo::displacement(ellipse.orientation, ellipse.semi_major * angle.cos());
+        let (minor_x, minor_y) =
+            geo::displacement(ellipse.orientation + 90.0, ellipse.semi_minor * angle.sin());
+        (major_x + minor_x, major_y + minor_y)
+    })
+}
+
+/// Canvas bounds showing at least `range` meters around the center with equal scale on
+/// both axes, assuming terminal cells are twice as tall as they are wide.
+pub fn bounds(area: Rect, range: f64) -> ([f64; 2], [f64; 2]) {
+    let aspect = f64::from(area.width.max(1)) / (2.0 * f64::from(area.height.max(1)));
+    let (x, y) = if aspect >= 1.0 {
+        (range * aspect, range)
+    } else {
+        (range, range / aspect)
+    };
+    ([-x, x], [-y, y])
+}
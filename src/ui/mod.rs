@@ -0,0 +1,1578 @@
+#[cfg(feature = "ais")]
+mod ais;
+mod alarms;
+mod big;
+mod charts;
+mod device;
+mod flash;
+mod log;
+mod map;
+mod night;
+mod rose;
+mod satellites;
+mod sensors;
+mod sources;
+mod statistics;
+mod traffic;
+
+use std::{
+    collections::HashMap,
+    io::Write as _,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures::StreamExt as _;
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::Backend,
+    style::{Color, Style, Stylize as _},
+    text::{Line, Text},
+    widgets::{Block, Clear, Paragraph, Row, Table, TableState, Wrap},
+    Frame, Terminal,
+};
+
+use tokio::{
+    sync::{mpsc::UnboundedSender, watch, Notify, RwLock},
+    time::Instant,
+};
+use tracing::{debug, info, warn};
+
+use self::{
+    big::BigValue,
+    flash::Flash,
+    log::{LogView, Search},
+    map::MapView,
+};
+
+use crate::{
+    alarm::Alarms,
+    barometer, browser,
+    capture::Capture,
+    config::{self, Config},
+    depth,
+    engine::Engine,
+    geo,
+    history::Window,
+    i18n::{self, tr},
+    logbook::{self, Entry},
+    maidenhead,
+    mob::Mob,
+    record::Recording,
+    replay,
+    shutdown::{self, Reason},
+    status::{NmeaStatus, StatusValue},
+    sun::{self, Crossing},
+    threshold::{self, Threshold},
+    waypoint::Waypoint,
+    Args,
+};
+
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Screen {
+    #[default]
+    Status,
+    Satellites,
+    #[cfg(feature = "ais")]
+    Ais,
+    Map,
+    Traffic,
+    Sensors,
+    Device,
+    Sources,
+    Statistics,
+    Charts,
+    Log,
+    Alarms,
+}
+
+impl Screen {
+    fn next(self) -> Screen {
+        match self {
+            Self::Status => Self::Satellites,
+            #[cfg(feature = "ais")]
+            Self::Satellites => Self::Ais,
+            #[cfg(feature = "ais")]
+            Self::Ais => Self::Map,
+            #[cfg(not(feature = "ais"))]
+            Self::Satellites => Self::Map,
+            Self::Map => Self::Traffic,
+            Self::Traffic => Self::Sensors,
+            Self::Sensors => Self::Device,
+            Self::Device => Self::Sources,
+            Self::Sources => Self::Statistics,
+            Self::Statistics => Self::Charts,
+            Self::Charts => Self::Log,
+            Self::Log => Self::Alarms,
+            Self::Alarms => Self::Status,
+        }
+    }
+
+    /// Whether the screen shows the map, which takes the map keys.
+    fn has_map(self) -> bool {
+        #[cfg(feature = "ais")]
+        if self == Self::Ais {
+            return true;
+        }
+        self == Self::Map
+    }
+}
+
+#[derive(Debug)]
+pub struct App {
+    screen: Screen,
+    satellites: TableState,
+    #[cfg(feature = "ais")]
+    ais: TableState,
+    traffic: TableState,
+    /// Selected waypoint on the status screen.
+    waypoints: TableState,
+    /// Selected replay bookmark on the sources screen.
+    bookmarks: TableState,
+    log: LogView,
+    /// Span of the statistics screen.
+    window: Window,
+    /// Time shown on the charts screen.
+    chart_span: Duration,
+    /// Whether altitude is charted against distance rather than time.
+    altitude_by_distance: bool,
+    /// Sentences typed by the user are sent here when the source is writable.
+    sender: Option<UnboundedSender<String>>,
+    /// Recording and capture of the raw input, shown on the status screen.
+    recording: Arc<Mutex<Recording>>,
+    /// Text being typed, if an input is open.
+    input: Option<Input>,
+    /// While frozen the screen keeps showing the last frame; input is still processed.
+    frozen: bool,
+    /// Draw once even though frozen, to show the indicator.
+    dirty: bool,
+    /// Position shown when the display was frozen.
+    frozen_position: Option<(f64, f64)>,
+    /// Short feedback such as "copied", shown in the banner for a few seconds.
+    message: Option<(String, Instant)>,
+    /// URL template for opening the position in a browser.
+    map_url: String,
+    /// Rotate the map so that own course points up.
+    course_up: bool,
+    map: MapView,
+    /// Meters between range rings on the map and radar, round for the zoom when not set.
+    range_rings: Option<f64>,
+    /// Show speed and course as received rather than smoothed.
+    raw: bool,
+    /// Show true wind computed from the apparent wind.
+    true_wind: bool,
+    /// Value shown in block digits instead of the current screen.
+    big: Option<BigValue>,
+    /// Dim red palette for use in the dark.
+    night: bool,
+    /// Config file the night palette setting is saved to.
+    config: Option<PathBuf>,
+    /// File logbook entries are appended to.
+    logbook: Option<PathBuf>,
+    /// Notified to reload the config file, when there is one.
+    reload: Option<Arc<Notify>>,
+    /// Highlight of status values that changed.
+    flash: Flash,
+    /// Colors of status values by range, keyed by title.
+    thresholds: HashMap<String, Vec<Threshold>>,
+    /// Alarm bells rung, out of those sounded by the status.
+    bells: u64,
+    /// When the last bell was rung, so that several are heard apart.
+    bell_at: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct Input {
+    purpose: InputPurpose,
+    text: String,
+}
+
+#[derive(Debug)]
+enum InputPurpose {
+    /// A sentence body to send to the device.
+    Send,
+    /// Name of a waypoint at the position where the key was pressed.
+    Waypoint {
+        lat: f64,
+        lon: f64,
+        alt: Option<f64>,
+    },
+    /// Logbook entry with the state when the key was pressed; the text is typed.
+    Logbook(Entry),
+    /// Pattern to find in the raw log.
+    Search,
+}
+
+/// How long a feedback message stays in the banner.
+const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+
+/// Time between the bells of an alarm.
+const BELL_INTERVAL: Duration = Duration::from_millis(300);
+
+impl App {
+    pub fn new(
+        engine: &Engine,
+        map_url: String,
+        logbook: Option<PathBuf>,
+        pinned: Vec<String>,
+        chart_span: Duration,
+        night: bool,
+        config: Option<PathBuf>,
+    ) -> App {
+        App {
+            screen: Screen::default(),
+            satellites: TableState::default(),
+            #[cfg(feature = "ais")]
+            ais: TableState::default(),
+            traffic: TableState::default(),
+            waypoints: TableState::default(),
+            bookmarks: TableState::default(),
+            log: LogView::new(pinned),
+            window: Window::default(),
+            chart_span,
+            altitude_by_distance: false,
+            sender: engine.sender.clone(),
+            recording: Arc::clone(&engine.recording),
+            input: None,
+            frozen: false,
+            dirty: false,
+            frozen_position: None,
+            message: None,
+            map_url,
+            course_up: false,
+            map: MapView::default(),
+            range_rings: None,
+            raw: false,
+            true_wind: false,
+            big: None,
+            night,
+            config,
+            logbook,
+            reload: None,
+            flash: Flash::default(),
+            thresholds: HashMap::new(),
+            bells: 0,
+            bell_at: None,
+        }
+    }
+
+    /// Applies the UI's part of a reloaded config. Sentences pinned in the file are added
+    /// to those pinned with `p`.
+    fn reconfigure(&mut self, args: &Args, config: &Config) {
+        self.map_url = map_url(args, config);
+        for address in &config.display.pinned {
+            if !self.log.pinned.contains(address) {
+                self.log.pinned.push(address.clone());
+            }
+        }
+        self.night = config.display.night;
+        self.thresholds = config.thresholds.clone();
+        self.map.set_overlay(config.overlay.clone());
+        self.range_rings = range_rings(config);
+        self.dirty = true;
+    }
+
+    /// Rings the next alarm bell the status has sounded, if the last one was long enough
+    /// ago to be heard apart. Bells still to ring are dropped when alarms are silenced.
+    fn ring(&mut self, alarms: &Alarms) {
+        if alarms.silenced {
+            self.bells = alarms.bells;
+        }
+        if alarms.bells <= self.bells || self.bell_at.is_some_and(|at| at.elapsed() < BELL_INTERVAL)
+        {
+            return;
+        }
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+        self.bells += 1;
+        self.bell_at = Some(Instant::now());
+    }
+
+    /// Whether the screen should be redrawn on this tick.
+    pub fn should_draw(&mut self) -> bool {
+        !self.frozen || std::mem::take(&mut self.dirty)
+    }
+
+    fn show_message(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        info!(message, "shown");
+        self.message = Some((message, Instant::now()));
+        self.dirty = true;
+    }
+
+    /// Current position, or the one on screen while frozen.
+    fn position(&self, nmea: &NmeaStatus) -> Option<(f64, f64)> {
+        if self.frozen {
+            return self.frozen_position;
+        }
+        nmea.lat.get().copied().zip(nmea.lon.get().copied())
+    }
+
+    /// Appends an event to the logbook, if there is one.
+    fn log_event(&mut self, nmea: &NmeaStatus, text: String) {
+        let Some(path) = &self.logbook else {
+            return;
+        };
+        let entry = Entry {
+            time: SystemTime::now(),
+            position: nmea.position().map(|position| (position.lat, position.lon)),
+            cog: nmea.cog.get().copied(),
+            sog: nmea.sog.get().copied(),
+            text,
+        };
+        if let Err(e) = logbook::append(path, &entry) {
+            self.show_message(format!("{e:#}"));
+        }
+    }
+
+    fn submit(&mut self, input: Input, nmea: &mut NmeaStatus) {
+        match input.purpose {
+            InputPurpose::Send => {
+                if let (Some(sender), Some(sentence)) =
+                    (&self.sender, crate::device::frame(&input.text))
+                {
+                    info!(sentence = input.text, "sending");
+                    let _ = sender.send(sentence);
+                }
+            }
+            InputPurpose::Waypoint { lat, lon, alt } => {
+                self.show_message(i18n::format("waypoint {} saved", &[&input.text]));
+                nmea.waypoints.add(Waypoint {
+                    name: input.text,
+                    lat,
+                    lon,
+                    alt,
+                    created_at: SystemTime::now(),
+                });
+            }
+            InputPurpose::Logbook(entry) => {
+                let Some(path) = &self.logbook else {
+                    return;
+                };
+                let entry = Entry {
+                    text: input.text,
+                    ..entry
+                };
+                match logbook::append(path, &entry) {
+                    Ok(()) => self.show_message(i18n::format("logged to {}", &[&path.display()])),
+                    Err(e) => self.show_message(format!("{e:#}")),
+                }
+            }
+            InputPurpose::Search if input.text.is_empty() => self.log.search = None,
+            InputPurpose::Search => match Search::new(&input.text) {
+                Ok(search) => {
+                    self.log.search = Some(search);
+                    self.log.selected = None;
+                    if !self.log.find_previous(&nmea.raw_log) {
+                        self.show_message(i18n::format("no match for {}", &[&input.text]));
+                    }
+                }
+                Err(e) => self.show_message(i18n::format("invalid regex: {}", &[&e])),
+            },
+        }
+    }
+
+    /// Handles a key press. Returns `false` when the app should quit.
+    pub fn handle_key(&mut self, key: KeyEvent, nmea: &mut NmeaStatus) -> bool {
+        if let Some(input) = &mut self.input {
+            match key.code {
+                KeyCode::Char(c) => input.text.push(c),
+                KeyCode::Backspace => {
+                    input.text.pop();
+                }
+                KeyCode::Enter => {
+                    if let Some(input) = self.input.take() {
+                        self.submit(input, nmea);
+                    }
+                }
+                KeyCode::Esc => self.input = None,
+                _ => {}
+            }
+            self.dirty = true;
+            return true;
+        }
+
+        if self.log.decoding.is_some() {
+            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                self.log.decoding = None;
+            }
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Esc => return false,
+            KeyCode::Char('f') => {
+                self.frozen_position = self.position(nmea);
+                self.frozen = !self.frozen;
+                self.dirty = true;
+            }
+            KeyCode::Char('c') => match self.position(nmea) {
+                Some((lat, lon)) => {
+                    let text = nmea.coordinates.position(lat, lon);
+                    match crate::clipboard::copy(&text) {
+                        Ok(()) => self.show_message(i18n::format("copied {}", &[&text])),
+                        Err(e) => self.show_message(i18n::format("copy failed: {}", &[&e])),
+                    }
+                }
+                None => self.show_message(tr("no position to copy")),
+            },
+            KeyCode::Char('w') => match self.position(nmea) {
+                Some((lat, lon)) => {
+                    self.input = Some(Input {
+                        purpose: InputPurpose::Waypoint {
+                            lat,
+                            lon,
+                            alt: nmea.alt.get().copied(),
+                        },
+                        text: nmea.waypoints.next_name(),
+                    });
+                    self.dirty = true;
+                }
+                None => self.show_message(tr("no position for a waypoint")),
+            },
+            KeyCode::Char('l') if self.logbook.is_none() => {
+                self.show_message(tr("start with --logbook to keep a logbook"));
+            }
+            KeyCode::Char('l') => {
+                self.input = Some(Input {
+                    purpose: InputPurpose::Logbook(Entry {
+                        time: SystemTime::now(),
+                        position: self.position(nmea),
+                        cog: nmea.cog.get().copied(),
+                        sog: nmea.sog.get().copied(),
+                        text: String::new(),
+                    }),
+                    text: String::new(),
+                });
+                self.dirty = true;
+            }
+            KeyCode::Char('o') => match self.position(nmea) {
+                Some((lat, lon)) => {
+                    let url = crate::browser::map_url(&self.map_url, lat, lon);
+                    match crate::browser::open(&url) {
+                        Ok(()) => self.show_message(i18n::format("opened {}", &[&url])),
+                        Err(e) => {
+                            self.show_message(i18n::format("failed to open browser: {}", &[&e]))
+                        }
+                    }
+                }
+                None => self.show_message(tr("no position to open")),
+            },
+            KeyCode::Char('t') => {
+                let captured = self
+                    .recording
+                    .lock()
+                    .expect("recording lock poisoned")
+                    .capture
+                    .as_mut()
+                    .map(|capture| capture.trigger("manual"));
+                match captured {
+                    Some(Ok(path)) => {
+                        self.show_message(i18n::format("captured to {}", &[&path.display()]))
+                    }
+                    Some(Err(e)) => self.show_message(i18n::format("capture failed: {}", &[&e])),
+                    None => self.show_message(tr("start with --capture to capture input")),
+                }
+            }
+            KeyCode::Char('k') => match &mut nmea.replay {
+                Some(replay) => match replay.bookmark() {
+                    Ok(bookmark) => self.show_message(i18n::format(
+                        "bookmarked {}",
+                        &[&bookmark
+                            .time
+                            .map_or_else(|| bookmark.offset.to_string(), replay::format_time)],
+                    )),
+                    Err(e) => self.show_message(i18n::format("bookmark failed: {}", &[&e])),
+                },
+                None => self.show_message(tr("start with --replay to bookmark")),
+            },
+            // Marks the live position, also while frozen.
+            KeyCode::Char('M') => match (&nmea.mob, nmea.position()) {
+                (Some(_), _) => self.show_message(tr("MOB already marked, X to cancel")),
+                (None, Some(position)) => {
+                    let (lat, lon) = (position.lat, position.lon);
+                    warn!(lat, lon, "man overboard");
+                    nmea.mob = Some(Mob::new((lat, lon)));
+                    nmea.waypoints.add(Waypoint {
+                        name: "MOB".to_string(),
+                        lat,
+                        lon,
+                        alt: None,
+                        created_at: SystemTime::now(),
+                    });
+                    self.log_event(
+                        nmea,
+                        format!("MOB at {}", nmea.coordinates.position(lat, lon)),
+                    );
+                    self.dirty = true;
+                }
+                (None, None) => self.show_message(tr("no position for MOB")),
+            },
+            KeyCode::Char('X') => match nmea.mob.take() {
+                Some(mob) => {
+                    let elapsed = humantime::format_duration(mob.elapsed());
+                    info!(%elapsed, "man overboard cancelled");
+                    self.log_event(nmea, format!("MOB cancelled after {elapsed}"));
+                    self.show_message(tr("MOB cancelled"));
+                }
+                None => self.show_message(tr("no MOB to cancel")),
+            },
+            _ if self.frozen => {}
+            KeyCode::Tab => {
+                self.screen = self.screen.next();
+                debug!(screen = ?self.screen, "screen changed");
+            }
+            KeyCode::Char('r') => self.raw = !self.raw,
+            KeyCode::Char('A') => {
+                if nmea.alarms.acknowledge() == 0 {
+                    self.show_message(tr("no alarms to acknowledge"));
+                }
+                self.dirty = true;
+            }
+            KeyCode::Char('S') => {
+                nmea.alarms.silenced = !nmea.alarms.silenced;
+                self.dirty = true;
+            }
+            KeyCode::Char('R') => match &self.reload {
+                Some(reload) => reload.notify_one(),
+                None => self.show_message(tr("no config file to reload")),
+            },
+            KeyCode::Char('a') => self.true_wind = !self.true_wind,
+            KeyCode::Char('m') => {
+                self.night = !self.night;
+                let mode = if self.night {
+                    "night palette"
+                } else {
+                    "day palette"
+                };
+                match &self.config {
+                    Some(path) => match config::set_display(path, "night", &self.night.to_string())
+                    {
+                        Ok(()) => self.show_message(format!("{} {}", tr(mode), tr("saved"))),
+                        Err(e) => {
+                            self.show_message(format!("{}, {}: {e:#}", tr(mode), tr("not saved")))
+                        }
+                    },
+                    None => self.show_message(tr(mode)),
+                }
+            }
+            KeyCode::Char('b') => {
+                self.big = match self.big {
+                    Some(value) => value.next(),
+                    None => Some(BigValue::Sog),
+                };
+            }
+            KeyCode::Char('u') if self.screen.has_map() => self.course_up = !self.course_up,
+            KeyCode::Char('+') if self.screen.has_map() => self.map.zoom_in(),
+            KeyCode::Char('-') if self.screen.has_map() => self.map.zoom_out(),
+            KeyCode::Char('=') if self.screen.has_map() => self.map.fit(),
+            KeyCode::Char('F') if self.screen.has_map() => self.map.follow(),
+            // The arrow keys select targets on the AIS screen, so panning there takes shift.
+            KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down
+                if self.screen == Screen::Map
+                    || self.screen.has_map() && key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.map.pan(match key.code {
+                    KeyCode::Left => (-1.0, 0.0),
+                    KeyCode::Right => (1.0, 0.0),
+                    KeyCode::Up => (0.0, 1.0),
+                    _ => (0.0, -1.0),
+                });
+            }
+            KeyCode::Char(':') if self.screen == Screen::Device && self.sender.is_some() => {
+                self.input = Some(Input {
+                    purpose: InputPurpose::Send,
+                    text: String::new(),
+                });
+            }
+            KeyCode::Char('s') if self.screen == Screen::Statistics => {
+                self.window = self.window.next();
+            }
+            KeyCode::Char('d') if self.screen == Screen::Charts => {
+                self.altitude_by_distance = !self.altitude_by_distance;
+            }
+            KeyCode::Char('/') if self.screen == Screen::Log => {
+                self.input = Some(Input {
+                    purpose: InputPurpose::Search,
+                    text: String::new(),
+                });
+            }
+            KeyCode::Char('p') if self.screen == Screen::Log => {
+                match self.log.toggle_pin(&nmea.raw_log) {
+                    Some((address, true)) => {
+                        self.show_message(i18n::format("pinned {}", &[&address]))
+                    }
+                    Some((address, false)) => {
+                        self.show_message(i18n::format("unpinned {}", &[&address]))
+                    }
+                    None => self.show_message(tr("select a sentence to pin with ↑")),
+                }
+            }
+            KeyCode::Enter if self.screen == Screen::Sources => {
+                let bookmark = nmea.replay.as_mut().and_then(|replay| {
+                    let bookmark = replay.bookmarks.get(self.bookmarks.selected()?)?.clone();
+                    replay.jump(bookmark.offset);
+                    Some(bookmark)
+                });
+                match bookmark {
+                    Some(bookmark) => self.show_message(i18n::format(
+                        "jumped to {}",
+                        &[&bookmark
+                            .time
+                            .map_or_else(|| bookmark.offset.to_string(), replay::format_time)],
+                    )),
+                    None => self.show_message(tr("select a bookmark with ↓")),
+                }
+            }
+            KeyCode::Enter if self.screen == Screen::Log => {
+                if self.log.selected.is_none() {
+                    self.show_message(tr("select a sentence to decode with ↑"));
+                } else if !self.log.decode(&nmea.raw_log) {
+                    self.show_message(tr("not a sentence"));
+                }
+            }
+            KeyCode::Char('n') if self.screen == Screen::Log => {
+                let found = self.log.find_previous(&nmea.raw_log);
+                if !found {
+                    self.show_message(tr("no older match"));
+                }
+            }
+            KeyCode::Char('N') if self.screen == Screen::Log => {
+                let found = self.log.find_next(&nmea.raw_log);
+                if !found {
+                    self.show_message(tr("no newer match"));
+                }
+            }
+            KeyCode::Down => match self.screen {
+                Screen::Status => self.waypoints.select_next(),
+                Screen::Satellites => self.satellites.select_next(),
+                #[cfg(feature = "ais")]
+                Screen::Ais => self.ais.select_next(),
+                Screen::Traffic => self.traffic.select_next(),
+                Screen::Sources => self.bookmarks.select_next(),
+                Screen::Log => self.log.select_next(&nmea.raw_log),
+                // Nothing to select; the map is panned above.
+                _ => {}
+            },
+            KeyCode::Up => match self.screen {
+                Screen::Status => self.waypoints.select_previous(),
+                Screen::Satellites => self.satellites.select_previous(),
+                #[cfg(feature = "ais")]
+                Screen::Ais => self.ais.select_previous(),
+                Screen::Traffic => self.traffic.select_previous(),
+                Screen::Sources => self.bookmarks.select_previous(),
+                Screen::Log => self.log.select_previous(&nmea.raw_log),
+                // Nothing to select; the map is panned above.
+                _ => {}
+            },
+            _ => {}
+        }
+        true
+    }
+}
+
+pub fn draw(frame: &mut Frame, nmea: &NmeaStatus, app: &mut App) {
+    let mut banner_lines = nmea
+        .alarms
+        .active()
+        .iter()
+        .map(|alarm| match (alarm.cleared, alarm.acknowledged) {
+            (Some(_), _) => {
+                Line::from(format!("{} ({})", alarm.text, tr("cleared"))).fg(Color::DarkGray)
+            }
+            (None, Some(_)) => Line::from(alarm.text.clone()).fg(Color::LightRed),
+            (None, None) => Line::from(alarm.text.clone()).bold().fg(Color::LightRed),
+        })
+        .collect::<Vec<_>>();
+    if let Some((message, _)) = app
+        .message
+        .as_ref()
+        .filter(|(_, at)| at.elapsed() < MESSAGE_DURATION)
+    {
+        banner_lines.insert(0, Line::from(message.clone()).fg(Color::Green));
+    }
+    if nmea.alarms.silenced {
+        banner_lines.insert(
+            0,
+            Line::from(format!(" {} ", tr("ALARMS SILENCED (S to sound)")))
+                .bold()
+                .reversed(),
+        );
+    }
+    if app.frozen {
+        banner_lines.insert(
+            0,
+            Line::from(format!(" {} ", tr("FROZEN (f to resume)")))
+                .bold()
+                .reversed(),
+        );
+    }
+    let [banner, area] = Layout::vertical([
+        Constraint::Length(banner_lines.len() as u16),
+        Constraint::Min(0),
+    ])
+    .areas(frame.area());
+    frame.render_widget(Paragraph::new(banner_lines), banner);
+
+    let prompt = match &app.input {
+        Some(Input {
+            purpose: InputPurpose::Send,
+            text,
+        }) => Some(format!("{}: ${text}█", tr("send"))),
+        Some(Input {
+            purpose: InputPurpose::Search,
+            text,
+        }) => Some(format!("{}: {text}█", tr("search (re: for regex)"))),
+        _ => None,
+    };
+    let [area, input] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(u16::from(prompt.is_some())),
+    ])
+    .areas(area);
+    if let Some(prompt) = prompt {
+        frame.render_widget(Paragraph::new(prompt), input);
+    }
+
+    if let Some(value) = app.big {
+        big::draw(frame, area, nmea, value);
+    } else {
+        match app.screen {
+            Screen::Status => draw_status(frame, area, nmea, app),
+            Screen::Satellites => satellites::draw(frame, area, nmea, &mut app.satellites),
+            #[cfg(feature = "ais")]
+            Screen::Ais => ais::draw(
+                frame,
+                area,
+                nmea,
+                &mut app.ais,
+                app.course_up,
+                &mut app.map,
+                app.range_rings,
+            ),
+            Screen::Map => map::draw(
+                frame,
+                area,
+                nmea,
+                None,
+                app.course_up,
+                &mut app.map,
+                app.range_rings,
+            ),
+            Screen::Traffic => traffic::draw(frame, area, nmea, &mut app.traffic, app.range_rings),
+            Screen::Sensors => sensors::draw(frame, area, nmea),
+            Screen::Device => device::draw(frame, area, nmea, app.sender.is_some()),
+            Screen::Sources => sources::draw(frame, area, nmea, &mut app.bookmarks),
+            Screen::Statistics => statistics::draw(frame, area, nmea, app.window),
+            Screen::Charts => {
+                charts::draw(frame, area, nmea, app.chart_span, app.altitude_by_distance)
+            }
+            Screen::Log => log::draw(frame, area, nmea, &app.log),
+            Screen::Alarms => alarms::draw(frame, area, nmea),
+        }
+    }
+
+    if let Some(mob) = &nmea.mob {
+        draw_mob(frame, area, nmea, mob);
+    }
+
+    let modal = match &app.input {
+        Some(Input {
+            purpose: InputPurpose::Waypoint { lat, lon, .. },
+            text,
+        }) => Some((
+            tr("new waypoint"),
+            format!("{}\nname: {text}█", nmea.coordinates.position(*lat, *lon)),
+        )),
+        Some(Input {
+            purpose: InputPurpose::Logbook(entry),
+            text,
+        }) => Some((
+            tr("logbook entry"),
+            format!(
+                "{} {}\n{text}█",
+                clock(entry.time),
+                entry.position.map_or_else(
+                    || "no position".to_string(),
+                    |(lat, lon)| nmea.coordinates.position(lat, lon)
+                )
+            ),
+        )),
+        _ => None,
+    };
+    if let Some((title, text)) = modal {
+        let [modal] = Layout::vertical([Constraint::Length(6)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [modal] = Layout::horizontal([Constraint::Length(60)])
+            .flex(Flex::Center)
+            .areas(modal);
+        frame.render_widget(Clear, modal);
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(Line::raw(title))),
+            modal,
+        );
+    }
+
+    if app.night {
+        night::apply(frame.buffer_mut());
+    }
+}
+
+/// Bearing, distance and time back to the man overboard mark, over whatever screen is
+/// shown.
+fn draw_mob(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, mob: &Mob) {
+    let back = match nmea.position() {
+        Some(position) => {
+            let (bearing, distance) = mob.back_from((position.lat, position.lon));
+            format!(
+                "{} {bearing:03.0}°  {} {:.2} NM ({distance:.0} m)",
+                tr("bearing"),
+                tr("distance"),
+                distance / geo::NAUTICAL_MILE,
+            )
+        }
+        None => tr("no position").into_owned(),
+    };
+    let text = Text::from(vec![
+        Line::from(back).bold(),
+        Line::from(format!(
+            "{} {}  {} {}",
+            tr("elapsed"),
+            humantime::format_duration(mob.elapsed()),
+            tr("marked at"),
+            nmea.coordinates.position(mob.position.0, mob.position.1),
+        )),
+    ]);
+
+    let [overlay] = Layout::vertical([Constraint::Length(4)]).areas(area);
+    let [overlay] = Layout::horizontal([Constraint::Length(64)])
+        .flex(Flex::Center)
+        .areas(overlay);
+    frame.render_widget(Clear, overlay);
+    frame.render_widget(
+        Paragraph::new(text)
+            .centered()
+            .block(Block::bordered().title(format!(" {} ", tr("MAN OVERBOARD (X to cancel)"))))
+            .white()
+            .on_red(),
+        overlay,
+    );
+}
+
+fn draw_status(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, app: &mut App) {
+    let cells = status_cells(nmea, app.raw, app.true_wind);
+    let columns = (area.width / CELL_WIDTH).max(1) as usize;
+    let rows = cells.len().div_ceil(columns) as u16;
+    let [cells_area, details] =
+        Layout::vertical([Constraint::Length(rows * 2), Constraint::Min(0)]).areas(area);
+    let [left, right, rose] = Layout::horizontal([
+        Constraint::Length(30),
+        Constraint::Min(0),
+        Constraint::Length(ROSE_WIDTH),
+    ])
+    .areas(details);
+    let waypoints = nmea.waypoints.iter().count() as u16;
+    let [dsc, waypoints] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(if waypoints == 0 { 0 } else { waypoints + 1 }),
+    ])
+    .areas(right);
+    let [accuracy, input, sun, tide, kalman, derived] = Layout::vertical([
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(3),
+        Constraint::Length(4),
+        Constraint::Length(5),
+        Constraint::Length(3),
+    ])
+    .areas(left);
+
+    for (i, (id, title, value)) in cells.into_iter().enumerate() {
+        let cell = Rect {
+            x: cells_area.x + (i % columns) as u16 * CELL_WIDTH,
+            y: cells_area.y + (i / columns) as u16 * 2,
+            width: CELL_WIDTH.min(cells_area.width),
+            height: 2,
+        };
+        let color = app
+            .thresholds
+            .get(&id)
+            .and_then(|thresholds| threshold::color(thresholds, &value.to_string()));
+        let value = match color {
+            Some(color) => value.fg(Color::from(color)),
+            None => value,
+        };
+        let value = app.flash.apply(&id, value);
+        render_statistics(frame, cell, &title, value);
+    }
+
+    let meters =
+        |value: Option<&f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.1} m"));
+    let label = |label: &str| format!("{:<10}", tr(label));
+    let accuracy_lines = [
+        format!(
+            "{} {}",
+            label("horizontal"),
+            meters(nmea.accuracy.horizontal.get())
+        ),
+        format!(
+            "{} {}",
+            label("vertical"),
+            meters(nmea.accuracy.vertical.get())
+        ),
+        format!(
+            "{} {}",
+            label("spherical"),
+            meters(nmea.accuracy.spherical.get())
+        ),
+        format!(
+            "{} {}",
+            label("garmin alt"),
+            meters(nmea.accuracy.garmin_altitude.get())
+        ),
+        format!(
+            "σ lat/lon/alt {}/{}/{}",
+            or_dash(nmea.accuracy.sigma_lat.get().map(|v| format!("{v:.1}"))),
+            or_dash(nmea.accuracy.sigma_lon.get().map(|v| format!("{v:.1}"))),
+            meters(nmea.accuracy.sigma_alt.get()),
+        ),
+        format!(
+            "{} {}",
+            label("raim"),
+            match nmea.integrity.fault.get() {
+                Some(Some(fault)) => i18n::format("sat {} flagged", &[&fault.satellite]),
+                Some(None) =>
+                    i18n::format("ok, {}", &[&meters(nmea.integrity.horizontal().as_ref())]),
+                None => "-".to_string(),
+            }
+        ),
+    ];
+    let title = match nmea.accuracy.source.get() {
+        Some(source) => format!("{} ({source})", tr("accuracy")),
+        None => tr("accuracy").into_owned(),
+    };
+    frame.render_widget(
+        Paragraph::new(accuracy_lines.join("\n")).block(Block::new().title(title)),
+        accuracy,
+    );
+
+    let active = match nmea.active_source() {
+        Some(0) => Line::from(i18n::format("active {}", &[&nmea.sources[0].name])),
+        Some(index) => Line::from(i18n::format(
+            "active {} (backup)",
+            &[&nmea.sources[index].name],
+        ))
+        .fg(Color::Yellow),
+        None => Line::from(i18n::format("active {}", &[&"-"])),
+    };
+    let recording = app.recording.lock().expect("recording lock poisoned");
+    frame.render_widget(
+        Paragraph::new(vec![
+            active,
+            Line::from(i18n::format(
+                "dropped {} B, {} duplicates, {} outliers",
+                &[&nmea.dropped_bytes, &nmea.duplicates, &nmea.outliers.count],
+            )),
+            {
+                let line = Line::from(i18n::format(
+                    "queued {}/{}, {} dropped",
+                    &[&nmea.queued.0, &nmea.queued.1, &nmea.queue_drops],
+                ));
+                if nmea.queue_drops > 0 {
+                    line.fg(Color::Yellow)
+                } else {
+                    line
+                }
+            },
+            match recording.recorder.path() {
+                Some(path) => {
+                    Line::from(i18n::format("● recording {}", &[&path.display()])).fg(Color::Red)
+                }
+                None => Line::from(""),
+            },
+            match &recording.capture {
+                Some(Capture {
+                    last: Some((path, reason)),
+                    ..
+                }) => Line::from(i18n::format("captured {} ({})", &[&path.display(), reason])),
+                Some(_) => Line::raw(tr("capture armed (t)")),
+                None => Line::from(""),
+            },
+        ])
+        .block(Block::new().title(Line::raw(tr("input")))),
+        input,
+    );
+
+    if let Some(position) = nmea.position() {
+        let times = sun::sun_times(position.lat, position.lon, SystemTime::now());
+        let crossing = |crossing: Crossing, up: &str, down: &str| match crossing {
+            Crossing::Times(rise, set) => {
+                format!("{up} {}  {down} {}", &clock(rise)[..5], &clock(set)[..5])
+            }
+            Crossing::AlwaysAbove => i18n::format("no {}", &[&down]),
+            Crossing::AlwaysBelow => i18n::format("no {}", &[&up]),
+        };
+        let lines = [
+            crossing(times.day, &tr("rise"), &tr("set")),
+            crossing(times.civil, &tr("dawn"), &tr("dusk")),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines.join("\n")).block(Block::new().title(format!(
+                "{} ({})",
+                tr("sun"),
+                crate::clock::zone_name(SystemTime::now())
+            ))),
+            sun,
+        );
+    }
+
+    if let (Some(tides), Some(position)) = (&nmea.tides, nmea.position()) {
+        if let Some((station, distance)) = tides.nearest(position.lat, position.lon) {
+            let prediction = station.predict(SystemTime::now());
+            let extreme = |label: &str, extreme: Option<(SystemTime, f64)>| {
+                extreme.map_or_else(String::new, |(at, height)| {
+                    format!("{label:<4} {} {height:.2} m", &clock(at)[..5])
+                })
+            };
+            let lines = [
+                format!(
+                    "{:.2} m {} {:.2} m/h",
+                    prediction.height,
+                    if prediction.rate >= 0.0 { '↑' } else { '↓' },
+                    prediction.rate.abs()
+                ),
+                extreme(&tr("high"), prediction.next_high),
+                extreme(&tr("low"), prediction.next_low),
+            ];
+            frame.render_widget(
+                Paragraph::new(lines.join("\n")).block(Block::new().title(i18n::format(
+                    "tide {} ({} NM)",
+                    &[
+                        &station.name,
+                        &format!("{:.1}", distance / geo::NAUTICAL_MILE),
+                    ],
+                ))),
+                tide,
+            );
+        }
+    }
+
+    if let Some(estimate) = nmea.kalman.as_ref().and_then(|kalman| kalman.estimate()) {
+        let lines = [
+            nmea.coordinates.lat(estimate.lat),
+            nmea.coordinates.lon(estimate.lon),
+            format!("{:.1} kn {:.0}°", estimate.sog, estimate.cog),
+            format!("±{:.1} m", estimate.sigma),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines.join("\n")).block(Block::new().title(Line::raw(tr("kalman")))),
+            kalman,
+        );
+    }
+
+    let knots =
+        |value: Option<&f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.1} kn"));
+    let derived_lines = [
+        format!(
+            "stw {}  sog {}",
+            knots(nmea.stw.get()),
+            knots(nmea.sog.get())
+        ),
+        match nmea.current() {
+            Some((set, drift)) => i18n::format(
+                "current {} kn → {}°",
+                &[&format!("{drift:.1}"), &format!("{set:03.0}")],
+            ),
+            None => i18n::format("current {}", &[&"-"]),
+        },
+    ];
+    frame.render_widget(
+        Paragraph::new(derived_lines.join("\n"))
+            .block(Block::new().title(Line::raw(tr("derived")))),
+        derived,
+    );
+
+    let calls = nmea
+        .dsc
+        .iter()
+        .map(|call| {
+            let line = Line::from(format!(
+                "{} {:<8} {}{}{}",
+                clock(call.received_at),
+                call.category.as_str(),
+                call.mmsi,
+                call.nature.map(|n| format!(" {n}")).unwrap_or_default(),
+                call.position
+                    .map(|(lat, lon)| format!(" {}", nmea.coordinates.position(lat, lon)))
+                    .unwrap_or_default(),
+            ));
+            if call.is_alert() {
+                line.fg(Color::LightRed)
+            } else if call.position_request {
+                line.fg(Color::Yellow)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>();
+    if !calls.is_empty() {
+        frame.render_widget(
+            Paragraph::new(calls).block(Block::new().title(Line::raw(tr("dsc")))),
+            dsc,
+        );
+    }
+
+    let rows = nmea.waypoints.iter().map(|waypoint| {
+        Row::new([
+            clock(waypoint.created_at),
+            waypoint.name.clone(),
+            nmea.coordinates.position(waypoint.lat, waypoint.lon),
+        ])
+    });
+    let widths = [
+        Constraint::Length(8),  // created
+        Constraint::Length(12), // name
+        Constraint::Min(0),     // position
+    ];
+    let count = nmea.waypoints.iter().count();
+    let selected = app
+        .waypoints
+        .selected()
+        .and_then(|i| nmea.waypoints.iter().nth(i.min(count.saturating_sub(1))));
+    let mut block = Block::new().title(Line::raw(tr("waypoints (↑/↓ to select)")));
+    if let (Some(waypoint), Some(position)) = (selected, nmea.position()) {
+        let (bearing, distance) = geo::course_and_length(geo::local_xy(
+            (position.lat, position.lon),
+            (waypoint.lat, waypoint.lon),
+        ));
+        block = block.title(target_title(
+            nmea,
+            &waypoint.name,
+            bearing,
+            format!("{:.2} NM", distance / geo::NAUTICAL_MILE),
+        ));
+    }
+    // The selected row is scrolled into view when the waypoints outgrow their area.
+    let table = Table::new(rows, widths)
+        .highlight_style(Style::new().reversed())
+        .block(block);
+    frame.render_stateful_widget(table, waypoints, &mut app.waypoints);
+    rose::draw(frame, rose, nmea);
+}
+
+/// Width of the compass rose on the status screen.
+const ROSE_WIDTH: u16 = 30;
+
+/// Width of one value cell at the top of the status screen. Cells wrap onto more rows
+/// when the terminal is narrow.
+const CELL_WIDTH: u16 = 20;
+
+/// The id, which is the title before translation, the title and the value of each cell.
+fn status_cells(
+    nmea: &NmeaStatus,
+    raw: bool,
+    true_wind: bool,
+) -> Vec<(String, String, Text<'static>)> {
+    let mut cells = Vec::new();
+    let mut cell =
+        |id: &str, value: Text<'static>| cells.push((id.to_string(), tr(id).into_owned(), value));
+
+    // Coordinates in another datum than WGS84 are labelled with it.
+    let datum = nmea.datum.as_ref().filter(|datum| !datum.is_wgs84());
+    match nmea.position() {
+        Some(position) if position.estimated => {
+            let dr = |text: String| Text::from(text).fg(Color::Yellow);
+            cell("latitude (DR)", dr(nmea.coordinates.lat(position.lat)));
+            cell("longitude (DR)", dr(nmea.coordinates.lon(position.lon)));
+        }
+        Some(position) => match datum {
+            Some(datum) => {
+                let local = |text: String| Text::from(text).fg(Color::LightRed);
+                let name = datum.name();
+                cell(
+                    &format!("{} ({name})", tr("latitude")),
+                    local(nmea.coordinates.lat(position.lat)),
+                );
+                cell(
+                    &format!("{} ({name})", tr("longitude")),
+                    local(nmea.coordinates.lon(position.lon)),
+                );
+            }
+            None => {
+                cell("latitude", nmea.coordinates.lat(position.lat).into());
+                cell("longitude", nmea.coordinates.lon(position.lon).into());
+            }
+        },
+        None => {
+            cell("latitude", "value".into());
+            cell("longitude", "value".into());
+        }
+    }
+    if let Some(datum) = &nmea.datum {
+        let name = Text::from(datum.name());
+        cell(
+            "datum",
+            if datum.is_wgs84() {
+                name
+            } else {
+                name.fg(Color::LightRed).bold()
+            },
+        );
+    }
+    cell(
+        "altitude",
+        match (nmea.alt.get(), nmea.vertical_speed.get()) {
+            (Some(alt), Some(&rate)) => {
+                format!("{alt:.1} m {}", nmea.vertical_speed_unit.format(rate))
+            }
+            (Some(alt), None) => format!("{alt:.1} m"),
+            (None, _) => "value".to_string(),
+        }
+        .into(),
+    );
+    cell("heading", nmea.hdg.clone().into());
+    let autopilot = &nmea.autopilot;
+    if let Some(steer) = autopilot.steer.get() {
+        let variation = nmea.variation.get().copied();
+        // How far the heading is off what the autopilot is told to steer.
+        let off = steer
+            .true_course(variation)
+            .zip(nmea.hdg.get())
+            .map_or_else(String::new, |(steer, heading)| {
+                format!(
+                    " ({:+.0}°)",
+                    (steer - heading + 180.0).rem_euclid(360.0) - 180.0
+                )
+            });
+        let reference = if steer.magnetic { "M" } else { "T" };
+        cell(
+            "steer",
+            format!("{:03.0}°{reference}{off}", steer.degrees).into(),
+        );
+    }
+    if let Some(xte) = autopilot.cross_track.get() {
+        cell(
+            "xte",
+            format!("{:.2} NM steer {}", xte.distance, xte.steer).into(),
+        );
+    }
+    if let Some(destination) = autopilot.destination.get() {
+        let bearing = autopilot
+            .bearing
+            .get()
+            .map_or_else(String::new, |bearing| format!(" {:03.0}°", bearing.degrees));
+        let arrived = if autopilot.arrived.get() == Some(&true) {
+            format!(" {}", tr("arrived"))
+        } else {
+            String::new()
+        };
+        cell(
+            "destination",
+            format!("{destination}{bearing}{arrived}").into(),
+        );
+    }
+    if raw {
+        cell("sog (raw)", fixed(&nmea.sog_raw, 2).into());
+    } else {
+        cell("sog", fixed(&nmea.sog, 1).into());
+    }
+    cell("stw", nmea.stw.clone().into());
+    if raw {
+        cell("cog (raw)", fixed(&nmea.cog_raw, 2).into());
+    } else {
+        cell("cog", fixed(&nmea.cog, 1).into());
+    }
+    cell("fix", nmea.fix_type.clone().into());
+    cell("hdop", fixed(&nmea.hdop, 1).into());
+    cell(
+        "motion",
+        match nmea.sog_raw.get() {
+            Some(_) if nmea.motion.stationary() => tr("stationary").into(),
+            Some(_) => tr("moving").into(),
+            None => "value".into(),
+        },
+    );
+    // Receiver time, advanced by the time since it was received.
+    let utc = nmea
+        .utc
+        .get()
+        .map(|&utc| utc + nmea.utc.last().map_or(Duration::ZERO, |(_, age)| age));
+    cell(
+        "time",
+        utc.map_or_else(
+            || "value".to_string(),
+            |utc| format!("{} {}", clock(utc), crate::clock::zone_name(utc)),
+        )
+        .into(),
+    );
+    if crate::clock::show_utc() {
+        cell(
+            "time (UTC)",
+            utc.map_or_else(|| "value".to_string(), crate::clock::utc_time)
+                .into(),
+        );
+    }
+    cell(
+        "grid",
+        match (nmea.lat.get(), nmea.lon.get()) {
+            (Some(&lat), Some(&lon)) => maidenhead::locator(lat, lon, nmea.locator_pairs),
+            _ => "value".to_string(),
+        }
+        .into(),
+    );
+    cell(
+        "depth",
+        match nmea.depth.get() {
+            Some(&depth) => {
+                let arrow = depth::trend(&nmea.history.depth).map_or("", depth::arrow);
+                format!("{:.1} m {arrow}", nmea.keel.below_keel(depth))
+            }
+            None => "value".to_string(),
+        }
+        .into(),
+    );
+    if true_wind {
+        cell(
+            "true wind",
+            match nmea.true_wind() {
+                Some((angle, speed, Some(direction))) => {
+                    format!("{angle:.0}° {speed:.1} kn from {direction:03.0}°")
+                }
+                Some((angle, speed, None)) => format!("{angle:.0}° {speed:.1} kn"),
+                None => "value".to_string(),
+            }
+            .into(),
+        );
+    } else {
+        cell(
+            "apparent wind",
+            match (nmea.wind_angle.get(), nmea.wind_speed.get()) {
+                (Some(angle), Some(speed)) => format!("{angle:.0}° {speed:.1} kn"),
+                _ => "value".to_string(),
+            }
+            .into(),
+        );
+    }
+    if let Some(&pressure) = nmea.barometer.pressure.get() {
+        let tendency = nmea
+            .barometer
+            .tendency()
+            .map_or_else(String::new, |(change, span)| {
+                let per_3h = change * 3.0 * 3600.0 / span.as_secs_f64();
+                format!(" {} {per_3h:+.1}/3h", tr(barometer::tendency_name(per_3h)))
+            });
+        cell("pressure", format!("{pressure:.1} hPa{tendency}").into());
+        cell(
+            "pressure altitude",
+            format!("{:.0} m", barometer::pressure_altitude(pressure)).into(),
+        );
+    }
+    #[cfg(feature = "extract")]
+    for extract in nmea.extracts.iter() {
+        let value = match (extract.value.get(), &extract.config.unit) {
+            (Some(value), Some(unit)) => format!("{value} {unit}"),
+            (Some(value), None) => value.clone(),
+            (None, _) => "value".to_string(),
+        };
+        cell(&extract.config.label, value.into());
+    }
+    #[cfg(feature = "script")]
+    for (name, value) in nmea.scripts.values() {
+        cell(&name, value.unwrap_or_else(|| "value".to_string()).into());
+    }
+    #[cfg(feature = "script")]
+    if let Some(error) = &nmea.scripts.error {
+        cell(
+            "script error",
+            Text::from(error.clone()).fg(Color::LightRed),
+        );
+    }
+    for (name, value) in nmea.plugins.values() {
+        cell(
+            name,
+            value.map_or("value", String::as_str).to_string().into(),
+        );
+    }
+    if let Some(error) = &nmea.plugins.error {
+        cell(
+            "plugin error",
+            Text::from(error.clone()).fg(Color::LightRed),
+        );
+    }
+    #[cfg(feature = "net")]
+    if let Some(aprs) = &nmea.aprs {
+        cell(
+            "aprs",
+            match (&aprs.error, aprs.last) {
+                (Some(error), _) => Text::from(error.clone()).fg(Color::LightRed),
+                (None, Some(last)) => {
+                    format!("{} beacons, last {}", aprs.beacons, clock(last)).into()
+                }
+                (None, None) => "value".into(),
+            },
+        );
+    }
+    cells
+}
+
+fn render_statistics<'a, T>(frame: &mut Frame, area: Rect, title: &str, value: T)
+where
+    T: Into<Text<'a>>,
+{
+    let block = Block::new().title(title);
+    frame.render_widget(Paragraph::new(value).block(block), area);
+}
+
+/// Formats a value with `decimals` places, or the placeholder when unknown.
+fn fixed(value: &StatusValue<f64>, decimals: usize) -> String {
+    value
+        .get()
+        .map_or_else(|| "value".to_string(), |v| format!("{v:.decimals$}"))
+}
+
+/// True `bearing` seen from own heading, or from own course without one, such as
+/// "45° starboard". Just the true bearing while neither is known.
+fn relative_bearing(nmea: &NmeaStatus, bearing: f64) -> String {
+    let Some(&heading) = nmea.hdg.get().or(nmea.cog.get()) else {
+        return format!("{bearing:03.0}°");
+    };
+    let relative = geo::relative_bearing(bearing, heading);
+    if relative.abs() < 0.5 {
+        tr("ahead").into_owned()
+    } else if relative.abs() >= 179.5 {
+        tr("astern").into_owned()
+    } else {
+        let side = if relative > 0.0 { "starboard" } else { "port" };
+        format!("{:.0}° {}", relative.abs(), tr(side))
+    }
+}
+
+/// Relative bearing and range of the selected target, for the right of its panel title.
+fn target_title(nmea: &NmeaStatus, name: &str, bearing: f64, range: String) -> Line<'static> {
+    Line::from(format!(
+        " {name} {} {range} ",
+        relative_bearing(nmea, bearing)
+    ))
+    .bold()
+    .reversed()
+    .right_aligned()
+}
+
+fn or_dash<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+/// Formats a wall-clock time as `HH:MM:SS` in the zone chosen with `--timezone`.
+fn clock(time: SystemTime) -> String {
+    crate::clock::time(time)
+}
+
+/// Runs the terminal UI until it is quit. Returns why it ended early.
+pub async fn run(args: &Args, config: Config, engine: &Engine) -> Option<Reason> {
+    let terminal = ratatui::init();
+
+    let pinned = args
+        .pin
+        .iter()
+        .chain(&config.display.pinned)
+        .cloned()
+        .collect();
+    let mut app = App::new(
+        engine,
+        map_url(args, &config),
+        args.logbook.clone(),
+        pinned,
+        args.chart_span.into(),
+        config.display.night,
+        args.config.clone(),
+    );
+    app.flash = Flash::new(args.flash.into());
+    app.thresholds = config.thresholds.clone();
+    app.map.set_overlay(config.overlay.clone());
+    app.range_rings = range_rings(&config);
+    app.reload = engine
+        .reload
+        .as_ref()
+        .map(|reload| Arc::clone(&reload.request));
+    let configs = engine.reload.as_ref().map(|reload| reload.config.clone());
+
+    // Spawned so that a panic in the UI also ends in the orderly exit of `main`.
+    let result = tokio::spawn(event_loop(
+        terminal,
+        Arc::clone(&engine.nmea),
+        app,
+        args.clone(),
+        configs,
+    ))
+    .await;
+
+    ratatui::restore();
+
+    match result {
+        Ok(result) => result.expect("Failed to run app."),
+        Err(_) => Some(Reason::Panic),
+    }
+}
+
+fn map_url(args: &Args, config: &Config) -> String {
+    args.map_url
+        .clone()
+        .or_else(|| config.map_url.clone())
+        .unwrap_or_else(|| browser::DEFAULT_MAP_URL.to_string())
+}
+
+fn range_rings(config: &Config) -> Option<f64> {
+    config
+        .display
+        .range_rings
+        .filter(|&miles| miles > 0.0)
+        .map(|miles| miles * geo::NAUTICAL_MILE)
+}
+
+async fn event_loop(
+    mut terminal: Terminal<impl Backend>,
+    nmea: Arc<RwLock<NmeaStatus>>,
+    mut app: App,
+    args: Args,
+    mut configs: Option<watch::Receiver<Option<Arc<Config>>>>,
+) -> Result<Option<Reason>> {
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / 60.0));
+    let mut events = EventStream::new();
+    let shutdown = shutdown::requested();
+    tokio::pin!(shutdown);
+
+    while tokio::select! {
+        reason = &mut shutdown => return Ok(Some(reason)),
+        _ = interval.tick() => {
+            if let Some(configs) = configs.as_mut().filter(|configs| configs.has_changed().unwrap_or(false)) {
+                if let Some(config) = configs.borrow_and_update().clone() {
+                    app.reconfigure(&args, &config);
+                    app.show_message(tr("config reloaded"));
+                }
+            }
+            let nmea = nmea.read().await;
+            app.ring(&nmea.alarms);
+            if app.should_draw() {
+                terminal.draw(|frame| draw(frame, &nmea, &mut app)).expect("Failed to draw terminal.");
+            }
+            true
+        }
+        Some(Ok(event)) = events.next() => {
+            match event {
+                // Windows reports releases too, which would act on every key twice.
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    app.handle_key(key, &mut *nmea.write().await)
+                }
+                _ => true,
+            }
+        }
+    } {}
+
+    Ok(None)
+}
@@ -0,0 +1,46 @@
+//! Night palette: every drawn cell is recoloured dim red on black after the screens have
+//! drawn, so that no widget needs to know about it.
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+
+/// Text that asks for attention, such as alerts.
+const ALERT: Color = Color::Rgb(200, 0, 0);
+const TEXT: Color = Color::Rgb(150, 0, 0);
+const COLORED: Color = Color::Rgb(120, 0, 0);
+const DIM: Color = Color::Rgb(70, 0, 0);
+/// Background of highlighted cells.
+const HIGHLIGHT: Color = Color::Rgb(50, 0, 0);
+
+pub fn apply(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        let mut fg = foreground(cell.fg);
+        let mut bg = if cell.bg == Color::Reset {
+            Color::Black
+        } else {
+            HIGHLIGHT
+        };
+        if cell.modifier.contains(Modifier::REVERSED) {
+            (fg, bg) = (TEXT, HIGHLIGHT);
+        }
+        cell.fg = fg;
+        cell.bg = bg;
+        cell.modifier
+            .remove(Modifier::BOLD | Modifier::REVERSED | Modifier::SLOW_BLINK);
+    }
+}
+
+fn foreground(color: Color) -> Color {
+    match color {
+        Color::Red | Color::LightRed => ALERT,
+        Color::Reset | Color::White | Color::Gray => TEXT,
+        Color::Black | Color::DarkGray => DIM,
+        Color::Rgb(r, g, b) => {
+            let luminance = 0.3 * f64::from(r) + 0.59 * f64::from(g) + 0.11 * f64::from(b);
+            Color::Rgb((40.0 + luminance * 0.5) as u8, 0, 0)
+        }
+        _ => COLORED,
+    }
+}
@@ -0,0 +1,89 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Stylize as _},
+    symbols::Marker,
+    text::{Line as TextLine, Span},
+    widgets::{
+        canvas::{Canvas, Circle, Context, Line},
+        Block,
+    },
+    Frame,
+};
+
+use crate::{i18n::tr, status::NmeaStatus};
+
+use super::map;
+
+const COG_TRUE: Color = Color::Cyan;
+const COG_MAGNETIC: Color = Color::Yellow;
+const HEADING: Color = Color::Green;
+
+/// Compass rose with needles for course over ground, true and, when the variation is
+/// known, magnetic, and for heading.
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus) {
+    let cog = nmea.cog.get().copied();
+    let magnetic = cog
+        .zip(nmea.variation.get())
+        .map(|(cog, variation)| (cog - variation).rem_euclid(360.0));
+    let heading = nmea.hdg.get().copied();
+
+    let degrees = |value: Option<f64>, suffix: &str, color: Color| {
+        value.map(|v| Span::from(format!(" {v:03.0}°{suffix}")).fg(color))
+    };
+    let title = [
+        Some(Span::from(tr("cog"))),
+        degrees(cog, "T", COG_TRUE),
+        degrees(magnetic, "M", COG_MAGNETIC),
+        heading.map(|_| Span::from(format!(" {}", tr("hdg")))),
+        degrees(heading, "", HEADING),
+    ];
+    let block = Block::new().title(TextLine::from(
+        title.into_iter().flatten().collect::<Vec<_>>(),
+    ));
+    let (x_bounds, y_bounds) = map::bounds(block.inner(area), 1.2);
+
+    let canvas = Canvas::default()
+        .block(block)
+        .marker(Marker::Braille)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            ctx.draw(&Circle {
+                x: 0.0,
+                y: 0.0,
+                radius: 1.0,
+                color: Color::DarkGray,
+            });
+            for tick in (0..360).step_by(30) {
+                let length = if tick % 90 == 0 { 0.8 } else { 0.9 };
+                needle(ctx, f64::from(tick), length, 1.0, Color::DarkGray);
+            }
+            for (label, angle) in [("N", 0.0), ("E", 90.0), ("S", 180.0), ("W", 270.0)] {
+                let (x, y) = point(angle, 1.12);
+                ctx.print(x, y, Span::from(label).fg(Color::Gray));
+            }
+            if let Some(magnetic) = magnetic {
+                needle(ctx, magnetic, 0.0, 0.75, COG_MAGNETIC);
+            }
+            if let Some(heading) = heading {
+                needle(ctx, heading, 0.0, 0.6, HEADING);
+            }
+            if let Some(cog) = cog {
+                needle(ctx, cog, 0.0, 0.9, COG_TRUE);
+            }
+        });
+    frame.render_widget(canvas, area);
+}
+
+/// Line along `angle` from `from` to `to` times the radius.
+fn needle(ctx: &mut Context, angle: f64, from: f64, to: f64, color: Color) {
+    let (x1, y1) = point(angle, from);
+    let (x2, y2) = point(angle, to);
+    ctx.draw(&Line::new(x1, y1, x2, y2, color));
+}
+
+/// Canvas coordinates of a bearing in degrees at `radius`, north up.
+fn point(angle: f64, radius: f64) -> (f64, f64) {
+    let angle = angle.to_radians();
+    (radius * angle.sin(), radius * angle.cos())
+}
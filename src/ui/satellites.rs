@@ -0,0 +1,115 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize as _},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Sparkline, Table, TableState},
+    Frame,
+};
+
+use super::{clock, or_dash};
+use crate::{
+    i18n::{self, tr},
+    status::NmeaStatus,
+};
+
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, state: &mut TableState) {
+    let modes = nmea.constellation_modes.get();
+    let almanac = nmea.almanac.coverage(nmea.utc.get().copied());
+    let [fix, almanac_area, table, detail] = Layout::vertical([
+        Constraint::Length(if modes.is_some() { 2 } else { 0 }),
+        Constraint::Length(if almanac.is_empty() { 0 } else { 2 }),
+        Constraint::Min(0),
+        Constraint::Length(8),
+    ])
+    .areas(area);
+
+    if let Some(modes) = modes {
+        let modes = modes
+            .iter()
+            .map(|(constellation, mode)| format!("{constellation} {}", tr(mode)))
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            Paragraph::new(modes.join("  "))
+                .block(Block::new().title(Line::raw(tr("fix by constellation")))),
+            fix,
+        );
+    }
+
+    if !almanac.is_empty() {
+        let coverage = almanac
+            .iter()
+            .map(|c| {
+                let unhealthy = if c.unhealthy > 0 {
+                    format!(" {} {}", c.unhealthy, tr("unhealthy"))
+                } else {
+                    String::new()
+                };
+                let age = c
+                    .age
+                    .map(|age| format!(" ({})", i18n::format("{} weeks old", &[&age])))
+                    .unwrap_or_default();
+                format!(
+                    "{} {}/{} {} {}{age}{unhealthy}",
+                    c.constellation,
+                    c.satellites,
+                    or_dash(c.expected),
+                    tr("week"),
+                    c.week,
+                )
+            })
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            Paragraph::new(coverage.join("  ")).block(Block::new().title(format!(
+                "{} ({} ALM)",
+                tr("almanac"),
+                nmea.almanac.sentences
+            ))),
+            almanac_area,
+        );
+    }
+
+    let satellites = nmea.satellites.iter().collect::<Vec<_>>();
+    let rows = satellites.iter().map(|(id, sat)| {
+        Row::new([
+            id.constellation().to_string(),
+            id.prn.to_string(),
+            or_dash(sat.elevation),
+            or_dash(sat.azimuth),
+            or_dash(sat.snr),
+        ])
+    });
+    let widths = [
+        Constraint::Length(8), // system
+        Constraint::Length(5), // prn
+        Constraint::Length(6), // elevation
+        Constraint::Length(6), // azimuth
+        Constraint::Length(6), // snr
+    ];
+    let table_widget = Table::new(rows, widths)
+        .header(Row::new(["system", "prn", "elev", "azim", "snr"].map(tr)).bold())
+        .highlight_style(Style::new().reversed())
+        .block(Block::new().title(Line::raw(tr("satellites (↑/↓ to select)"))));
+    frame.render_stateful_widget(table_widget, table, state);
+
+    let Some((id, sat)) = state
+        .selected()
+        .and_then(|i| satellites.get(i.min(satellites.len().saturating_sub(1))))
+    else {
+        return;
+    };
+
+    let [times, sparkline] =
+        Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(detail);
+    let title = format!("{} {} {}", id.constellation(), id.prn, tr("snr"));
+    frame.render_widget(
+        Paragraph::new(format!(
+            "rise {}  set {}",
+            sat.rise.map_or_else(|| "-".to_string(), clock),
+            sat.set.map_or_else(|| "-".to_string(), clock),
+        ))
+        .block(Block::new().title(title)),
+        times,
+    );
+    let history = sat.history.iter().copied().collect::<Vec<_>>();
+    frame.render_widget(Sparkline::default().data(&history), sparkline);
+}
@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize as _},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Sparkline, Table},
+    Frame,
+};
+
+use super::or_dash;
+use crate::{
+    battery::Battery,
+    depth,
+    history::{Series, RETENTION},
+    i18n::tr,
+    status::NmeaStatus,
+};
+
+/// Time shown in the depth sparkline.
+const DEPTH_SPAN: Duration = Duration::from_secs(10 * 60);
+
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus) {
+    let engine_height = if nmea.propulsion.is_present(&nmea.sensors) {
+        3
+    } else {
+        0
+    };
+    let batteries = nmea.batteries.iter(&nmea.sensors);
+    let batteries_height = if batteries.is_empty() {
+        0
+    } else {
+        batteries.len() as u16 + 1
+    };
+    let depth_height = if nmea.depth.get().is_some() { 2 } else { 0 };
+    let [depth, engine, batteries_area, area] = Layout::vertical([
+        Constraint::Length(depth_height),
+        Constraint::Length(engine_height),
+        Constraint::Length(batteries_height),
+        Constraint::Min(0),
+    ])
+    .areas(area);
+    if let Some(&value) = nmea.depth.get() {
+        draw_depth(frame, depth, nmea, value);
+    }
+    if engine_height > 0 {
+        draw_engine(frame, engine, nmea);
+    }
+    if !batteries.is_empty() {
+        draw_batteries(frame, batteries_area, &batteries);
+    }
+
+    let rows = nmea.sensors.iter().map(|(id, reading)| {
+        Row::new([
+            nmea.sensors.name(id).to_string(),
+            format!("{:.2}", reading.value),
+            reading.unit.clone(),
+            reading.kind_name().to_string(),
+            format!("{:.0}s", reading.updated_at.elapsed().as_secs_f64()),
+        ])
+    });
+    let widths = [
+        Constraint::Length(24), // name
+        Constraint::Length(10), // value
+        Constraint::Length(5),  // unit
+        Constraint::Length(12), // type
+        Constraint::Length(6),  // age
+    ];
+    let table = Table::new(rows, widths)
+        .header(Row::new(["name", "value", "unit", "type", "age"].map(tr)).bold())
+        .block(Block::new().title(Line::raw(tr("sensors"))));
+    frame.render_widget(table, area);
+}
+
+/// Depth below the keel with its trend, and the last ten minutes as a sparkline.
+fn draw_depth(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, depth: f64) {
+    let below = nmea.keel.below_keel(depth);
+    let reference = if nmea.keel.offset().is_some() {
+        tr("below keel")
+    } else {
+        tr("below transducer")
+    };
+    let trend = depth::trend(&nmea.history.depth).map_or_else(String::new, |trend| {
+        format!("{} {trend:+.1} m/min", depth::arrow(trend))
+    });
+    let style = if nmea.keel.shallow(depth).is_some() {
+        Color::LightRed
+    } else {
+        Color::Reset
+    };
+    let block = Block::new().title(Line::raw(tr("depth")));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [label, sparkline] =
+        Layout::horizontal([Constraint::Length(36), Constraint::Min(0)]).areas(inner);
+    frame.render_widget(
+        Paragraph::new(format!("{below:.1} m {reference} {trend}")).fg(style),
+        label,
+    );
+    let data = sparkline_data(&nmea.history.depth, DEPTH_SPAN, sparkline.width);
+    frame.render_widget(Sparkline::default().data(&data).fg(style), sparkline);
+}
+
+fn draw_engine(frame: &mut Frame, area: Rect, nmea: &NmeaStatus) {
+    let propulsion = &nmea.propulsion;
+    let sensors = &nmea.sensors;
+    let range = nmea
+        .sog
+        .get()
+        .and_then(|&sog| propulsion.range(sensors, sog));
+    let lines = [
+        format!(
+            "rpm {}  fuel {} l/h  on board {} l",
+            or_dash(propulsion.rpm(sensors).map(|v| format!("{v:.0}"))),
+            or_dash(propulsion.fuel_rate(sensors).map(|v| format!("{v:.1}"))),
+            or_dash(propulsion.fuel_level(sensors).map(|v| format!("{v:.0}"))),
+        ),
+        format!(
+            "range {} NM  hours {}",
+            or_dash(range.map(|v| format!("{v:.0}"))),
+            or_dash(propulsion.hours(sensors).map(|v| format!("{v:.1}"))),
+        ),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines.join("\n")).block(Block::new().title(Line::raw(tr("engine")))),
+        area,
+    );
+}
+
+/// One line per battery: voltage, then the last hour as a sparkline scaled between its
+/// lowest and highest reading.
+fn draw_batteries(frame: &mut Frame, area: Rect, batteries: &[Battery]) {
+    let block = Block::new().title(Line::raw(tr("batteries")));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    for (i, battery) in batteries.iter().enumerate() {
+        let row = Rect {
+            y: inner.y + i as u16,
+            height: 1,
+            ..inner
+        };
+        let [label, sparkline] =
+            Layout::horizontal([Constraint::Length(36), Constraint::Min(0)]).areas(row);
+        let text = format!("{:<24} {:>6.2} V", battery.name, battery.voltage);
+        let style = if battery.is_low() {
+            Color::LightRed
+        } else {
+            Color::Reset
+        };
+        frame.render_widget(Paragraph::new(text).fg(style), label);
+
+        let data = battery
+            .history
+            .map(|history| sparkline_data(history, RETENTION, sparkline.width))
+            .unwrap_or_default();
+        frame.render_widget(Sparkline::default().data(&data).fg(style), sparkline);
+    }
+}
+
+/// The samples of `series` within `span` averaged down to one value per column, in
+/// hundredths above the lowest.
+fn sparkline_data(series: &Series, span: Duration, width: u16) -> Vec<u64> {
+    let values = series.recent(span).map(|(_, v)| v).collect::<Vec<_>>();
+    let chunk = values.len().div_ceil(usize::from(width).max(1)).max(1);
+    let means = values
+        .chunks(chunk)
+        .map(|chunk| chunk.iter().sum::<f64>() / chunk.len() as f64)
+        .collect::<Vec<_>>();
+    let min = means.iter().copied().fold(f64::INFINITY, f64::min);
+    means
+        .iter()
+        .map(|v| ((v - min) * 100.0) as u64 + 1)
+        .collect()
+}
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize as _},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    diagnostics::Latency,
+    i18n::tr,
+    replay::{self, Replay},
+    source::SourceState,
+    status::NmeaStatus,
+};
+
+use super::or_dash;
+
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, bookmarks: &mut TableState) {
+    let [area, diagnostics, replay_area] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(5),
+        Constraint::Length(if nmea.replay.is_some() { 12 } else { 0 }),
+    ])
+    .areas(area);
+    draw_diagnostics(frame, diagnostics, nmea);
+    if let Some(replay) = &nmea.replay {
+        draw_replay(frame, replay_area, replay, bookmarks);
+    }
+
+    let active = nmea.active_source();
+    let rows = nmea.sources.iter().enumerate().map(|(index, source)| {
+        let row = Row::new([
+            if active == Some(index) { "*" } else { "" }.to_string(),
+            source.name.clone(),
+            source.state.to_string(),
+            format!("{:.1}/s", source.rate()),
+            source.lines.to_string(),
+            source.errors.to_string(),
+            or_dash(
+                source
+                    .last_position
+                    .map(|(lat, lon)| nmea.coordinates.position(lat, lon)),
+            ),
+            or_dash(
+                source
+                    .position_at
+                    .map(|at| format!("{:.0}s", at.elapsed().as_secs_f64())),
+            ),
+        ]);
+        match source.state {
+            SourceState::Failed(_) => row.fg(Color::LightRed),
+            SourceState::Closed | SourceState::Connecting => row.fg(Color::DarkGray),
+            SourceState::Connected => row,
+        }
+    });
+    let widths = [
+        Constraint::Length(1),  // active
+        Constraint::Length(30), // name
+        Constraint::Length(20), // state
+        Constraint::Length(8),  // rate
+        Constraint::Length(8),  // lines
+        Constraint::Length(6),  // errors
+        Constraint::Length(32), // position
+        Constraint::Length(6),  // age
+    ];
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new([
+                "", "name", "state", "rate", "lines", "errors", "position", "age",
+            ])
+            .bold(),
+        )
+        .block(Block::new().title(Line::raw(tr("sources"))));
+    frame.render_widget(table, area);
+}
+
+/// The monitor's own CPU, memory, queue depths and decode latency.
+fn draw_diagnostics(frame: &mut Frame, area: Rect, nmea: &NmeaStatus) {
+    let diagnostics = &nmea.diagnostics;
+    let megabytes = |bytes: u64| bytes as f64 / (1 << 20) as f64;
+    let memory = match diagnostics.memory {
+        Some((first, now)) => Line::from(format!(
+            "memory {:.1} MB ({:+.1} MB since start)",
+            megabytes(now),
+            megabytes(now) - megabytes(first)
+        )),
+        None => Line::from("memory -"),
+    };
+    let latency = |latency: &Latency| match latency.mean() {
+        Some(mean) => format!("{mean:.1?} mean, {:.1?} max", latency.max),
+        None => "-".to_string(),
+    };
+    let lines = vec![
+        Line::from(format!(
+            "cpu {}  up {}",
+            or_dash(diagnostics.cpu.map(|cpu| format!("{cpu:.1}%"))),
+            humantime::format_duration(Duration::from_secs(diagnostics.uptime().as_secs()))
+        )),
+        memory,
+        Line::from(format!(
+            "queues: read {}/{}, decode {}/{} ({} dropped), subscribers {}",
+            diagnostics.received.0,
+            diagnostics.received.1,
+            nmea.queued.0,
+            nmea.queued.1,
+            nmea.queue_drops,
+            diagnostics.subscribers,
+        )),
+        Line::from(format!(
+            "queued {}, decode {} ({} lines/s)",
+            latency(&diagnostics.wait),
+            latency(&diagnostics.decode),
+            diagnostics.decode.count,
+        )),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::new().title(Line::raw(tr("diagnostics")))),
+        area,
+    );
+}
+
+/// Playback position and the bookmarks, selected with ↑/↓ and jumped to with Enter.
+fn draw_replay(frame: &mut Frame, area: Rect, replay: &Replay, state: &mut TableState) {
+    let [progress, table] =
+        Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(area);
+    let percent = if replay.length > 0 {
+        replay.offset as f64 / replay.length as f64 * 100.0
+    } else {
+        100.0
+    };
+    frame.render_widget(
+        Paragraph::new(format!(
+            "{}  {} / {} B ({percent:.0}%)  ×{}  {} {}  k bookmark, Enter jump",
+            or_dash(replay.time.map(replay::format_time)),
+            replay.offset,
+            replay.length,
+            replay.speed,
+            replay.discontinuities,
+            tr("time jumps"),
+        ))
+        .block(Block::new().title(Line::raw(tr("replay")))),
+        progress,
+    );
+    let rows = replay.bookmarks.iter().map(|bookmark| {
+        Row::new([
+            or_dash(bookmark.time.map(replay::format_time)),
+            bookmark.offset.to_string(),
+        ])
+    });
+    let widths = [
+        Constraint::Length(10), // time
+        Constraint::Length(12), // offset
+    ];
+    let table_widget = Table::new(rows, widths)
+        .header(Row::new(["time", "offset"].map(tr)).bold())
+        .highlight_style(Style::new().reversed())
+        .block(Block::new().title(Line::raw(tr("bookmarks (↑/↓ to select)"))));
+    frame.render_stateful_widget(table_widget, table, state);
+}
@@ -0,0 +1,106 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize as _},
+    widgets::{Bar, BarChart, BarGroup, Block, Row, Table},
+    Frame,
+};
+
+use crate::{
+    history::{Histogram, Series, Window},
+    i18n::{self, tr},
+    status::{NmeaStatus, StatusValue},
+};
+
+pub fn draw(frame: &mut Frame, area: Rect, nmea: &NmeaStatus, window: Window) {
+    let history = &nmea.history;
+    let satellites = nmea.satellites_used.get().map(|&n| f64::from(n));
+    let rate = nmea.sources.iter().map(|source| source.rate()).sum::<f64>();
+    let quantities: [(&str, &Series, Option<f64>, usize); 8] = [
+        ("sog (kn)", &history.sog, current(&nmea.sog_raw), 1),
+        ("stw (kn)", &history.stw, current(&nmea.stw), 1),
+        ("altitude (m)", &history.alt, current(&nmea.alt), 1),
+        ("depth (m)", &history.depth, current(&nmea.depth), 1),
+        (
+            "wind (kn)",
+            &history.wind_speed,
+            current(&nmea.wind_speed),
+            1,
+        ),
+        ("hdop", &history.hdop, current(&nmea.hdop), 1),
+        ("satellites used", &history.satellites, satellites, 0),
+        ("lines/s", &history.rate, Some(rate), 1),
+    ];
+
+    let [table, histogram] = Layout::vertical([
+        Constraint::Length(quantities.len() as u16 + 2),
+        Constraint::Min(0),
+    ])
+    .areas(area);
+
+    let rows = quantities
+        .into_iter()
+        .map(|(name, series, current, decimals)| {
+            let summary = series.summary(window);
+            let number = |value: Option<f64>| {
+                value.map_or_else(|| "-".to_string(), |v| format!("{v:.decimals$}"))
+            };
+            Row::new([
+                tr(name).into_owned(),
+                number(current),
+                number(summary.map(|s| s.min)),
+                number(summary.map(|s| s.max)),
+                number(summary.map(|s| s.mean())),
+            ])
+        });
+    let widths = [
+        Constraint::Length(16),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ];
+    frame.render_widget(
+        Table::new(rows, widths)
+            .header(Row::new(["", "current", "min", "max", "mean"].map(tr)).bold())
+            .block(Block::new().title(i18n::format(
+                "statistics, {} (s to change)",
+                &[&tr(window.label())],
+            ))),
+        table,
+    );
+    draw_histogram(frame, histogram, &history.sog_histogram);
+}
+
+/// Share of the session spent at each speed over ground.
+fn draw_histogram(frame: &mut Frame, area: Rect, histogram: &Histogram) {
+    let total = histogram.total().max(1);
+    let bars = histogram
+        .bins
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            let low = i as f64 * histogram.width;
+            Bar::default()
+                .value(count)
+                .label(format!("{low}").into())
+                .text_value(format!("{}%", count * 100 / total))
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        BarChart::default()
+            .block(Block::new().title(i18n::format(
+                "time at sog, bins of {} kn, session",
+                &[&histogram.width],
+            )))
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(4)
+            .bar_gap(1)
+            .bar_style(Style::new().fg(Color::Cyan))
+            .value_style(Style::new().black().on_cyan()),
+        area,
+    );
+}
+
+fn current(value: &StatusValue<f64>) -> Option<f64> {
+    value.get().copied()
+}
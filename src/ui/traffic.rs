@@ -0,0 +1,164 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize as _},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{
+        canvas::{Canvas, Points},
+        Block, Paragraph, Row, Table, TableState,
+    },
+    Frame,
+};
+
+use super::{map, or_dash, target_title};
+use crate::{
+    i18n::tr,
+    status::NmeaStatus,
+    traffic::{Aircraft, AlarmLevel},
+};
+
+/// Smallest distance from own position to the edge of the radar.
+const MIN_RANGE: f64 = 2000.0;
+
+pub fn draw(
+    frame: &mut Frame,
+    area: Rect,
+    nmea: &NmeaStatus,
+    state: &mut TableState,
+    rings: Option<f64>,
+) {
+    let [left, radar] =
+        Layout::horizontal([Constraint::Length(86), Constraint::Min(0)]).areas(area);
+    let [status, table] = Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(left);
+
+    let status_line = match nmea.traffic.flarm() {
+        Some(flarm) => format!(
+            "{} {}  tx {}  gps {}  alarm {}{}",
+            flarm.received,
+            tr("received"),
+            if flarm.transmitting { "on" } else { "off" },
+            if flarm.gps { "ok" } else { "none" },
+            flarm.alarm.as_str(),
+            match (flarm.relative_bearing, flarm.distance) {
+                (Some(bearing), Some(distance)) if flarm.alarm != AlarmLevel::None => {
+                    format!(" at {bearing:+.0}° {distance:.0} m")
+                }
+                _ => String::new(),
+            },
+        ),
+        None => tr("no FLARM status").into_owned(),
+    };
+    frame.render_widget(
+        Paragraph::new(status_line).block(Block::new().title(Line::raw(tr("flarm")))),
+        status,
+    );
+
+    let aircraft = nmea.traffic.by_distance();
+    let rows = aircraft.iter().map(|aircraft| {
+        let row = Row::new([
+            aircraft.id.clone(),
+            or_dash(aircraft.callsign.as_deref()),
+            or_dash(aircraft.kind),
+            or_dash(aircraft.distance().map(|d| format!("{d:.0}"))),
+            or_dash(aircraft.bearing().map(|b| format!("{b:03.0}"))),
+            or_dash(aircraft.relative_altitude.map(|v| format!("{v:+.0}"))),
+            or_dash(aircraft.ground_speed.map(|v| format!("{v:.0}"))),
+            or_dash(aircraft.climb_rate.map(|v| format!("{v:+.1}"))),
+            aircraft.alarm.as_str().to_string(),
+        ]);
+        match aircraft.alarm {
+            AlarmLevel::None => row,
+            AlarmLevel::Low => row.fg(Color::Yellow),
+            AlarmLevel::Important | AlarmLevel::Urgent => row.fg(Color::LightRed),
+        }
+    });
+    let widths = [
+        Constraint::Length(8),  // id
+        Constraint::Length(9),  // callsign
+        Constraint::Length(12), // type
+        Constraint::Length(7),  // distance
+        Constraint::Length(5),  // bearing
+        Constraint::Length(7),  // relative altitude
+        Constraint::Length(5),  // speed
+        Constraint::Length(6),  // climb
+        Constraint::Length(10), // alarm
+    ];
+    let selected = state
+        .selected()
+        .and_then(|i| aircraft.get(i.min(aircraft.len().saturating_sub(1))));
+    let mut block = Block::new().title(Line::raw(tr("traffic (↑/↓ to select)")));
+    if let Some(target) = selected {
+        if let (Some(bearing), Some(distance)) = (target.bearing(), target.distance()) {
+            block = block.title(target_title(
+                nmea,
+                target.callsign.as_deref().unwrap_or(&target.id),
+                bearing,
+                format!("{distance:.0} m"),
+            ));
+        }
+    }
+    let table_widget = Table::new(rows, widths)
+        .header(
+            Row::new([
+                "id", "call", "type", "dist m", "brg", "alt m", "kn", "m/s", "alarm",
+            ])
+            .bold(),
+        )
+        .highlight_style(Style::new().reversed())
+        .block(block);
+    frame.render_stateful_widget(table_widget, table, state);
+
+    let selected = selected.map(|aircraft| aircraft.id.as_str());
+    draw_radar(frame, radar, &aircraft, selected, rings);
+}
+
+/// Aircraft around own position, north up, with range rings every `rings` meters.
+fn draw_radar(
+    frame: &mut Frame,
+    area: Rect,
+    aircraft: &[&Aircraft],
+    selected: Option<&str>,
+    rings: Option<f64>,
+) {
+    let range = aircraft
+        .iter()
+        .filter_map(|aircraft| aircraft.relative)
+        .map(|(x, y)| x.abs().max(y.abs()))
+        .fold(MIN_RANGE, f64::max)
+        * 1.2;
+    let block = Block::bordered().title(Line::raw(tr("radar (north up)")));
+    let (x_bounds, y_bounds) = map::bounds(block.inner(area), range);
+    let canvas = Canvas::default()
+        .block(block)
+        .marker(Marker::Braille)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            map::draw_rings(ctx, (0.0, 0.0), x_bounds, y_bounds, rings);
+            for aircraft in aircraft {
+                let Some((x, y)) = aircraft.relative else {
+                    continue;
+                };
+                let color = match aircraft.alarm {
+                    _ if Some(aircraft.id.as_str()) == selected => Color::LightCyan,
+                    AlarmLevel::None => Color::Yellow,
+                    _ => Color::LightRed,
+                };
+                ctx.draw(&Points {
+                    coords: &[(x, y)],
+                    color,
+                });
+                let label = match aircraft.relative_altitude {
+                    Some(alt) => format!("{alt:+.0}"),
+                    None => aircraft.id.clone(),
+                };
+                ctx.print(x, y, Span::from(label).fg(color));
+            }
+            ctx.draw(&Points {
+                coords: &[(0.0, 0.0)],
+                color: Color::White,
+            });
+            map::draw_scale_bar(ctx, x_bounds, y_bounds);
+        });
+    frame.render_widget(canvas, area);
+}
@@ -0,0 +1,107 @@
+//! `validate`: checks the framing, checksum and parseability of every line of a log
+//! without decoding it into a status, for data pipelines that should reject bad logs.
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use anyhow::{Context as _, Result};
+
+use crate::{
+    extended, input, n2k, sbs,
+    sentence::{Rejection, Sentence},
+};
+
+#[derive(Default, Debug)]
+struct Counts {
+    lines: u64,
+    overlong: u64,
+    framing: u64,
+    checksum: u64,
+    parse: u64,
+    /// Sentences without a checksum, which are accepted but cannot be checked.
+    unchecked: u64,
+}
+
+impl Counts {
+    fn errors(&self) -> u64 {
+        self.overlong + self.framing + self.checksum + self.parse
+    }
+}
+
+/// Prints each bad line with its number, then the counts. Returns whether there were
+/// no more than `max_errors` errors.
+pub fn run(log: &Path, max_errors: u64) -> Result<bool> {
+    let file = File::open(log).with_context(|| format!("Failed to open {}", log.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut counts = Counts::default();
+    let mut buf = Vec::new();
+    let mut number = 0;
+    // The rest of a line cut at the maximum length, which was reported already.
+    let mut rest = false;
+    // The `\n` of a `\r\n` ending comes as a line of its own, which is not numbered.
+    let mut after_cr = false;
+    loop {
+        buf.clear();
+        if input::read_line_blocking(&mut reader, &mut buf)? == 0 {
+            break;
+        }
+        let cut = input::is_cut(&buf);
+        if std::mem::replace(&mut rest, cut) {
+            continue;
+        }
+        if std::mem::replace(&mut after_cr, buf.ends_with(b"\r")) && buf == b"\n" {
+            continue;
+        }
+        number += 1;
+        if buf.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        counts.lines += 1;
+        let line = extended::strip(input::extract_text(&buf).0);
+        let error = if cut {
+            counts.overlong += 1;
+            Some(format!("longer than {} bytes", input::MAX_LINE_LEN))
+        } else {
+            match Sentence::check(line) {
+                Err(Rejection::Framing)
+                    if n2k::parse(line).is_none() && sbs::parse(line).is_none() =>
+                {
+                    counts.framing += 1;
+                    Some("not a sentence".to_string())
+                }
+                Err(Rejection::Checksum) => {
+                    counts.checksum += 1;
+                    Some("bad checksum".to_string())
+                }
+                Ok(_) => match nmea::parse_str(line) {
+                    Err(nmea::Error::ParsingError(e)) => {
+                        counts.parse += 1;
+                        Some(format!("does not parse: {e}"))
+                    }
+                    _ => {
+                        if !line.contains('*') {
+                            counts.unchecked += 1;
+                        }
+                        None
+                    }
+                },
+                Err(Rejection::Framing) => None,
+            }
+        };
+        if let Some(error) = error {
+            println!("{}:{number}: {error}: {line}", log.display());
+        }
+    }
+
+    println!(
+        "{} lines: {} errors ({} not sentences, {} bad checksums, {} not parsed, {} too long), \
+         {} without checksum",
+        counts.lines,
+        counts.errors(),
+        counts.framing,
+        counts.checksum,
+        counts.parse,
+        counts.overlong,
+        counts.unchecked,
+    );
+    Ok(counts.errors() <= max_errors)
+}
@@ -0,0 +1,36 @@
+//! Positions marked by the user.
+
+use std::time::SystemTime;
+
+#[cfg_attr(not(any(feature = "tui", feature = "export")), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct Waypoint {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    #[cfg_attr(not(feature = "export"), allow(dead_code))]
+    pub alt: Option<f64>,
+    pub created_at: SystemTime,
+}
+
+#[derive(Default, Debug)]
+pub struct Waypoints {
+    waypoints: Vec<Waypoint>,
+}
+
+impl Waypoints {
+    pub fn add(&mut self, waypoint: Waypoint) {
+        self.waypoints.push(waypoint);
+    }
+
+    /// Name suggested for the next waypoint.
+    pub fn next_name(&self) -> String {
+        format!("WPT{:03}", self.waypoints.len() + 1)
+    }
+
+    /// Waypoints, oldest first.
+    #[cfg(any(feature = "tui", feature = "export"))]
+    pub fn iter(&self) -> impl Iterator<Item = &Waypoint> {
+        self.waypoints.iter()
+    }
+}
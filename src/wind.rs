@@ -0,0 +1,89 @@
+//! True wind from the apparent wind measured on board and the boat's own motion.
+
+use crate::geo;
+
+/// True wind angle relative to the bow in degrees (0 to 360) and speed, from the
+/// apparent wind and the boat speed in the same unit.
+///
+/// The boat's motion adds a headwind of its own speed, which is taken away again.
+#[cfg(feature = "tui")]
+pub fn true_wind(apparent_angle: f64, apparent_speed: f64, boat_speed: f64) -> (f64, f64) {
+    // Vectors of where the wind blows from, in the boat's frame with the bow up.
+    let (x, y) = geo::displacement(apparent_angle, apparent_speed);
+    geo::course_and_length((x, y - boat_speed))
+}
+
+/// Speed in knots from an MWV speed and its unit letter.
+pub fn knots(speed: f64, unit: &str) -> Option<f64> {
+    match unit {
+        "N" => Some(speed),
+        "K" => Some(speed / 1.852),
+        "M" => Some(speed / geo::KNOT),
+        "S" => Some(speed * 1.609_344 / 1.852),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "tui")]
+    fn assert_wind((angle, speed): (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (angle - expected.0).abs() < 1e-6 && (speed - expected.1).abs() < 1e-6,
+            "got {angle}° {speed}, expected {}° {}",
+            expected.0,
+            expected.1
+        );
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn head_wind_slower_boat() {
+        assert_wind(true_wind(0.0, 10.0, 4.0), (0.0, 6.0));
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn head_wind_faster_boat() {
+        // The apparent head wind is all the boat's own; the true wind is from astern.
+        assert_wind(true_wind(0.0, 3.0, 5.0), (180.0, 2.0));
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn beam_wind() {
+        let angle = 10.0_f64.atan2(-5.0).to_degrees();
+        assert_wind(true_wind(90.0, 10.0, 5.0), (angle, 125.0_f64.sqrt()));
+        assert_wind(
+            true_wind(270.0, 10.0, 5.0),
+            (360.0 - angle, 125.0_f64.sqrt()),
+        );
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn following_wind() {
+        assert_wind(true_wind(180.0, 5.0, 5.0), (180.0, 10.0));
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn stationary_boat() {
+        assert_wind(true_wind(45.0, 12.0, 0.0), (45.0, 12.0));
+    }
+
+    #[test]
+    fn speed_units() {
+        let close = |value: Option<f64>, expected: f64| {
+            value.is_some_and(|value| (value - expected).abs() < 1e-9)
+        };
+        assert!(close(knots(10.0, "N"), 10.0));
+        assert!(close(knots(1.852, "K"), 1.0));
+        assert!(close(knots(geo::KNOT, "M"), 1.0));
+        assert!(close(knots(1.852, "S"), 1.609_344));
+        assert_eq!(knots(10.0, "X"), None);
+        assert_eq!(knots(10.0, ""), None);
+    }
+}
@@ -0,0 +1,33 @@
+//! Runs every log in `tests/golden` through `--check` against the expectation file of the
+//! same name.
+
+use std::{path::Path, process::Command};
+
+#[test]
+fn golden_logs() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut checked = 0;
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let log = entry.unwrap().path();
+        if log.extension().and_then(|e| e.to_str()) != Some("nmea") {
+            continue;
+        }
+        let expect = log.with_extension("json");
+        let output = Command::new(env!("CARGO_BIN_EXE_nmea-monitor"))
+            .arg("--check")
+            .arg(&log)
+            .arg("--expect")
+            .arg(&expect)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{} does not match {}:\n{}",
+            log.display(),
+            expect.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        checked += 1;
+    }
+    assert!(checked > 0, "no golden logs in {}", dir.display());
+}